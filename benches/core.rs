@@ -0,0 +1,120 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use corrosion::prelude::{fetch_and_decode, Cpu};
+
+const PROGRAM_START: u16 = 0xC000;
+const DECODE_STREAM_SIZE: u16 = 0x1000;
+const NOP_RUN: usize = 0x1000;
+
+const ILLEGAL_OPCODES: [u8; 11] = [0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD];
+
+// Memory regions exercised by the dispatch benchmark: VRAM, WRAM, OAM and an IO register
+const DISPATCH_ADDRESSES: [u16; 4] = [0x8010, 0xC010, 0xFE10, 0xFF40];
+
+fn load_program(cpu: &mut Cpu, program: &[u8]) {
+	for (offset, byte) in program.iter().enumerate() {
+		cpu.write_byte(PROGRAM_START + offset as u16, *byte)
+			.expect("Write program to RAM");
+	}
+	cpu.set_pc(PROGRAM_START);
+}
+
+fn opcode_stream() -> Vec<u8> {
+	// Fixed-seed LCG, so every run decodes the exact same stream
+	let mut state: u32 = 0x1234_5678;
+	let mut stream = Vec::with_capacity(DECODE_STREAM_SIZE.into());
+
+	while stream.len() < DECODE_STREAM_SIZE.into() {
+		state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+		let byte = (state >> 24) as u8;
+		if !ILLEGAL_OPCODES.contains(&byte) {
+			stream.push(byte);
+		}
+	}
+
+	stream
+}
+
+fn decode(c: &mut Criterion) {
+	let mut cpu = Cpu::new();
+	load_program(&mut cpu, &opcode_stream());
+	let stream_end = PROGRAM_START + DECODE_STREAM_SIZE - 4;
+
+	let mut group = c.benchmark_group("decode");
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("random_stream", |b| {
+		b.iter(|| {
+			if cpu.current_pc() >= stream_end {
+				cpu.set_pc(PROGRAM_START);
+			}
+			black_box(fetch_and_decode(&mut cpu).expect("Decode instruction"))
+		})
+	});
+	group.finish();
+}
+
+fn execute(c: &mut Criterion) {
+	let program = [
+		0x3C, // inc A
+		0x80, // add A, B
+		0xA9, // xor C
+		0x18, 0xFB, // jr -5
+	];
+	let mut cpu = Cpu::new();
+	load_program(&mut cpu, &program);
+
+	let mut group = c.benchmark_group("execute");
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("register_arithmetic_loop", |b| {
+		b.iter(|| {
+			let instruction = fetch_and_decode(&mut cpu).expect("Decode instruction");
+			instruction.execute(&mut cpu).expect("Execute instruction");
+		})
+	});
+
+	// Fetch, decode and execute overhead alone
+	let mut cpu = Cpu::new();
+	load_program(&mut cpu, &[0x00; NOP_RUN]);
+	group.throughput(Throughput::Elements(NOP_RUN as u64));
+	group.bench_function("nop_run", |b| {
+		b.iter(|| {
+			cpu.set_pc(PROGRAM_START);
+			for _ in 0..NOP_RUN {
+				let instruction = fetch_and_decode(&mut cpu).expect("Decode instruction");
+				instruction.execute(&mut cpu).expect("Execute instruction");
+			}
+		})
+	});
+	group.finish();
+}
+
+fn memory_dispatch(c: &mut Criterion) {
+	let mut group = c.benchmark_group("memory_dispatch");
+	group.throughput(Throughput::Elements(DISPATCH_ADDRESSES.len() as u64));
+
+	group.bench_function("read_byte", |b| {
+		let cpu = Cpu::new();
+		b.iter(|| {
+			for address in DISPATCH_ADDRESSES {
+				black_box(cpu.read_byte(black_box(address)).expect("Read byte"));
+			}
+		})
+	});
+
+	group.bench_function("write_byte", |b| {
+		b.iter_batched_ref(
+			Cpu::new,
+			|cpu| {
+				for address in DISPATCH_ADDRESSES {
+					cpu.write_byte(black_box(address), black_box(0x12)).expect("Write byte");
+				}
+			},
+			BatchSize::LargeInput,
+		)
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, decode, execute, memory_dispatch);
+criterion_main!(benches);
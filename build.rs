@@ -0,0 +1,30 @@
+use std::path::{Path, PathBuf};
+
+// Path to a boot ROM dump to embed instead of the bundled stub, which isn't distributed with the sources
+const BOOT_ROM_ENV: &str = "CORROSION_BOOT_ROM";
+const BOOT_ROM_SIZE: u64 = 0x100;
+
+fn main() {
+	println!("cargo::rustc-check-cfg=cfg(external_boot_rom)");
+	println!("cargo::rerun-if-env-changed={BOOT_ROM_ENV}");
+
+	let Some(boot_rom) = std::env::var_os(BOOT_ROM_ENV) else {
+		return;
+	};
+	let boot_rom = Path::new(&boot_rom);
+	println!("cargo::rerun-if-changed={}", boot_rom.display());
+
+	let size = std::fs::metadata(boot_rom)
+		.unwrap_or_else(|err| panic!("Can't read the boot ROM at {}: {err}", boot_rom.display()))
+		.len();
+	assert_eq!(
+		size,
+		BOOT_ROM_SIZE,
+		"The boot ROM at {} should be {BOOT_ROM_SIZE} bytes",
+		boot_rom.display()
+	);
+
+	let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+	std::fs::copy(boot_rom, out_dir.join("boot_rom.bin")).expect("Copy the boot ROM");
+	println!("cargo::rustc-cfg=external_boot_rom");
+}
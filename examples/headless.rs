@@ -0,0 +1,76 @@
+// Runs the core without any frontend and prints where it ended up.
+//
+// Usage: cargo run --example headless --no-default-features -- [instructions]
+//
+// There is no cartridge loading yet, so this runs the bootstrap ROM for the given number of instructions (100000 by
+// default) and prints the CPU state plus a hash of VRAM, which is what the bootstrap ROM draws the logo into. The
+// embedded stub doesn't draw anything, pass the path to a boot ROM dump as the second argument to run that instead.
+
+use corrosion::prelude::{BitFlags, Cpu, DoubleRegisters, ExecutionError, BOOTSTRAP_RAM_SIZE};
+
+const DEFAULT_INSTRUCTIONS: u64 = 100_000;
+const VRAM: std::ops::Range<u16> = 0x8000..0xA000;
+
+// FNV-1a, stable across runs and Rust versions unlike the std hasher
+fn vram_hash(cpu: &Cpu) -> Result<u64, ExecutionError> {
+	let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+	for address in VRAM {
+		hash ^= u64::from(cpu.peek_byte(address)?);
+		hash = hash.wrapping_mul(0x0100_0000_01B3);
+	}
+
+	Ok(hash)
+}
+
+fn main() -> Result<(), String> {
+	let instructions = match std::env::args().nth(1) {
+		Some(arg) => arg.parse().map_err(|_| format!("Invalid instruction count: {arg}"))?,
+		None => DEFAULT_INSTRUCTIONS,
+	};
+
+	let mut cpu = match std::env::args().nth(2) {
+		Some(path) => {
+			let boot_rom = std::fs::read(&path).map_err(|err| format!("Can't read the boot ROM at {path}: {err}"))?;
+			let boot_rom: [u8; BOOTSTRAP_RAM_SIZE] = boot_rom
+				.try_into()
+				.map_err(|_| format!("The boot ROM should be {BOOTSTRAP_RAM_SIZE} bytes"))?;
+			Cpu::with_boot_rom(boot_rom)
+		}
+		None => Cpu::new(),
+	};
+	let mut cycles: u64 = 0;
+	for executed in 0..instructions {
+		match cpu.step() {
+			Ok(outcome) => cycles += u64::from(outcome.cycles()),
+			Err(err) => {
+				println!("Stopped after {executed} instructions: {err}");
+				break;
+			}
+		}
+	}
+
+	println!("Cycles: {cycles}");
+	println!("PC: {:#06X}  SP: {:#06X}", cpu.current_pc(), cpu.current_sp());
+	for register in [
+		DoubleRegisters::AF,
+		DoubleRegisters::BC,
+		DoubleRegisters::DE,
+		DoubleRegisters::HL,
+	] {
+		println!("{register}: {:#06X}", cpu.read_double_register(register));
+	}
+	for flag in [
+		BitFlags::Zero,
+		BitFlags::Subtraction,
+		BitFlags::HalfCarry,
+		BitFlags::Carry,
+	] {
+		println!("{flag:?}: {}", cpu.read_flag(flag));
+	}
+	println!("IME: {}", cpu.ime());
+
+	let hash = vram_hash(&cpu).map_err(|err| err.to_string())?;
+	println!("VRAM hash: {hash:#018X}");
+
+	Ok(())
+}
@@ -0,0 +1,63 @@
+// Skeleton for embedding the core in a custom frontend, without SDL.
+//
+// Usage: cargo run --example minimal_frontend --no-default-features -- [frames]
+//
+// A frontend owns the CPU, runs it in frame sized slices, paces frames against a HostClock and presents the result.
+// The presentation layer here only counts frames. Input injection and audio pulling will plug into the same loop
+// once the joypad and APU are emulated.
+
+use std::time::Duration;
+
+use corrosion::prelude::{Cpu, FramePacer, HostClock, SystemClock, CYCLES_PER_FRAME};
+
+const DEFAULT_FRAMES: u64 = 60;
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+trait Presenter {
+	fn present(&mut self, cpu: &Cpu);
+}
+
+#[derive(Default)]
+struct FrameCounter {
+	frames: u64,
+}
+
+impl Presenter for FrameCounter {
+	fn present(&mut self, _cpu: &Cpu) {
+		self.frames += 1;
+	}
+}
+
+fn main() -> Result<(), String> {
+	let frames = match std::env::args().nth(1) {
+		Some(arg) => arg.parse().map_err(|_| format!("Invalid frame count: {arg}"))?,
+		None => DEFAULT_FRAMES,
+	};
+
+	let mut cpu = Cpu::new();
+	let mut presenter = FrameCounter::default();
+	let mut pacer = FramePacer::new(SystemClock::new(), FRAME_DURATION);
+
+	// Cycles the last frame ran past its budget, taken off the next one
+	let mut overshoot = 0;
+	for frame in 0..frames {
+		match cpu.run_cycles(CYCLES_PER_FRAME - overshoot) {
+			Ok(cycles) => overshoot = cycles,
+			Err(err) => {
+				println!("Stopped during frame {frame}: {err}");
+				break;
+			}
+		}
+		presenter.present(&cpu);
+		pacer.wait_for_next_frame();
+	}
+
+	println!(
+		"Presented {} frames in {:?}, PC at {:#06X}",
+		presenter.frames,
+		pacer.clock().now(),
+		cpu.current_pc()
+	);
+
+	Ok(())
+}
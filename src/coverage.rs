@@ -0,0 +1,142 @@
+use std::fmt::{Display, Formatter};
+
+use crate::decoder::table::{opcode_table, prefixed_opcode_table};
+use crate::hardware::cpu::OPCODE_COUNTS_SIZE;
+
+// Counted as the opcode following it instead
+const CB_PREFIX: usize = 0xCB;
+const PREFIXED_START: usize = 0x100;
+
+/// Which legal opcodes executed at least once, from the counts gathered with `Cpu::set_opcode_profiling`.
+/// Collecting them costs nothing while profiling is off.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageReport {
+	covered: usize,
+	total: usize,
+	missing: Vec<(&'static str, Vec<u16>)>,
+}
+
+impl CoverageReport {
+	pub fn new(counts: &[u64; OPCODE_COUNTS_SIZE]) -> Self {
+		let mut covered = 0;
+		let mut total = 0;
+		let mut missing: Vec<(&'static str, Vec<u16>)> = Vec::new();
+
+		let infos = opcode_table().into_iter().chain(prefixed_opcode_table());
+		for (index, (info, count)) in infos.zip(counts).enumerate() {
+			if info.illegal || index == CB_PREFIX {
+				continue;
+			}
+			total += 1;
+			if *count > 0 {
+				covered += 1;
+				continue;
+			}
+
+			match missing.iter_mut().find(|(mnemonic, _)| *mnemonic == info.mnemonic) {
+				Some((_, opcodes)) => opcodes.push(index as u16),
+				None => missing.push((info.mnemonic, vec![index as u16])),
+			}
+		}
+
+		Self {
+			covered,
+			total,
+			missing,
+		}
+	}
+
+	pub fn covered(&self) -> usize {
+		self.covered
+	}
+
+	/// Legal opcodes, prefixed ones included
+	pub fn total(&self) -> usize {
+		self.total
+	}
+
+	/// Opcodes that never executed, grouped by mnemonic in opcode order. Prefixed ones are 0x100 + opcode.
+	pub fn missing(&self) -> &[(&'static str, Vec<u16>)] {
+		&self.missing
+	}
+
+	pub fn percentage(&self) -> f64 {
+		100.0 * self.covered as f64 / self.total as f64
+	}
+
+	pub fn meets_threshold(&self, percentage: f64) -> bool {
+		self.percentage() >= percentage
+	}
+}
+
+impl Display for CoverageReport {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		writeln!(
+			f,
+			"{}/{} opcodes executed ({:.1}%)",
+			self.covered,
+			self.total,
+			self.percentage()
+		)?;
+		for (mnemonic, opcodes) in &self.missing {
+			let opcodes: Vec<_> = opcodes
+				.iter()
+				.map(|&index| match usize::from(index) {
+					index if index >= PREFIXED_START => format!("CB {:02X}", index - PREFIXED_START),
+					index => format!("{index:02X}"),
+				})
+				.collect();
+			writeln!(f, "  {mnemonic}: {}", opcodes.join(", "))?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// 256 unprefixed opcodes without the 11 illegal ones and the prefix, and all 256 prefixed ones
+	const LEGAL_OPCODES: usize = 256 - 11 - 1 + 256;
+
+	#[test]
+	fn nothing_executed() {
+		let report = CoverageReport::new(&[0; OPCODE_COUNTS_SIZE]);
+
+		assert_eq!(report.covered(), 0);
+		assert_eq!(report.total(), LEGAL_OPCODES);
+		assert!(report.meets_threshold(0.0));
+		assert!(!report.meets_threshold(0.1));
+	}
+
+	#[test]
+	fn everything_executed() {
+		let report = CoverageReport::new(&[1; OPCODE_COUNTS_SIZE]);
+
+		assert_eq!(report.covered(), LEGAL_OPCODES);
+		assert!(report.missing().is_empty());
+		assert!(report.meets_threshold(100.0));
+	}
+
+	#[test]
+	fn missing_grouped_by_mnemonic() {
+		let mut counts = [1; OPCODE_COUNTS_SIZE];
+		// ld B, n and ld C, n, swap A, halt
+		for index in [0x06, 0x0E, PREFIXED_START + 0x37, 0x76] {
+			counts[index] = 0;
+		}
+		let report = CoverageReport::new(&counts);
+
+		assert_eq!(report.covered(), LEGAL_OPCODES - 4);
+		assert_eq!(
+			report.missing(),
+			[("ld", vec![0x06, 0x0E]), ("halt", vec![0x76]), ("swap", vec![0x137])]
+		);
+		assert!(report.meets_threshold(99.0));
+		assert!(!report.meets_threshold(100.0));
+		assert!(report
+			.to_string()
+			.ends_with("  ld: 06, 0E\n  halt: 76\n  swap: CB 37\n"));
+	}
+}
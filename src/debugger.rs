@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+
+use crate::decoder::table::{opcode_table, OpcodeInfo};
+use crate::hardware::cpu::{Cpu, StepOutcome};
+use crate::hardware::watchpoints::WatchHit;
+use crate::instructions::ExecutionError;
+
+/// Why `Debugger::run_until_break` returned
+#[derive(Debug)]
+pub enum BreakReason {
+	/// PC is on a breakpoint, the instruction there hasn't executed yet
+	Breakpoint(u16),
+	/// At least this many cycles ran without hitting a breakpoint
+	CyclesExhausted(u64),
+	/// The last instruction wrote to watched addresses, only when breaking on watchpoints
+	Watchpoint(Vec<WatchHit>),
+	Error(ExecutionError),
+}
+
+// How far above SP the stack walker looks, so a stack in WRAM isn't walked into its echo
+const STACK_SCAN_LIMIT: u16 = 0x200;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameConfidence {
+	/// Right after a CALL, checked by decoding it
+	High,
+	/// Right after an RST, a single byte that data can easily match
+	Low,
+}
+
+/// A value on the stack that looks like a return address
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StackFrame {
+	pub return_address: u16,
+	/// Where it was found, counting up from SP
+	pub sp_offset: u16,
+	pub confidence: FrameConfidence,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Debugger {
+	breakpoints: HashSet<u16>,
+	break_on_watchpoints: bool,
+}
+
+impl Debugger {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn add_breakpoint(&mut self, address: u16) {
+		self.breakpoints.insert(address);
+	}
+
+	/// Returns whether there was a breakpoint at `address`
+	pub fn remove_breakpoint(&mut self, address: u16) -> bool {
+		self.breakpoints.remove(&address)
+	}
+
+	pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+		self.breakpoints.iter().copied()
+	}
+
+	/// Stop after any instruction that writes to an address watched on the CPU, taking its hits
+	pub fn set_break_on_watchpoints(&mut self, enabled: bool) {
+		self.break_on_watchpoints = enabled;
+	}
+
+	/// Executes a single instruction, ignoring breakpoints
+	pub fn step(&self, cpu: &mut Cpu) -> Result<StepOutcome, ExecutionError> {
+		cpu.step()
+	}
+
+	/// Runs until PC lands on a breakpoint, `max_cycles` run out, or an instruction fails.
+	/// The instruction at the starting PC always executes, so a stopped run can be resumed from its breakpoint.
+	pub fn run_until_break(&self, cpu: &mut Cpu, max_cycles: u64) -> BreakReason {
+		let mut cycles = 0;
+		while cycles < max_cycles {
+			match self.step(cpu) {
+				Ok(outcome) => cycles += u64::from(outcome.cycles()),
+				Err(err) => return BreakReason::Error(err),
+			}
+
+			if self.break_on_watchpoints {
+				let hits = cpu.take_watchpoint_hits();
+				if !hits.is_empty() {
+					return BreakReason::Watchpoint(hits);
+				}
+			}
+
+			let pc = cpu.current_pc();
+			if self.breakpoints.contains(&pc) {
+				return BreakReason::Breakpoint(pc);
+			}
+		}
+
+		BreakReason::CyclesExhausted(cycles)
+	}
+}
+
+/// Guesses the call stack by scanning up from SP for values that point right after a CALL or RST, innermost first.
+/// Anything else on the stack, like pushed registers, is skipped unless it happens to look the same.
+pub fn walk_stack(cpu: &Cpu, max_frames: usize) -> Vec<StackFrame> {
+	let table = opcode_table();
+	let sp = cpu.current_sp();
+	let mut frames = Vec::new();
+
+	for sp_offset in (0..STACK_SCAN_LIMIT).step_by(2) {
+		if frames.len() >= max_frames {
+			break;
+		}
+		let address = match sp.checked_add(sp_offset) {
+			Some(address) if address < 0xFFFF => address,
+			_ => break,
+		};
+		let return_address = match (cpu.peek_byte(address), cpu.peek_byte(address + 1)) {
+			(Ok(low), Ok(high)) => u16::from_le_bytes([low, high]),
+			_ => break,
+		};
+
+		if let Some(confidence) = call_confidence(cpu, &table, return_address) {
+			frames.push(StackFrame {
+				return_address,
+				sp_offset,
+				confidence,
+			});
+		}
+	}
+
+	frames
+}
+
+fn call_confidence(cpu: &Cpu, table: &[OpcodeInfo; 256], return_address: u16) -> Option<FrameConfidence> {
+	let opcode_before = |length: u16| {
+		let opcode = cpu.peek_byte(return_address.checked_sub(length)?).ok()?;
+		let info = table[usize::from(opcode)];
+		(info.length == length).then_some(info.mnemonic)
+	};
+
+	match (opcode_before(3), opcode_before(1)) {
+		(Some("call"), _) => Some(FrameConfidence::High),
+		(_, Some("rst")) => Some(FrameConfidence::Low),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::hardware::register_bank::{DoubleRegisters, SingleRegisters};
+
+	use super::*;
+
+	// loop: inc B; inc C; jr loop
+	const PROGRAM: [u8; 4] = [0x04, 0x0C, 0x18, 0xFC];
+
+	fn load_program(cpu: &mut Cpu) {
+		for (offset, byte) in PROGRAM.into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+	}
+
+	#[test]
+	fn stops_on_breakpoint() {
+		let mut cpu = Cpu::new();
+		load_program(&mut cpu);
+		let mut debugger = Debugger::new();
+		debugger.add_breakpoint(WORKING_RAM_START + 1);
+
+		let reason = debugger.run_until_break(&mut cpu, 1_000);
+		assert!(matches!(reason, BreakReason::Breakpoint(pc) if pc == WORKING_RAM_START + 1));
+		assert_eq!(cpu.read_register(SingleRegisters::B), 1);
+		assert_eq!(cpu.read_register(SingleRegisters::C), 0);
+
+		// Resuming runs the loop once more before hitting it again
+		let reason = debugger.run_until_break(&mut cpu, 1_000);
+		assert!(matches!(reason, BreakReason::Breakpoint(pc) if pc == WORKING_RAM_START + 1));
+		assert_eq!(cpu.read_register(SingleRegisters::B), 2);
+		assert_eq!(cpu.read_register(SingleRegisters::C), 1);
+	}
+
+	#[test]
+	fn cycle_budget() {
+		let mut cpu = Cpu::new();
+		load_program(&mut cpu);
+		let mut debugger = Debugger::new();
+		debugger.add_breakpoint(WORKING_RAM_START + 1);
+		assert!(debugger.remove_breakpoint(WORKING_RAM_START + 1));
+
+		// One pass of the loop is 4 + 4 + 12 cycles
+		let reason = debugger.run_until_break(&mut cpu, 40);
+		assert!(matches!(reason, BreakReason::CyclesExhausted(40)));
+		assert_eq!(cpu.read_register(SingleRegisters::B), 2);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START);
+	}
+
+	#[test]
+	fn execution_error() {
+		let mut cpu = Cpu::new();
+		cpu.write_byte(WORKING_RAM_START, 0xD3).unwrap();
+		cpu.set_pc(WORKING_RAM_START);
+
+		let reason = Debugger::new().run_until_break(&mut cpu, 1_000);
+		assert!(matches!(
+			reason,
+			BreakReason::Error(ExecutionError::InvalidOpcode(0xD3))
+		));
+	}
+
+	#[test]
+	fn stops_on_watchpoint() {
+		let mut cpu = Cpu::new();
+		// ld HL, 0xC010; ld (HL), 0x42; ld (HL), 0x42
+		for (offset, byte) in [0x21, 0x10, 0xC0, 0x36, 0x42, 0x36, 0x42].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+		cpu.add_watchpoint(WORKING_RAM_START + 0x10..=WORKING_RAM_START + 0x10);
+		let mut debugger = Debugger::new();
+		debugger.set_break_on_watchpoints(true);
+
+		let reason = debugger.run_until_break(&mut cpu, 1_000);
+		let hits = match reason {
+			BreakReason::Watchpoint(hits) => hits,
+			reason => panic!("Expected a watchpoint, got {reason:?}"),
+		};
+		assert_eq!(hits.len(), 1);
+		assert_eq!(hits[0].new_value, 0x42);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 5);
+	}
+
+	#[test]
+	fn nested_calls() {
+		let mut cpu = Cpu::new();
+		let program: [(u16, &[u8]); 4] = [
+			// call 0xC010
+			(0x00, &[0xCD, 0x10, 0xC0]),
+			// push BC; call 0xC020
+			(0x10, &[0xC5, 0xCD, 0x20, 0xC0]),
+			// push DE; call 0xC040
+			(0x20, &[0xD5, 0xCD, 0x40, 0xC0]),
+			// rst 0x38, never executed
+			(0x30, &[0xFF]),
+		];
+		for (start, bytes) in program {
+			for (offset, byte) in bytes.iter().enumerate() {
+				cpu.write_byte(WORKING_RAM_START + start + offset as u16, *byte)
+					.unwrap();
+			}
+		}
+		// Decoys: right after a call's opcode, and right after an RST
+		cpu.write_double_register(DoubleRegisters::BC, WORKING_RAM_START + 0x01);
+		cpu.write_double_register(DoubleRegisters::DE, WORKING_RAM_START + 0x31);
+		cpu.set_sp(WORKING_RAM_START + 0x1000);
+		cpu.set_pc(WORKING_RAM_START);
+
+		let debugger = Debugger::new();
+		for _ in 0..5 {
+			debugger.step(&mut cpu).unwrap();
+		}
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 0x40);
+
+		let frame = |return_address, sp_offset, confidence| StackFrame {
+			return_address: WORKING_RAM_START + return_address,
+			sp_offset,
+			confidence,
+		};
+		assert_eq!(
+			walk_stack(&cpu, 10),
+			[
+				frame(0x24, 0, FrameConfidence::High),
+				frame(0x31, 2, FrameConfidence::Low),
+				frame(0x14, 4, FrameConfidence::High),
+				frame(0x03, 8, FrameConfidence::High),
+			]
+		);
+		assert_eq!(walk_stack(&cpu, 2).len(), 2);
+	}
+}
@@ -1,5 +1,11 @@
-use crate::bits::byte_to_bits;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::decoder::dispatch::dispatch_opcode;
+use crate::decoder::fetch::{ByteFetcher, CpuCursor, SliceCursor};
+use crate::decoder::hooks::{HookAction, HookInstruction};
 use crate::decoder::prefixed::{decode_prefixed_shifting, decode_prefixed_single_bit};
+use crate::decoder::table::{ImmediateKind, OperandKind};
 use crate::hardware::cpu::Cpu;
 use crate::hardware::ram::IO_REGISTERS_MAPPING_START;
 use crate::hardware::register_bank::{BitFlags, DoubleRegisters, SingleRegisters};
@@ -20,6 +26,7 @@ use crate::instructions::flags::{BitFlagChangeType, ChangeCarryFlagInstruction};
 use crate::instructions::flow::{
 	BranchCondition, CallInstruction, JumpInstruction, JumpInstructionDestination, ReturnInstruction,
 };
+use crate::instructions::kind::InstructionKind;
 use crate::instructions::load::byte_load::{ByteLoadInstruction, ByteLoadOperation, ByteLoadUpdate};
 use crate::instructions::load::double_byte_load::{
 	DoubleByteLoadInstruction, DoubleByteLoadOperation, PopInstruction, PushInstruction,
@@ -31,11 +38,16 @@ use crate::instructions::shared::IndexUpdateType;
 use crate::instructions::shifting::operation::{ByteShiftOperation, ShiftDirection, ShiftType};
 use crate::instructions::shifting::ByteShiftInstruction;
 use crate::instructions::single_bit::SingleBitOperation;
-use crate::instructions::{ExecutionError, Instruction};
+use crate::instructions::{Executable, ExecutionError, Instruction};
 
+mod dispatch;
+mod fetch;
+pub(crate) mod hooks;
 mod prefixed;
+pub(crate) mod table;
+mod timing;
 
-#[derive(Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 enum DecodedInstructionPrefix {
 	CB,
 }
@@ -49,522 +61,723 @@ impl DecodedInstructionPrefix {
 	}
 }
 
-pub fn fetch_and_decode(cpu: &mut Cpu) -> Result<Box<dyn Instruction>, ExecutionError> {
-	let first_byte = cpu.next_byte()?;
+/// A decoded instruction with its timing, in clock cycles
+#[derive(Debug)]
+pub struct DecodedInstruction {
+	instruction: InstructionKind,
+	/// Bytes read by the decoder, prefix and immediates included
+	pub length: u16,
+	pub cycles: u8,
+	/// Extra cycles when a conditional branch is taken
+	pub branch_cycles: u8,
+	fetched: FetchedBytes,
+}
+
+impl DecodedInstruction {
+	fn new(
+		prefix: Option<DecodedInstructionPrefix>,
+		opcode: u8,
+		instruction: InstructionKind,
+		length: u16,
+		fetched: FetchedBytes,
+	) -> Self {
+		let (cycles, branch_cycles) = timing::cycles(prefix, opcode);
+
+		Self {
+			instruction,
+			length,
+			cycles,
+			branch_cycles,
+			fetched,
+		}
+	}
+
+	/// The instruction as a trait object, for code that doesn't care which instruction it is
+	pub fn instruction(&self) -> &dyn Instruction {
+		self.instruction.as_dyn()
+	}
+
+	/// Moves the instruction out into a boxed trait object, as the decoder used to return it
+	pub fn into_boxed(self) -> Box<dyn Instruction> {
+		self.instruction.into_boxed()
+	}
+
+	/// Bytes as they were read when decoding, which may no longer match memory if the code modified itself.
+	/// Hooked opcodes only record the opcode.
+	pub fn bytes(&self) -> &[u8] {
+		self.fetched.as_slice()
+	}
+
+	/// Executes the instruction, returning how many clock cycles it took
+	pub fn execute(&self, cpu: &mut Cpu) -> Result<u8, ExecutionError> {
+		let branch_taken = self.instruction.branch_taken(cpu);
+		// An EI before this instruction enables interrupts once it's done, unless this one is a DI
+		let enable_ime = cpu.ime_scheduled;
+		self.instruction.execute(cpu)?;
+		if enable_ime && cpu.ime_scheduled {
+			cpu.ime.write(true);
+			cpu.ime_scheduled = false;
+		}
+
+		let branch_cycles = if branch_taken { self.branch_cycles } else { 0 };
+		Ok(self.cycles + branch_cycles)
+	}
+}
+
+impl Display for DecodedInstruction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.instruction)
+	}
+}
+
+pub fn fetch_and_decode(cpu: &mut Cpu) -> Result<DecodedInstruction, ExecutionError> {
+	let start = cpu.current_pc();
+	cpu.count_opcode();
+	let (prefix, opcode, instruction, fetched) = fetch_and_decode_instruction(cpu)?;
+	cpu.halt_bug = false;
+	let decoded = DecodedInstruction::new(
+		prefix,
+		opcode,
+		instruction,
+		cpu.current_pc().wrapping_sub(start),
+		fetched,
+	);
+
+	cpu.record_trace(start, decoded.bytes());
+	Ok(decoded)
+}
+
+/// Decodes the instruction at PC without moving it, returning it along with the address of the next instruction.
+/// Opcode hooks are not applied.
+pub fn peek_and_decode(cpu: &Cpu) -> Result<(DecodedInstruction, u16), ExecutionError> {
+	let mut cursor = CpuCursor::peeking(cpu);
+	let mut reader = InstructionReader::new(&mut cursor);
+	let (prefix, opcode, instruction, fetched) = decode_instruction(&mut reader)?;
+	let next_pc = reader.pc();
+	let length = next_pc.wrapping_sub(cpu.current_pc());
+
+	Ok((
+		DecodedInstruction::new(prefix, opcode, instruction, length, fetched),
+		next_pc,
+	))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+	InvalidOpcode(u8),
+	/// The instruction continues past the end of the bytes, `offset` being the first missing one
+	Truncated {
+		offset: usize,
+	},
+}
+
+impl Display for DecodeError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidOpcode(opcode) => write!(f, "Invalid opcode {opcode:#04X}"),
+			Self::Truncated { offset } => write!(f, "Instruction truncated at offset {offset:#06X}"),
+		}
+	}
+}
+
+impl Error for DecodeError {}
+
+/// Decodes the instruction at `offset` in `bytes`, such as a ROM image, returning it with the number of bytes it
+/// takes. Relative jump targets assume the bytes are mapped at address 0.
+pub fn decode_from_slice(bytes: &[u8], offset: usize) -> Result<(DecodedInstruction, usize), DecodeError> {
+	let mut cursor = SliceCursor::new(bytes, offset);
+	let mut reader = InstructionReader::new(&mut cursor);
+	let decoded = decode_instruction(&mut reader);
+	let length = cursor.offset() - offset;
+
+	match decoded {
+		Ok((prefix, opcode, instruction, fetched)) => Ok((
+			DecodedInstruction::new(prefix, opcode, instruction, length as u16, fetched),
+			length,
+		)),
+		Err(ExecutionError::InvalidOpcode(opcode)) => Err(DecodeError::InvalidOpcode(opcode)),
+		Err(ExecutionError::RamError(_)) => Err(DecodeError::Truncated {
+			offset: cursor.offset(),
+		}),
+	}
+}
+
+/// Formats an opcode and its immediates in canonical assembly, such as `LD A,(HL+)` or `JR NZ,+0x05`, for debuggers
+/// disassembling memory. Missing immediates read as zero, extra ones are ignored, and opcodes without an instruction are
+/// shown as a data byte.
+pub fn disassemble(prefixed: bool, opcode: u8, operands: &[u8]) -> String {
+	let Some(info) = table::opcode_info(prefixed, opcode) else {
+		return format!("DB 0x{opcode:02X}");
+	};
+
+	let low = operands.first().copied().unwrap_or(0);
+	let high = operands.get(1).copied().unwrap_or(0);
+	let immediate = match info.immediate {
+		Some(ImmediateKind::U16) => format!("0x{:04X}", u16::from_le_bytes([low, high])),
+		Some(ImmediateKind::I8) => format_offset(low as i8),
+		_ => format!("0x{low:02X}"),
+	};
+
+	let operands: Vec<String> = info
+		.operands
+		.into_iter()
+		.flatten()
+		.map(|operand| match operand {
+			OperandKind::Register(name) | OperandKind::Condition(name) => name.to_string(),
+			OperandKind::Indirect(name) => format!("({name})"),
+			OperandKind::Bit(bit) => bit.to_string(),
+			OperandKind::Vector(vector) => format!("0x{vector:02X}"),
+			OperandKind::Immediate => immediate.clone(),
+			// LDH addresses the high page with a single byte
+			OperandKind::IndirectImmediate if info.immediate == Some(ImmediateKind::U8) => {
+				format!("(0x{:04X})", IO_REGISTERS_MAPPING_START + u16::from(low))
+			}
+			OperandKind::IndirectImmediate => format!("({immediate})"),
+			OperandKind::SpPlusImmediate => format!("SP{immediate}"),
+		})
+		.collect();
+
+	let mnemonic = info.mnemonic.to_uppercase();
+	if operands.is_empty() {
+		mnemonic
+	} else {
+		format!("{mnemonic} {}", operands.join(","))
+	}
+}
+
+// Signed offsets keep their sign, so relative jumps read as a distance
+fn format_offset(offset: i8) -> String {
+	let sign = if offset < 0 { '-' } else { '+' };
+	format!("{sign}0x{:02X}", offset.unsigned_abs())
+}
+
+type FetchedInstruction = (Option<DecodedInstructionPrefix>, u8, InstructionKind, FetchedBytes);
+
+// Prefix and opcode, or opcode and a 16-bit immediate
+const MAX_INSTRUCTION_LENGTH: usize = 3;
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+struct FetchedBytes {
+	bytes: [u8; MAX_INSTRUCTION_LENGTH],
+	count: usize,
+}
+
+impl FetchedBytes {
+	fn push(&mut self, byte: u8) {
+		self.bytes[self.count] = byte;
+		self.count += 1;
+	}
+
+	fn as_slice(&self) -> &[u8] {
+		&self.bytes[..self.count]
+	}
+}
+
+fn fetch_and_decode_instruction(cpu: &mut Cpu) -> Result<FetchedInstruction, ExecutionError> {
+	if !cpu.opcode_hooks.is_empty() {
+		if let Some(hooked) = decode_hooked(cpu)? {
+			return Ok(hooked);
+		}
+	}
+
+	let mut cursor = CpuCursor::new(cpu);
+	let mut reader = InstructionReader::new(&mut cursor);
+	let fetched = decode_instruction(&mut reader)?;
+	let next_pc = reader.pc();
+	cpu.set_pc(next_pc);
+
+	Ok(fetched)
+}
+
+fn decode_instruction(reader: &mut InstructionReader) -> Result<FetchedInstruction, ExecutionError> {
+	let first_byte = reader.next_byte()?;
 
 	let prefix = DecodedInstructionPrefix::try_decode_prefix(first_byte);
 
-	let opcode = if prefix.is_some() { cpu.next_byte()? } else { first_byte };
+	let opcode = if prefix.is_some() {
+		reader.next_byte()?
+	} else {
+		first_byte
+	};
+
+	let instruction = dispatch_opcode(prefix, opcode, reader)?;
+
+	Ok((prefix, opcode, instruction, reader.fetched))
+}
+
+// Reads the bytes of one instruction from a fetcher, so the same decoding works on the CPU's memory and on slices.
+// Keeps every byte it read, so they can be shown as fetched even if the instruction then overwrites them.
+struct InstructionReader<'a> {
+	fetcher: &'a mut dyn ByteFetcher,
+	fetched: FetchedBytes,
+}
+
+impl<'a> InstructionReader<'a> {
+	fn new(fetcher: &'a mut dyn ByteFetcher) -> Self {
+		Self {
+			fetcher,
+			fetched: FetchedBytes::default(),
+		}
+	}
+
+	// Address right after the bytes read so far
+	fn pc(&self) -> u16 {
+		self.fetcher.address()
+	}
+
+	fn next_byte(&mut self) -> Result<u8, ExecutionError> {
+		let byte = self.fetcher.next_byte()?;
+		self.fetched.push(byte);
+
+		Ok(byte)
+	}
+}
+
+// Hooked opcodes keep the timing of the opcode they replace
+fn decode_hooked(cpu: &mut Cpu) -> Result<Option<FetchedInstruction>, ExecutionError> {
+	let opcode = cpu.read_byte(cpu.current_pc())?;
+	let Some(handler) = cpu.opcode_hooks.get(opcode) else {
+		return Ok(None);
+	};
+
+	let instruction: InstructionKind = match handler(cpu) {
+		HookAction::Continue => return Ok(None),
+		HookAction::Skip(length) => {
+			cpu.set_pc(cpu.current_pc().wrapping_add(length));
+			InstructionKind::from(NopInstruction::new())
+		}
+		HookAction::Custom(writes) => {
+			cpu.next_pc();
+			InstructionKind::from(HookInstruction::new(opcode, writes))
+		}
+	};
+
+	let mut fetched = FetchedBytes::default();
+	fetched.push(opcode);
 
-	decode_opcode(prefix, opcode, cpu)
+	Ok(Some((None, opcode, instruction, fetched)))
 }
 
+/// An opcode split into the fields the instruction tables are organized by: `xxyyyzzz`, with `y` split again as `ppq`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Opcode(u8);
+
+impl Opcode {
+	fn x(self) -> u8 {
+		self.0 >> 6
+	}
+
+	fn y(self) -> u8 {
+		(self.0 >> 3) & 0b111
+	}
+
+	fn z(self) -> u8 {
+		self.0 & 0b111
+	}
+
+	fn p(self) -> u8 {
+		self.y() >> 1
+	}
+
+	fn q(self) -> bool {
+		self.y() & 1 != 0
+	}
+}
+
+// Inlined into every dispatch table entry, each calling it with its opcode as a constant
+#[inline(always)]
 fn decode_opcode(
 	prefix: Option<DecodedInstructionPrefix>,
 	opcode: u8,
-	cpu: &mut Cpu,
-) -> Result<Box<dyn Instruction>, ExecutionError> {
-	let (x, y, z) = decode_xyz(opcode);
+	reader: &mut InstructionReader,
+) -> Result<InstructionKind, ExecutionError> {
+	let op = Opcode(opcode);
 
 	match prefix {
-		Some(DecodedInstructionPrefix::CB) => {
-			match x {
-				[false, false] /* x = 0 */ => Ok(decode_prefixed_shifting(y, z)),
-				[true, false] /* x = 1 */ => Ok(decode_prefixed_single_bit(
-					SingleBitOperation::Test, y, z,
-				)),
-				[false, true] /* x = 2 */ => Ok(decode_prefixed_single_bit(
-					SingleBitOperation::Write(false), y, z,
-				)),
-				[true, true] /* x = 3 */ => Ok(decode_prefixed_single_bit(
-					SingleBitOperation::Write(true), y, z,
-				)),
+		Some(DecodedInstructionPrefix::CB) => Ok(match op.x() {
+			0 => decode_prefixed_shifting(op.y(), op.z()),
+			1 => decode_prefixed_single_bit(SingleBitOperation::Test, op.y(), op.z()),
+			2 => decode_prefixed_single_bit(SingleBitOperation::Write(false), op.y(), op.z()),
+			_ => decode_prefixed_single_bit(SingleBitOperation::Write(true), op.y(), op.z()),
+		}),
+		None => match op.x() {
+			0 => decode_block_0(op, reader),
+			1 => {
+				if op.y() == 6 && op.z() == 6 {
+					Ok(InstructionKind::from(HaltInstruction::new()))
+				} else {
+					let src_operand = DecodedInstructionOperand::from_index(op.z());
+					let dst_operand = DecodedInstructionOperand::from_index(op.y());
+
+					Ok(InstructionKind::from(ByteLoadInstruction::new(
+						src_operand.into(),
+						dst_operand.into(),
+						ByteLoadOperation::no_update(),
+					)))
+				}
+			}
+			2 => {
+				let decoded_operand = DecodedInstructionOperand::from_index(op.z());
+				Ok(decode_byte_instruction(op.y(), decoded_operand.into()))
+			}
+			_ => decode_block_3(op, reader),
+		},
+	}
+}
+
+// x = 0: relative jumps, 16-bit loads and arithmetic, indirect loads, inc/dec and accumulator operations
+#[inline(always)]
+fn decode_block_0(op: Opcode, reader: &mut InstructionReader) -> Result<InstructionKind, ExecutionError> {
+	match op.z() {
+		0 => match op.y() {
+			0 => Ok(InstructionKind::from(NopInstruction::new())),
+			1 => {
+				let address = load_next_u16(reader)?;
+
+				Ok(InstructionKind::from(DoubleByteLoadInstruction::new(
+					DoubleByteSource::StackPointer,
+					DoubleByteDestination::AddressInImmediate(address),
+					DoubleByteLoadOperation,
+				)))
+			}
+			2 => {
+				// The byte after STOP is skipped, it is usually 0x00
+				reader.next_byte()?;
+
+				Ok(InstructionKind::from(StopInstruction::new()))
+			}
+			3 => {
+				let delta = load_next_i8(reader)?;
+
+				Ok(InstructionKind::from(JumpInstruction::new(
+					JumpInstructionDestination::relative_to(reader.pc(), delta),
+					BranchCondition::Unconditional,
+				)))
+			}
+			y => {
+				let (flag, branch_if_equals) = decode_conditional(y - 4);
+				let delta = load_next_i8(reader)?;
+
+				Ok(InstructionKind::from(JumpInstruction::new(
+					JumpInstructionDestination::relative_to(reader.pc(), delta),
+					BranchCondition::TestFlag { flag, branch_if_equals },
+				)))
+			}
+		},
+		1 => {
+			let double_register_operand = DecodedInstructionDoubleOperand::from_index_or_sp(op.p());
+
+			if op.q() {
+				Ok(InstructionKind::from(BinaryDoubleByteAddInstruction::new(
+					DoubleByteSource::DoubleRegister(DoubleRegisters::HL),
+					double_register_operand.into(),
+					DoubleByteDestination::DoubleRegister(DoubleRegisters::HL),
+					BinaryDoubleByteAddOperation::new(),
+				)))
+			} else {
+				let immediate = load_next_u16(reader)?;
+
+				Ok(InstructionKind::from(DoubleByteLoadInstruction::new(
+					DoubleByteSource::Immediate(immediate),
+					double_register_operand.into(),
+					DoubleByteLoadOperation::new(),
+				)))
 			}
 		}
-		None => {
-			match x {
-				[false, false] /* x = 0 */ => {
-					match z {
-						[false, false, false] /* z = 0 */ => {
-							match y {
-								[false, false, false] /* y = 0 */ => {
-									Ok(Box::new(NopInstruction::new()))
-								}
-								[true, false, false] /* y = 1 */ => {
-									let address = load_next_u16(cpu)?;
-
-									Ok(Box::new(DoubleByteLoadInstruction::new(
-										DoubleByteSource::StackPointer,
-										DoubleByteDestination::AddressInImmediate(address),
-										DoubleByteLoadOperation,
-									)))
-								}
-								[false, true, false] /* y = 2 */ => {
-									Ok(Box::new(StopInstruction::new()))
-								}
-								[true, true, false] /* y = 3 */ => {
-									let delta = load_next_i8(cpu)?;
-
-									Ok(Box::new(JumpInstruction::new(
-										JumpInstructionDestination::RelativeToPc(delta),
-										BranchCondition::Unconditional,
-									)))
-								}
-								[y0, y1, true]/* 4 <= y < 8 */ => {
-									let flag = match y1 {
-										false => BitFlags::Zero,
-										true => BitFlags::Carry
-									};
-									let branch_if_equals = y0;
-									let delta = load_next_i8(cpu)?;
-
-									Ok(Box::new(JumpInstruction::new(
-										JumpInstructionDestination::RelativeToPc(delta),
-										BranchCondition::TestFlag { flag, branch_if_equals },
-									)))
-								}
-							}
-						}
-						[true, false, false] /* z = 1 */ => {
-							let [y0, y1, y2] = y;
-							let q = y0;
-							let p = [y1, y2];
-
-							let double_register_operand =
-								DecodedInstructionDoubleOperand::from_opcode_part_double_or_sp(p);
-
-							match q {
-								false => {
-									let immediate = load_next_u16(cpu)?;
-
-									Ok(Box::new(DoubleByteLoadInstruction::new(
-										DoubleByteSource::Immediate(immediate),
-										double_register_operand.into(),
-										DoubleByteLoadOperation::new(),
-									)))
-								}
-								true => {
-									Ok(Box::new(BinaryDoubleByteAddInstruction::new(
-										DoubleByteSource::DoubleRegister(DoubleRegisters::HL),
-										double_register_operand.into(),
-										DoubleByteDestination::DoubleRegister(DoubleRegisters::HL),
-										BinaryDoubleByteAddOperation::new(),
-									)))
-								}
-							}
-						}
-						[false, true, false] /* z = 2 */ => {
-							let [y0, y1, y2] = y;
-							let q = y0;
-							let p = [y1, y2];
-
-							match p[1] {
-								false /* 0 <= p < 2*/ => {
-									let register_address = match p[0] {
-										false /* p = 0 */ => {
-											DoubleRegisters::BC
-										}
-										true /* p = 1 */ => {
-											DoubleRegisters::DE
-										}
-									};
-
-									let (destination, source) = match q {
-										false /* q = 0 */ => {
-											(
-												ByteDestination::AddressInRegister(register_address),
-												ByteSource::read_from_acc()
-											)
-										}
-										true /* q = 1 */ => {
-											(
-												ByteDestination::write_to_acc(),
-												ByteSource::AddressInRegister(register_address)
-											)
-										}
-									};
-
-									Ok(Box::new(ByteLoadInstruction::just_load(
-										source, destination,
-									)))
-								}
-								true /* 2 <= p < 4 */ => {
-									let update_type = match p[0] {
-										false /* p = 3 */ => {
-											IndexUpdateType::Increment
-										}
-										true /* p = 4 */ => {
-											IndexUpdateType::Decrement
-										}
-									};
-
-									let update = ByteLoadUpdate::new(
-										DoubleRegisters::HL,
-										update_type,
-									);
-
-									let operation = ByteLoadOperation::with_update(update);
-
-									let (destination, source) = match q {
-										false /* q = 0 */ => {
-											(
-												ByteDestination::AddressInRegister(DoubleRegisters::HL),
-												ByteSource::read_from_acc()
-											)
-										}
-										true /* q = 1 */ => {
-											(
-												ByteDestination::write_to_acc(),
-												ByteSource::AddressInRegister(DoubleRegisters::HL)
-											)
-										}
-									};
-
-									Ok(Box::new(ByteLoadInstruction::new(
-										source, destination, operation,
-									)))
-								}
-							}
-						}
-						[true, true, false] /* z = 3 */ => {
-							let (p, q) = decode_pq(y);
-
-							let decoded_double_operator = DecodedInstructionDoubleOperand::from_opcode_part_double_or_sp(p);
-
-
-							let inc_or_dec_type = match q {
-								false => IndexUpdateType::Increment,
-								true => IndexUpdateType::Decrement
-							};
-
-							Ok(Box::new(IncOrDecDoubleInstruction::new(
-								decoded_double_operator.into(),
-								decoded_double_operator.into(),
-								IncOrDecDoubleByteOperation::new(inc_or_dec_type),
-							)))
-						}
-						[y0, false, true] /* 4 <= z < 6 */ => {
-							let inc_dec_op_type = match y0 {
-								false /* z = 4 */ => {
-									IndexUpdateType::Increment
-								}
-								true /* z = 5 */ => {
-									IndexUpdateType::Decrement
-								}
-							};
-
-							let decoded_operand = DecodedInstructionOperand::from_opcode_part(y);
-
-							Ok(Box::new(IncOrDecByteInstruction::new(
-								decoded_operand.into(),
-								decoded_operand.into(),
-								IncOrDecByteOperation::new(inc_dec_op_type),
-							)))
-						}
-						[false, true, true] /* z = 6 */ => {
-							let decoded_operand = DecodedInstructionOperand::from_opcode_part(y);
-							let immediate = load_next_u8(cpu)?;
-
-							Ok(Box::new(ByteLoadInstruction::new(
-								ByteSource::Immediate(immediate),
-								decoded_operand.into(),
-								ByteLoadOperation::no_update(),
-							)))
-						}
-						[true, true, true] /* z = 7 */ => {
-							match y {
-								[y0, y1, false] /* 0 <= y < 4 */ => {
-									let shift_direction = match y0 {
-										false => ShiftDirection::Left,
-										true => ShiftDirection::Right
-									};
-
-									let shift_type = match y1 {
-										false => ShiftType::Rotate,
-										true => ShiftType::RotateWithCarry
-									};
-
-									Ok(Box::new(ByteShiftInstruction::new(
-										ByteSource::read_from_acc(),
-										ByteDestination::write_to_acc(),
-										ByteShiftOperation::new(shift_direction, shift_type),
-									)))
-								}
-								[false, false, true] /* z = 4 */ => {
-									Ok(Box::new(DecimalAdjust::new()))
-								}
-								[true, false, true] /* z = 5 */ => {
-									Ok(Box::new(
-										LogicalNegateInstruction::negate_acc()
-									))
-								}
-								[false, true, true] /* z = 6 */ => {
-									Ok(Box::new(ChangeCarryFlagInstruction::new(
-										BitFlagChangeType::Write(true)
-									)))
-								}
-								[true, true, true] /* z = 7 */ => {
-									Ok(Box::new(ChangeCarryFlagInstruction::new(
-										BitFlagChangeType::Toggle
-									)))
-								}
-							}
-						}
-					}
-				}
-				[true, false] /* x = 1 */ => {
-					if y == [false, true, true] && z == [false, true, true] /* y = z = 6 */ {
-						Ok(Box::new(HaltInstruction::new()))
-					} else {
-						let src_operand = DecodedInstructionOperand::from_opcode_part(z);
-						let dst_operand = DecodedInstructionOperand::from_opcode_part(y);
-
-						Ok(Box::new(ByteLoadInstruction::new(
-							src_operand.into(),
-							dst_operand.into(),
-							ByteLoadOperation::no_update(),
-						)))
-					}
-				}
-				[false, true] /* x = 2 */ => {
-					let decoded_operand = DecodedInstructionOperand::from_opcode_part(z);
-					Ok(decode_byte_instruction(y, decoded_operand.into()))
-				}
-				[true, true] /* x = 3 */ => {
-					match z {
-						[false, false, false] /* z = 0 */ => {
-							match y {
-								[y0, y1, false] /* 0 <= y < 4 */ => {
-									let (flag, value) = decode_conditional([y0, y1]);
-									Ok(Box::new(ReturnInstruction::ret_conditional(flag, value)))
-								}
-								[false, false, true] /* y = 4 */ => {
-									let offset = load_next_u8(cpu)?;
-
-									Ok(Box::new(ByteLoadInstruction::new(
-										ByteSource::read_from_acc(),
-										ByteDestination::AddressImmediate(
-											IO_REGISTERS_MAPPING_START.wrapping_add(offset.into())
-										),
-										ByteLoadOperation::no_update(),
-									)))
-								}
-								[true, false, true] /* y = 5 */ => {
-									let delta = load_next_i8(cpu)?;
-
-									Ok(Box::new(AddSignedByteToDoubleByte::add_to_sp(delta)))
-								}
-								[false, true, true] /* y = 6 */ => {
-									let offset = load_next_u8(cpu)?;
-
-									Ok(Box::new(ByteLoadInstruction::new(
-										ByteSource::AddressInImmediate(
-											IO_REGISTERS_MAPPING_START.wrapping_add(offset.into())
-										),
-										ByteDestination::write_to_acc(),
-										ByteLoadOperation::no_update(),
-									)))
-								}
-								[true, true, true] /* y = 7 */ => {
-									let offset = load_next_i8(cpu)?;
-
-									Ok(Box::new(AddSignedByteToDoubleByte::new(
-										DoubleByteSource::StackPointer,
-										DoubleByteDestination::DoubleRegister(DoubleRegisters::HL),
-										offset,
-									)))
-								}
-							}
-						}
-						[true, false, false] /* z = 1 */ => {
-							let (p, q) = decode_pq(y);
-
-							match q {
-								false => {
-									let decoded_double_operand = DecodedInstructionDoubleOperand::from_opcode_part_double_or_af(p);
-
-									Ok(Box::new(PopInstruction::new(
-										decoded_double_operand.into()
-									)))
-								}
-								true => {
-									match p {
-										[p0, false] /* 0 <= p < 2 */ => {
-											let enable_interrupts = p0;
-											Ok(Box::new(ReturnInstruction::new(
-												BranchCondition::Unconditional,
-												enable_interrupts,
-											)))
-										}
-										[false, true] /* p = 2 */ => {
-											Ok(Box::new(JumpInstruction::new(
-												JumpInstructionDestination::FromSource(
-													DoubleByteSource::DoubleRegister(DoubleRegisters::HL)
-												),
-												BranchCondition::Unconditional,
-											)))
-										}
-										[true, true] /* p = 3 */ => {
-											Ok(Box::new(DoubleByteLoadInstruction::new(
-												DoubleByteSource::DoubleRegister(DoubleRegisters::HL),
-												DoubleByteDestination::StackPointer,
-												DoubleByteLoadOperation::new(),
-											)))
-										}
-									}
-								}
-							}
-						}
-						[false, true, false] /* z = 2 */ => {
-							match y {
-								[y0, y1, false] /* 0 <= y < 4 */ => {
-									let (flag, value) = decode_conditional([y0, y1]);
-									let branch_conditon = BranchCondition::TestFlag { flag, branch_if_equals: value };
-									let address = load_next_u16(cpu)?;
-
-									Ok(Box::new(JumpInstruction::new(
-										JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(address)),
-										branch_conditon,
-									)))
-								}
-								[false, y1, true] /* y in {4, 6} */ => {
-									let base = IO_REGISTERS_MAPPING_START;
-									let offset = SingleRegisters::C;
-
-
-									let (src, dst) = match y1 {
-										false => {
-											(
-												ByteSource::read_from_acc(),
-												ByteDestination::OffsetAddressInRegister { base, offset }
-											)
-										}
-										true => {
-											(
-												ByteSource::OffsetAddressInRegister { base, offset },
-												ByteDestination::write_to_acc()
-											)
-										}
-									};
-
-									Ok(Box::new(ByteLoadInstruction::new(
-										src, dst, ByteLoadOperation::no_update(),
-									)))
-								}
-								[true, y1, true] /* y in {5, 7} */ => {
-									let address = load_next_u16(cpu)?;
-
-									let (src, dst) = match y1 {
-										false => {
-											(
-												ByteSource::read_from_acc(),
-												ByteDestination::AddressImmediate(address)
-											)
-										}
-										true => (
-											ByteSource::AddressInImmediate(address),
-											ByteDestination::write_to_acc()
-										)
-									};
-
-									Ok(Box::new(ByteLoadInstruction::new(
-										src, dst, ByteLoadOperation::no_update(),
-									)))
-								}
-							}
-						}
-						[true, true, false] /* z = 3 */ => {
-							match y {
-								[false, false, false] /* y = 0 */ => {
-									let address = load_next_u16(cpu)?;
-									Ok(Box::new(JumpInstruction::new(
-										JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(address)),
-										BranchCondition::Unconditional,
-									)))
-								}
-								[y0, true, true] /* 6 <= y < 8 */ => {
-									let enable_interrupts = y0;
-
-									Ok(Box::new(SetImeInstruction::new(
-										enable_interrupts
-									)))
-								}
-								_ => {
-									Err(ExecutionError::InvalidOpcode(opcode))
-								}
-							}
-						}
-						[false, false, true] /* z = 4 */ => {
-							match y {
-								[y0, y1, false] /* 0 <= y < 4 */ => {
-									let address = load_next_u16(cpu)?;
-									let (flag, branch_if_equals) = decode_conditional([y0, y1]);
-									Ok(Box::new(CallInstruction::call_conditional(
-										flag, branch_if_equals, address,
-									)))
-								}
-								_ => Err(ExecutionError::InvalidOpcode(opcode))
-							}
-						}
-						[true, false, true] /* z = 5 */ => {
-							let (p, q) = decode_pq(y);
-
-							match q {
-								false => {
-									let decoded_operand = DecodedInstructionDoubleOperand::from_opcode_part_double_or_af(p);
-									Ok(Box::new(PushInstruction::new(decoded_operand.into())))
-								}
-								true => {
-									match p {
-										[false, false] /* p = 0 */ => {
-											let address = load_next_u16(cpu)?;
-											Ok(Box::new(CallInstruction::new(
-												BranchCondition::Unconditional,
-												address,
-											)))
-										}
-										_ => Err(ExecutionError::InvalidOpcode(opcode))
-									}
-								}
-							}
-						}
-						[false, true, true] /* z = 6 */ => {
-							let immediate = load_next_u8(cpu)?;
-							Ok(decode_byte_instruction(y, ByteSource::Immediate(immediate)))
-						}
-						[true, true, true] /* z = 7 */ => {
-							Ok(Box::new(CallInstruction::restart(y)))
-						}
-					}
+		2 => {
+			// (BC), (DE), then (HL+) and (HL-)
+			let (register_address, update_type) = match op.p() {
+				0 => (DoubleRegisters::BC, None),
+				1 => (DoubleRegisters::DE, None),
+				2 => (DoubleRegisters::HL, Some(IndexUpdateType::Increment)),
+				_ => (DoubleRegisters::HL, Some(IndexUpdateType::Decrement)),
+			};
+
+			let (destination, source) = if op.q() {
+				(
+					ByteDestination::write_to_acc(),
+					ByteSource::AddressInRegister(register_address),
+				)
+			} else {
+				(
+					ByteDestination::AddressInRegister(register_address),
+					ByteSource::read_from_acc(),
+				)
+			};
+
+			match update_type {
+				None => Ok(InstructionKind::from(ByteLoadInstruction::just_load(
+					source,
+					destination,
+				))),
+				Some(update_type) => {
+					let update = ByteLoadUpdate::new(DoubleRegisters::HL, update_type);
+					let operation = ByteLoadOperation::with_update(update);
+
+					Ok(InstructionKind::from(ByteLoadInstruction::new(
+						source,
+						destination,
+						operation,
+					)))
 				}
 			}
 		}
+		3 => {
+			let decoded_double_operator = DecodedInstructionDoubleOperand::from_index_or_sp(op.p());
+
+			let inc_or_dec_type = match op.q() {
+				false => IndexUpdateType::Increment,
+				true => IndexUpdateType::Decrement,
+			};
+
+			Ok(InstructionKind::from(IncOrDecDoubleInstruction::new(
+				decoded_double_operator.into(),
+				decoded_double_operator.into(),
+				IncOrDecDoubleByteOperation::new(inc_or_dec_type),
+			)))
+		}
+		z @ (4 | 5) => {
+			let inc_dec_op_type = match z {
+				4 => IndexUpdateType::Increment,
+				_ => IndexUpdateType::Decrement,
+			};
+
+			let decoded_operand = DecodedInstructionOperand::from_index(op.y());
+
+			Ok(InstructionKind::from(IncOrDecByteInstruction::new(
+				decoded_operand.into(),
+				decoded_operand.into(),
+				IncOrDecByteOperation::new(inc_dec_op_type),
+			)))
+		}
+		6 => {
+			let decoded_operand = DecodedInstructionOperand::from_index(op.y());
+			let immediate = load_next_u8(reader)?;
+
+			Ok(InstructionKind::from(ByteLoadInstruction::new(
+				ByteSource::Immediate(immediate),
+				decoded_operand.into(),
+				ByteLoadOperation::no_update(),
+			)))
+		}
+		_ => match op.y() {
+			y @ 0..=3 => {
+				let shift_direction = match y & 1 {
+					0 => ShiftDirection::Left,
+					_ => ShiftDirection::Right,
+				};
+
+				let shift_type = match y >> 1 {
+					0 => ShiftType::Rotate,
+					_ => ShiftType::RotateWithCarry,
+				};
+
+				Ok(InstructionKind::from(ByteShiftInstruction::new(
+					ByteSource::read_from_acc(),
+					ByteDestination::write_to_acc(),
+					ByteShiftOperation::accumulator(shift_direction, shift_type),
+				)))
+			}
+			4 => Ok(InstructionKind::from(DecimalAdjust::new())),
+			5 => Ok(InstructionKind::from(LogicalNegateInstruction::negate_acc())),
+			6 => Ok(InstructionKind::from(ChangeCarryFlagInstruction::new(
+				BitFlagChangeType::Write(true),
+			))),
+			_ => Ok(InstructionKind::from(ChangeCarryFlagInstruction::new(
+				BitFlagChangeType::Toggle,
+			))),
+		},
 	}
 }
 
-fn decode_pq(y: [bool; 3]) -> ([bool; 2], bool) {
-	let [y0, y1, y2] = y;
-	let q = y0;
-	let p = [y1, y2];
-	(p, q)
+// x = 3: returns, jumps and calls, stack operations, high page loads and immediate arithmetic
+#[inline(always)]
+fn decode_block_3(op: Opcode, reader: &mut InstructionReader) -> Result<InstructionKind, ExecutionError> {
+	match op.z() {
+		0 => match op.y() {
+			y @ 0..=3 => {
+				let (flag, value) = decode_conditional(y);
+				Ok(InstructionKind::from(ReturnInstruction::ret_conditional(flag, value)))
+			}
+			4 => {
+				let offset = load_next_u8(reader)?;
+
+				Ok(InstructionKind::from(ByteLoadInstruction::new(
+					ByteSource::read_from_acc(),
+					ByteDestination::AddressImmediate(IO_REGISTERS_MAPPING_START.wrapping_add(offset.into())),
+					ByteLoadOperation::no_update(),
+				)))
+			}
+			5 => {
+				let delta = load_next_i8(reader)?;
+
+				Ok(InstructionKind::from(AddSignedByteToDoubleByte::add_to_sp(delta)))
+			}
+			6 => {
+				let offset = load_next_u8(reader)?;
+
+				Ok(InstructionKind::from(ByteLoadInstruction::new(
+					ByteSource::AddressInImmediate(IO_REGISTERS_MAPPING_START.wrapping_add(offset.into())),
+					ByteDestination::write_to_acc(),
+					ByteLoadOperation::no_update(),
+				)))
+			}
+			_ => {
+				let offset = load_next_i8(reader)?;
+
+				Ok(InstructionKind::from(AddSignedByteToDoubleByte::new(
+					DoubleByteSource::StackPointer,
+					DoubleByteDestination::DoubleRegister(DoubleRegisters::HL),
+					offset,
+				)))
+			}
+		},
+		1 => {
+			if !op.q() {
+				let decoded_double_operand = DecodedInstructionDoubleOperand::from_index_or_af(op.p());
+
+				return Ok(InstructionKind::from(PopInstruction::new(
+					decoded_double_operand.into(),
+				)));
+			}
+
+			match op.p() {
+				p @ (0 | 1) => {
+					let enable_interrupts = p == 1;
+					Ok(InstructionKind::from(ReturnInstruction::new(
+						BranchCondition::Unconditional,
+						enable_interrupts,
+					)))
+				}
+				2 => Ok(InstructionKind::from(JumpInstruction::new(
+					JumpInstructionDestination::FromSource(DoubleByteSource::DoubleRegister(DoubleRegisters::HL)),
+					BranchCondition::Unconditional,
+				))),
+				_ => Ok(InstructionKind::from(DoubleByteLoadInstruction::new(
+					DoubleByteSource::DoubleRegister(DoubleRegisters::HL),
+					DoubleByteDestination::StackPointer,
+					DoubleByteLoadOperation::new(),
+				))),
+			}
+		}
+		2 => match op.y() {
+			y @ 0..=3 => {
+				let (flag, value) = decode_conditional(y);
+				let branch_conditon = BranchCondition::TestFlag {
+					flag,
+					branch_if_equals: value,
+				};
+				let address = load_next_u16(reader)?;
+
+				Ok(InstructionKind::from(JumpInstruction::new(
+					JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(address)),
+					branch_conditon,
+				)))
+			}
+			y @ (4 | 6) => {
+				let base = IO_REGISTERS_MAPPING_START;
+				let offset = SingleRegisters::C;
+
+				let (src, dst) = match y {
+					4 => (
+						ByteSource::read_from_acc(),
+						ByteDestination::OffsetAddressInRegister { base, offset },
+					),
+					_ => (
+						ByteSource::OffsetAddressInRegister { base, offset },
+						ByteDestination::write_to_acc(),
+					),
+				};
+
+				Ok(InstructionKind::from(ByteLoadInstruction::new(
+					src,
+					dst,
+					ByteLoadOperation::no_update(),
+				)))
+			}
+			y => {
+				let address = load_next_u16(reader)?;
+
+				let (src, dst) = match y {
+					5 => (ByteSource::read_from_acc(), ByteDestination::AddressImmediate(address)),
+					_ => (ByteSource::AddressInImmediate(address), ByteDestination::write_to_acc()),
+				};
+
+				Ok(InstructionKind::from(ByteLoadInstruction::new(
+					src,
+					dst,
+					ByteLoadOperation::no_update(),
+				)))
+			}
+		},
+		3 => match op.y() {
+			0 => {
+				let address = load_next_u16(reader)?;
+				Ok(InstructionKind::from(JumpInstruction::new(
+					JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(address)),
+					BranchCondition::Unconditional,
+				)))
+			}
+			y @ (6 | 7) => {
+				let enable_interrupts = y == 7;
+
+				Ok(InstructionKind::from(SetImeInstruction::new(enable_interrupts)))
+			}
+			_ => Err(ExecutionError::InvalidOpcode(op.0)),
+		},
+		4 => match op.y() {
+			y @ 0..=3 => {
+				let address = load_next_u16(reader)?;
+				let (flag, branch_if_equals) = decode_conditional(y);
+				Ok(InstructionKind::from(CallInstruction::call_conditional(
+					flag,
+					branch_if_equals,
+					address,
+				)))
+			}
+			_ => Err(ExecutionError::InvalidOpcode(op.0)),
+		},
+		5 => {
+			if !op.q() {
+				let decoded_operand = DecodedInstructionDoubleOperand::from_index_or_af(op.p());
+				return Ok(InstructionKind::from(PushInstruction::new(decoded_operand.into())));
+			}
+
+			match op.p() {
+				0 => {
+					let address = load_next_u16(reader)?;
+					Ok(InstructionKind::from(CallInstruction::new(
+						BranchCondition::Unconditional,
+						address,
+					)))
+				}
+				_ => Err(ExecutionError::InvalidOpcode(op.0)),
+			}
+		}
+		6 => {
+			let immediate = load_next_u8(reader)?;
+			Ok(decode_byte_instruction(op.y(), ByteSource::Immediate(immediate)))
+		}
+		_ => Ok(InstructionKind::from(CallInstruction::restart(op.y()))),
+	}
 }
 
-fn load_next_u16(cpu: &mut Cpu) -> Result<u16, ExecutionError> {
-	let low = cpu.next_byte()?;
-	let high = cpu.next_byte()?;
+fn load_next_u16(reader: &mut InstructionReader) -> Result<u16, ExecutionError> {
+	let low = reader.next_byte()?;
+	let high = reader.next_byte()?;
 
 	Ok(u16::from_le_bytes([low, high]))
 }
 
-fn load_next_i8(cpu: &mut Cpu) -> Result<i8, ExecutionError> {
-	let delta = cpu.next_byte()?;
+fn load_next_i8(reader: &mut InstructionReader) -> Result<i8, ExecutionError> {
+	let delta = reader.next_byte()?;
 	let delta = delta as i8;
 
 	Ok(delta)
 }
 
-fn load_next_u8(cpu: &mut Cpu) -> Result<u8, ExecutionError> {
-	cpu.next_byte()
+fn load_next_u8(reader: &mut InstructionReader) -> Result<u8, ExecutionError> {
+	reader.next_byte()
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -574,16 +787,19 @@ enum DecodedInstructionOperand {
 }
 
 impl DecodedInstructionOperand {
-	fn from_opcode_part(opcode_part: [bool; 3]) -> Self {
-		match opcode_part {
-			[false, false, false] => Self::SingleRegister(SingleRegisters::B), // 0 => B
-			[true, false, false] => Self::SingleRegister(SingleRegisters::C),  // 1 => C
-			[false, true, false] => Self::SingleRegister(SingleRegisters::D),  // 2 => D
-			[true, true, false] => Self::SingleRegister(SingleRegisters::E),   // 3 => E
-			[false, false, true] => Self::SingleRegister(SingleRegisters::H),  // 4 => H
-			[true, false, true] => Self::SingleRegister(SingleRegisters::L),   // 5 => L
-			[false, true, true] => Self::HlMemoryAddress,                      // 6 => (HL)
-			[true, true, true] => Self::SingleRegister(SingleRegisters::A),    // 7 => A
+	/// Register operand encoded in 3 opcode bits, in the order B, C, D, E, H, L, (HL), A. F can't be an operand:
+	/// index 6 is the byte at HL, even though F comes right after E in the register bank.
+	fn from_index(index: u8) -> Self {
+		match index {
+			0 => Self::SingleRegister(SingleRegisters::B),
+			1 => Self::SingleRegister(SingleRegisters::C),
+			2 => Self::SingleRegister(SingleRegisters::D),
+			3 => Self::SingleRegister(SingleRegisters::E),
+			4 => Self::SingleRegister(SingleRegisters::H),
+			5 => Self::SingleRegister(SingleRegisters::L),
+			6 => Self::HlMemoryAddress,
+			7 => Self::SingleRegister(SingleRegisters::A),
+			_ => unreachable!("Operand index {index} doesn't fit in 3 bits"),
 		}
 	}
 }
@@ -614,21 +830,23 @@ enum DecodedInstructionDoubleOperand {
 }
 
 impl DecodedInstructionDoubleOperand {
-	fn from_opcode_maybe_double(opcode_part: [bool; 2]) -> Option<DoubleRegisters> {
-		match opcode_part {
-			[false, false] => Some(DoubleRegisters::BC),
-			[true, false] => Some(DoubleRegisters::DE),
-			[false, true] => Some(DoubleRegisters::HL),
-			[true, true] => None,
+	// Pair encoded in 2 opcode bits, the last one being SP or AF depending on the instruction
+	fn from_index(index: u8) -> Option<DoubleRegisters> {
+		match index {
+			0 => Some(DoubleRegisters::BC),
+			1 => Some(DoubleRegisters::DE),
+			2 => Some(DoubleRegisters::HL),
+			3 => None,
+			_ => unreachable!("Double operand index {index} doesn't fit in 2 bits"),
 		}
 	}
 
-	fn from_opcode_part_double_or_sp(opcode_part: [bool; 2]) -> Self {
-		Self::from_opcode_maybe_double(opcode_part).map_or(Self::Sp, Self::DoubleRegister)
+	fn from_index_or_sp(index: u8) -> Self {
+		Self::from_index(index).map_or(Self::Sp, Self::DoubleRegister)
 	}
 
-	fn from_opcode_part_double_or_af(opcode_part: [bool; 2]) -> Self {
-		Self::from_opcode_maybe_double(opcode_part).map_or(Self::Af, Self::DoubleRegister)
+	fn from_index_or_af(index: u8) -> Self {
+		Self::from_index(index).map_or(Self::Af, Self::DoubleRegister)
 	}
 }
 
@@ -652,53 +870,556 @@ impl From<DecodedInstructionDoubleOperand> for DoubleByteDestination {
 	}
 }
 
-fn decode_xyz(opcode: u8) -> ([bool; 2], [bool; 3], [bool; 3]) {
-	let bits = byte_to_bits(opcode);
+#[inline(always)]
+fn decode_byte_instruction(op: u8, right: ByteSource) -> InstructionKind {
+	let dst = ByteDestination::write_to_acc();
 
-	let x = bits[6..8].try_into().unwrap();
-	let y = bits[3..6].try_into().unwrap();
-	let z = bits[0..3].try_into().unwrap();
+	let logical_operation_type = match op {
+		0..=3 => {
+			let use_carry = op & 1 != 0;
+			let operation_type = match op >> 1 {
+				0 => BinaryArithmeticOperationType::Add,
+				_ => BinaryArithmeticOperationType::Sub,
+			};
 
-	(x, y, z)
+			let operation = BinaryArithmeticOperation::new(operation_type, use_carry);
+			return InstructionKind::from(BinaryArithmeticInstruction::new(
+				ByteSource::read_from_acc(),
+				right,
+				dst,
+				operation,
+			));
+		}
+		4 => BinaryLogicalOperationType::And,
+		5 => BinaryLogicalOperationType::Xor,
+		6 => BinaryLogicalOperationType::Or,
+		_ => return InstructionKind::from(CompareInstruction::new(ByteSource::read_from_acc(), right)),
+	};
+
+	let logical_operation = BinaryLogicalOperation::new(logical_operation_type);
+	InstructionKind::from(BinaryLogicalInstruction::new(
+		ByteSource::read_from_acc(),
+		right,
+		dst,
+		logical_operation,
+	))
 }
 
-fn decode_byte_instruction(op_part: [bool; 3], right: ByteSource) -> Box<dyn Instruction> {
-	let dst = ByteDestination::write_to_acc();
+// cc: bit 0 is the value the flag is tested against, bit 1 selects the flag (NZ, Z, NC, C)
+fn decode_conditional(cc: u8) -> (BitFlags, bool) {
+	let value = cc & 1 != 0;
+	let flag = match cc >> 1 {
+		0 => BitFlags::Zero,
+		_ => BitFlags::Carry,
+	};
+	(flag, value)
+}
 
-	match op_part {
-		[op0, op1, false] /* 0 <= op < 4 */ => {
-			let use_carry = op1;
-			let operation_type = match op0 {
-				false => BinaryArithmeticOperationType::Add,
-				true => BinaryArithmeticOperationType::Sub
-			};
+#[cfg(test)]
+mod tests {
+	use crate::hardware::interrupts::Interrupt;
+	use crate::hardware::ram::{Rom, BOOTSTRAP_RAM_SIZE, WORKING_RAM_START};
 
-			let operation = BinaryArithmeticOperation::new(operation_type, use_carry);
-			Box::new(BinaryArithmeticInstruction::new(ByteSource::read_from_acc(), right, dst, operation))
-		}
-		[op0, op1, true] /* 4 <= z < 8 */ => {
-			let maybe_logical_operation_type = match [op0, op1] {
-				[false, false] => Some(BinaryLogicalOperationType::And),
-				[true, false] => Some(BinaryLogicalOperationType::Xor),
-				[false, true] => Some(BinaryLogicalOperationType::Or),
-				[true, true] => None,
-			};
+	use super::*;
 
-			if let Some(logical_operation_type) = maybe_logical_operation_type {
-				let logical_operation = BinaryLogicalOperation::new(logical_operation_type);
-				Box::new(BinaryLogicalInstruction::new(ByteSource::read_from_acc(), right, dst, logical_operation))
-			} else {
-				Box::new(CompareInstruction::new(ByteSource::read_from_acc(), right))
+	#[test]
+	fn opcode_fields() {
+		let op = Opcode(0b11_010_001);
+		assert_eq!((op.x(), op.y(), op.z()), (3, 2, 1));
+		assert_eq!((op.p(), op.q()), (1, false));
+
+		let op = Opcode(0b00_111_110);
+		assert_eq!((op.x(), op.y(), op.z()), (0, 7, 6));
+		assert_eq!((op.p(), op.q()), (3, true));
+	}
+
+	#[test]
+	fn restart_vectors() {
+		let vectors = [
+			(0xC7, 0x00, "rst 0x00"),
+			(0xCF, 0x08, "rst 0x08"),
+			(0xD7, 0x10, "rst 0x10"),
+			(0xDF, 0x18, "rst 0x18"),
+			(0xE7, 0x20, "rst 0x20"),
+			(0xEF, 0x28, "rst 0x28"),
+			(0xF7, 0x30, "rst 0x30"),
+			(0xFF, 0x38, "rst 0x38"),
+		];
+
+		for (opcode, vector, display) in vectors {
+			let mut cpu = Cpu::new();
+			cpu.pc.write(WORKING_RAM_START + 0x100);
+			cpu.sp.write(WORKING_RAM_START + 0x10);
+			cpu.write_byte(WORKING_RAM_START + 0x100, opcode).unwrap();
+
+			let instruction = fetch_and_decode(&mut cpu).unwrap();
+			assert_eq!(instruction.to_string(), display, "Display of {opcode:#04X}");
+
+			instruction.execute(&mut cpu).unwrap();
+
+			assert_eq!(cpu.pc.read(), vector, "PC after {opcode:#04X}");
+			assert_eq!(cpu.sp.read(), WORKING_RAM_START + 0x0E, "SP after {opcode:#04X}");
+			assert_eq!(
+				cpu.mapped_ram.read_double_byte(WORKING_RAM_START + 0x0E).unwrap(),
+				WORKING_RAM_START + 0x101,
+				"Return address pushed by {opcode:#04X}"
+			);
+		}
+	}
+
+	fn decode_at(cpu: &mut Cpu, bytes: &[u8]) -> DecodedInstruction {
+		for (offset, byte) in bytes.iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, *byte).unwrap();
+		}
+		cpu.pc.write(WORKING_RAM_START);
+
+		fetch_and_decode(cpu).unwrap()
+	}
+
+	#[test]
+	fn disassemble_all_opcodes() {
+		for prefixed in [false, true] {
+			for opcode in 0..=u8::MAX {
+				let text = disassemble(prefixed, opcode, &[0x12, 0x34]);
+				assert!(!text.is_empty(), "prefixed {prefixed}, opcode {opcode:#04X}");
 			}
 		}
 	}
-}
 
-fn decode_conditional(op_part: [bool; 2]) -> (BitFlags, bool) {
-	let value = op_part[1];
-	let flag = match op_part[0] {
-		false => BitFlags::Zero,
-		true => BitFlags::Carry,
-	};
-	(flag, value)
+	#[test]
+	fn disassemble_unprefixed() {
+		let cases: [(u8, &[u8], &str); 16] = [
+			(0x00, &[], "NOP"),
+			(0x01, &[0x34, 0x12], "LD BC,0x1234"),
+			(0x06, &[0x42], "LD B,0x42"),
+			(0x08, &[0x00, 0xC0], "LD (0xC000),SP"),
+			(0x2A, &[], "LD A,(HL+)"),
+			(0x32, &[], "LD (HL-),A"),
+			(0x41, &[], "LD B,C"),
+			(0x76, &[], "HALT"),
+			(0x86, &[], "ADD A,(HL)"),
+			(0xC5, &[], "PUSH BC"),
+			(0xCD, &[0x34, 0x12], "CALL 0x1234"),
+			(0xD3, &[], "DB 0xD3"),
+			(0xE0, &[0x42], "LDH (0xFF42),A"),
+			(0xE2, &[], "LD (C),A"),
+			(0xF8, &[0x05], "LD HL,SP+0x05"),
+			(0xFF, &[], "RST 0x38"),
+		];
+
+		for (opcode, operands, expected) in cases {
+			assert_eq!(disassemble(false, opcode, operands), expected, "opcode {opcode:#04X}");
+		}
+	}
+
+	#[test]
+	fn disassemble_prefixed() {
+		let cases: [(u8, &str); 6] = [
+			(0x00, "RLC B"),
+			(0x37, "SWAP A"),
+			(0x3E, "SRL (HL)"),
+			(0x58, "BIT 3,B"),
+			(0x86, "RES 0,(HL)"),
+			(0xFF, "SET 7,A"),
+		];
+
+		for (opcode, expected) in cases {
+			assert_eq!(disassemble(true, opcode, &[]), expected, "opcode {opcode:#04X}");
+		}
+	}
+
+	#[test]
+	fn disassemble_relative() {
+		assert_eq!(disassemble(false, 0x18, &[0x05]), "JR +0x05");
+		assert_eq!(disassemble(false, 0x20, &[0x05]), "JR NZ,+0x05");
+		assert_eq!(disassemble(false, 0x38, &[0xFD]), "JR C,-0x03");
+		assert_eq!(disassemble(false, 0x18, &[0x80]), "JR -0x80");
+		assert_eq!(disassemble(false, 0xE8, &[0xFE]), "ADD SP,-0x02");
+		// Missing immediates read as zero
+		assert_eq!(disassemble(false, 0x20, &[]), "JR NZ,+0x00");
+	}
+
+	#[test]
+	fn boxed_instruction_matches() {
+		let mut cpu = Cpu::new();
+
+		let store = decode_at(&mut cpu, &[0xEA, 0x00, 0xC1]);
+		let display = store.to_string();
+		assert_eq!(store.instruction().to_string(), display);
+
+		let boxed = store.into_boxed();
+		assert_eq!(boxed.to_string(), display);
+		cpu.write_register(SingleRegisters::A, 0x42);
+		boxed.execute(&mut cpu).unwrap();
+		assert_eq!(cpu.read_byte(0xC100).unwrap(), 0x42);
+	}
+
+	#[test]
+	fn instruction_timing() {
+		let mut cpu = Cpu::new();
+
+		let nop = decode_at(&mut cpu, &[0x00]);
+		assert_eq!((nop.length, nop.execute(&mut cpu).unwrap()), (1, 4));
+
+		let store = decode_at(&mut cpu, &[0xEA, 0x00, 0xC1]);
+		assert_eq!((store.length, store.execute(&mut cpu).unwrap()), (3, 16));
+
+		let bit_hl = decode_at(&mut cpu, &[0xCB, 0x46]);
+		assert_eq!((bit_hl.length, bit_hl.cycles), (2, 12));
+
+		cpu.set_sp(0xD000);
+		cpu.write_flag(BitFlags::Zero, false);
+		let call = decode_at(&mut cpu, &[0xC4, 0x00, 0xC1]);
+		assert_eq!((call.length, call.execute(&mut cpu).unwrap()), (3, 24));
+		assert_eq!(cpu.current_pc(), 0xC100);
+
+		cpu.write_flag(BitFlags::Zero, true);
+		let call = decode_at(&mut cpu, &[0xC4, 0x00, 0xC1]);
+		assert_eq!(call.execute(&mut cpu).unwrap(), 12);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 3);
+	}
+
+	#[test]
+	fn peek_matches_fetch() {
+		let mut cpu = Cpu::new();
+
+		for bytes in [&[0x00][..], &[0xCB, 0x7E], &[0xC4, 0x34, 0x12], &[0x20, 0xFE]] {
+			decode_at(&mut cpu, bytes);
+			cpu.set_pc(WORKING_RAM_START);
+
+			let (peeked, next_pc) = peek_and_decode(&cpu).unwrap();
+			assert_eq!(cpu.current_pc(), WORKING_RAM_START);
+
+			let fetched = fetch_and_decode(&mut cpu).unwrap();
+			assert_eq!(next_pc, cpu.current_pc());
+			assert_eq!(peeked.length, fetched.length);
+			assert_eq!(peeked.to_string(), fetched.to_string());
+			assert_eq!(peeked.bytes(), bytes);
+		}
+	}
+
+	#[test]
+	fn store_sp_to_immediate_address() {
+		let mut cpu = Cpu::new();
+		cpu.set_sp(0xBEEF);
+
+		let instruction = decode_at(&mut cpu, &[0x08, 0x00, 0xC1]);
+		assert_eq!(instruction.to_string(), "ld (0xC100) <- SP");
+		instruction.execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.read_byte(0xC100).unwrap(), 0xEF);
+		assert_eq!(cpu.read_byte(0xC101).unwrap(), 0xBE);
+		assert_eq!(cpu.current_sp(), 0xBEEF);
+	}
+
+	#[test]
+	fn immediates_are_little_endian() {
+		let mut cpu = Cpu::new();
+		cpu.set_sp(0xD000);
+
+		decode_at(&mut cpu, &[0xC3, 0x34, 0x12]).execute(&mut cpu).unwrap();
+		assert_eq!(cpu.current_pc(), 0x1234, "jp nn");
+
+		decode_at(&mut cpu, &[0xCD, 0x78, 0x56]).execute(&mut cpu).unwrap();
+		assert_eq!(cpu.current_pc(), 0x5678, "call nn");
+
+		for (opcode, register) in [
+			(0x01, DoubleRegisters::BC),
+			(0x11, DoubleRegisters::DE),
+			(0x21, DoubleRegisters::HL),
+		] {
+			decode_at(&mut cpu, &[opcode, 0xCD, 0xAB]).execute(&mut cpu).unwrap();
+			assert_eq!(cpu.read_double_register(register), 0xABCD, "ld {register}, nn");
+		}
+
+		decode_at(&mut cpu, &[0x31, 0x02, 0xDF]).execute(&mut cpu).unwrap();
+		assert_eq!(cpu.current_sp(), 0xDF02, "ld SP, nn");
+
+		cpu.write_register(SingleRegisters::A, 0x42);
+		decode_at(&mut cpu, &[0xEA, 0x10, 0xC2]).execute(&mut cpu).unwrap();
+		assert_eq!(cpu.read_byte(0xC210).unwrap(), 0x42, "ld (nn), A");
+	}
+
+	#[test]
+	fn operands_never_flag_register() {
+		let mut seen_hl = false;
+
+		for index in 0..8 {
+			let operand = DecodedInstructionOperand::from_index(index);
+			seen_hl |= operand == DecodedInstructionOperand::HlMemoryAddress;
+
+			let source = ByteSource::from(operand);
+			let destination = ByteDestination::from(operand);
+			assert_ne!(source, ByteSource::SingleRegister(SingleRegisters::F), "source {index}");
+			assert_ne!(
+				destination,
+				ByteDestination::SingleRegister(SingleRegisters::F),
+				"destination {index}"
+			);
+		}
+
+		assert!(seen_hl);
+		assert_eq!(
+			DecodedInstructionOperand::from_index(6),
+			DecodedInstructionOperand::HlMemoryAddress
+		);
+	}
+
+	#[test]
+	fn invalid_opcodes() {
+		const INVALID: [u8; 11] = [0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD];
+
+		for opcode in 0..=u8::MAX {
+			let mut cpu = Cpu::new();
+			cpu.write_byte(WORKING_RAM_START, opcode).unwrap();
+			cpu.set_pc(WORKING_RAM_START);
+
+			match fetch_and_decode(&mut cpu) {
+				Err(ExecutionError::InvalidOpcode(invalid)) => {
+					assert!(INVALID.contains(&opcode), "{opcode:#04X} decoded as invalid");
+					assert_eq!(invalid, opcode);
+				}
+				Ok(_) => assert!(!INVALID.contains(&opcode), "{opcode:#04X} decoded as valid"),
+				Err(err) => panic!("{opcode:#04X} failed to decode: {err}"),
+			}
+		}
+	}
+
+	#[test]
+	fn branch_conditions() {
+		let mut cpu = Cpu::new();
+
+		for (opcode, expected) in [
+			(0xC0, "ret nz"),
+			(0xC8, "ret z"),
+			(0xD0, "ret nc"),
+			(0xD8, "ret c"),
+			(0xC2, "jp nz, 0x0000"),
+			(0xCA, "jp z, 0x0000"),
+			(0xD2, "jp nc, 0x0000"),
+			(0xDA, "jp c, 0x0000"),
+			(0xC4, "call nz, 0x0000"),
+			(0xCC, "call z, 0x0000"),
+			(0xD4, "call nc, 0x0000"),
+			(0xDC, "call c, 0x0000"),
+			(0x20, "jr nz, PC+0x0 ; → 0xC002"),
+			(0x28, "jr z, PC+0x0 ; → 0xC002"),
+			(0x30, "jr nc, PC+0x0 ; → 0xC002"),
+			(0x38, "jr c, PC+0x0 ; → 0xC002"),
+		] {
+			assert_eq!(
+				decode_at(&mut cpu, &[opcode, 0, 0]).to_string(),
+				expected,
+				"{opcode:#04X}"
+			);
+		}
+	}
+
+	#[test]
+	fn arithmetic_operations() {
+		let mut cpu = Cpu::new();
+
+		for (opcode, expected) in [
+			(0x80, "add A <- A, B"),
+			(0x88, "adc A <- A, B"),
+			(0x90, "sub A <- A, B"),
+			(0x98, "sbc A <- A, B"),
+			(0xC6, "add A <- A, 0x00"),
+			(0xCE, "adc A <- A, 0x00"),
+			(0xD6, "sub A <- A, 0x00"),
+			(0xDE, "sbc A <- A, 0x00"),
+		] {
+			assert_eq!(decode_at(&mut cpu, &[opcode, 0]).to_string(), expected, "{opcode:#04X}");
+		}
+	}
+
+	#[test]
+	fn bytes_as_fetched() {
+		let mut cpu = Cpu::new();
+		cpu.write_register(SingleRegisters::A, 0x55);
+
+		// ld (0xC001), A overwrites its own address operand, then jp 0xC000 runs it again
+		let program = [0xEA, 0x01, 0xC0, 0xC3, 0x00, 0xC0];
+		decode_at(&mut cpu, &program);
+		cpu.set_pc(WORKING_RAM_START);
+
+		let mut trace = Vec::new();
+		for _ in 0..3 {
+			let instruction = fetch_and_decode(&mut cpu).unwrap();
+			trace.push(instruction.bytes().to_vec());
+			instruction.execute(&mut cpu).unwrap();
+		}
+
+		assert_eq!(trace[0], [0xEA, 0x01, 0xC0]);
+		assert_eq!(trace[1], [0xC3, 0x00, 0xC0]);
+		assert_eq!(trace[2], [0xEA, 0x55, 0xC0]);
+
+		assert_eq!(decode_at(&mut cpu, &[0xCB, 0x7E]).bytes(), [0xCB, 0x7E]);
+	}
+
+	#[test]
+	#[cfg(not(external_boot_rom))]
+	fn decode_bootstrap_from_slice() {
+		use crate::hardware::ram::BOOTSTRAP_DATA;
+
+		// Up to the jump to the last instruction of the stub
+		const STUB_ENTRY_END: usize = 0x1D;
+
+		let mut offset = 0;
+		let mut last = String::new();
+		while offset < STUB_ENTRY_END {
+			let (instruction, length) = decode_from_slice(BOOTSTRAP_DATA, offset).unwrap();
+			assert_eq!(usize::from(instruction.length), length);
+			assert_eq!(instruction.bytes(), &BOOTSTRAP_DATA[offset..offset + length]);
+
+			last = instruction.to_string();
+			offset += length;
+		}
+
+		assert_eq!(offset, STUB_ENTRY_END);
+		assert_eq!(last, "jp 0x00FE");
+	}
+
+	#[test]
+	fn decode_from_slice_errors() {
+		assert_eq!(
+			decode_from_slice(&[0x01, 0x34], 0).unwrap_err(),
+			DecodeError::Truncated { offset: 2 }
+		);
+		assert_eq!(
+			decode_from_slice(&[0xCB], 0).unwrap_err(),
+			DecodeError::Truncated { offset: 1 }
+		);
+		assert_eq!(
+			decode_from_slice(&[0x00], 4).unwrap_err(),
+			DecodeError::Truncated { offset: 4 }
+		);
+		assert_eq!(
+			decode_from_slice(&[0x00, 0xD3], 1).unwrap_err(),
+			DecodeError::InvalidOpcode(0xD3)
+		);
+	}
+
+	#[test]
+	fn relative_jump_target_fixed_at_decode() {
+		let mut cpu = Cpu::new();
+
+		let instruction = decode_at(&mut cpu, &[0x18, 0x05]);
+		cpu.pc.write(0x0000);
+		instruction.execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.pc.read(), WORKING_RAM_START + 2 + 5);
+	}
+
+	#[test]
+	fn prefixed_and_unprefixed_operands_match() {
+		let mut cpu = Cpu::new();
+
+		for z in 0..8 {
+			// LD A, z
+			let load = decode_at(&mut cpu, &[0x78 | z]).to_string();
+			let (_, load_operand) = load.split_once(" <- ").unwrap();
+
+			// RLC z, which reads and writes back the same operand
+			let rotate = decode_at(&mut cpu, &[0xCB, z]).to_string();
+
+			assert_eq!(rotate, format!("rlc {load_operand} <- {load_operand}"), "z = {z}");
+		}
+	}
+
+	#[test]
+	fn push_and_pop_use_af_table() {
+		let opcodes = [
+			(0xC1, "pop BC"),
+			(0xD1, "pop DE"),
+			(0xE1, "pop HL"),
+			(0xF1, "pop AF"),
+			(0xC5, "push BC"),
+			(0xD5, "push DE"),
+			(0xE5, "push HL"),
+			(0xF5, "push AF"),
+		];
+
+		let mut cpu = Cpu::new();
+		for (opcode, expected) in opcodes {
+			assert_eq!(decode_at(&mut cpu, &[opcode]).to_string(), expected, "{opcode:#04X}");
+		}
+	}
+
+	#[test]
+	fn loads_and_arithmetic_use_sp_table() {
+		let mut cpu = Cpu::new();
+
+		// ld SP, 0x1234
+		assert_eq!(decode_at(&mut cpu, &[0x31, 0x34, 0x12]).to_string(), "ld SP <- 0x1234");
+		// add HL, SP
+		assert_eq!(decode_at(&mut cpu, &[0x39]).to_string(), "add HL <- HL, SP");
+	}
+
+	// The boot ROM at 0x0000 supplies the bytes fetched past 0xFFFF, IE at 0xFFFF holds the one before
+	fn boundary_cpu(ie: u8, first_boot_byte: u8) -> Cpu {
+		let mut boot_rom = [0; BOOTSTRAP_RAM_SIZE];
+		boot_rom[0] = first_boot_byte;
+		let mut cpu = Cpu::with_boot_rom(boot_rom);
+		cpu.write_byte(0xFFFF, ie).unwrap();
+
+		cpu
+	}
+
+	#[test]
+	fn immediate_wraps_past_top_of_memory() {
+		// ld A, d8
+		let mut cpu = boundary_cpu(0x3E, 0x42);
+		cpu.pc.write(0xFFFF);
+
+		fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.read_register(SingleRegisters::A), 0x42);
+		assert_eq!(cpu.pc.read(), 0x0001);
+	}
+
+	#[test]
+	fn jump_address_straddles_top_of_memory() {
+		// jp 0xC134, the low byte in IE and the high one in the boot ROM
+		let mut cpu = boundary_cpu(0x34, 0xC1);
+		cpu.write_byte(0xFFFE, 0xC3).unwrap();
+		cpu.pc.write(0xFFFE);
+
+		fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.pc.read(), 0xC134);
+	}
+
+	#[test]
+	fn call_returns_past_top_of_memory() {
+		// call 0xC100, the whole instruction before the wrap
+		let mut cpu = boundary_cpu(0xC1, 0x00);
+		cpu.write_byte(0xFFFD, 0xCD).unwrap();
+		cpu.write_byte(0xFFFE, 0x00).unwrap();
+		cpu.pc.write(0xFFFD);
+		cpu.sp.write(0xD000);
+
+		fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.pc.read(), 0xC100);
+		assert_eq!(cpu.sp.read(), 0xCFFE);
+		assert_eq!(cpu.mapped_ram.read_double_byte(0xCFFE).unwrap(), 0x0000);
+	}
+
+	#[test]
+	fn halt_bug_at_top_of_memory() {
+		// halt with IME clear and the timer pending, then ld A, d8 from IE, read twice
+		let mut cpu = boundary_cpu(0x3E, 0x42);
+		cpu.write_byte(0xFFFE, 0x76).unwrap();
+		cpu.pc.write(0xFFFE);
+		cpu.set_ime(false);
+		cpu.request_interrupt(Interrupt::Timer).unwrap();
+
+		cpu.step().unwrap();
+		assert!(!cpu.halted());
+		cpu.step().unwrap();
+
+		assert_eq!(cpu.read_register(SingleRegisters::A), 0x3E);
+		assert_eq!(cpu.pc.read(), 0x0000);
+	}
 }
@@ -0,0 +1,80 @@
+// One decoding function per opcode, so fetching indexes a table instead of walking `decode_opcode`'s match. Each entry
+// is `decode_opcode` with the opcode fixed at compile time, keeping it as the single source of truth.
+
+use crate::decoder::{decode_opcode, DecodedInstructionPrefix, InstructionReader};
+use crate::instructions::kind::InstructionKind;
+use crate::instructions::ExecutionError;
+
+type DecodeThunk = fn(&mut InstructionReader) -> Result<InstructionKind, ExecutionError>;
+
+fn unprefixed<const OPCODE: u8>(reader: &mut InstructionReader) -> Result<InstructionKind, ExecutionError> {
+	decode_opcode(None, OPCODE, reader)
+}
+
+fn prefixed<const OPCODE: u8>(reader: &mut InstructionReader) -> Result<InstructionKind, ExecutionError> {
+	decode_opcode(Some(DecodedInstructionPrefix::CB), OPCODE, reader)
+}
+
+// Expands to the 256 instances of a thunk, one row of 16 opcodes at a time
+macro_rules! thunks {
+	($thunk:ident) => {
+		thunks!(@rows $thunk [] 0x00 0x10 0x20 0x30 0x40 0x50 0x60 0x70 0x80 0x90 0xA0 0xB0 0xC0 0xD0 0xE0 0xF0)
+	};
+	(@rows $thunk:ident [$($done:expr,)*] $row:literal $($rest:literal)*) => {
+		thunks!(@rows $thunk [
+			$($done,)*
+			$thunk::<{ $row }>, $thunk::<{ $row + 0x1 }>, $thunk::<{ $row + 0x2 }>, $thunk::<{ $row + 0x3 }>,
+			$thunk::<{ $row + 0x4 }>, $thunk::<{ $row + 0x5 }>, $thunk::<{ $row + 0x6 }>, $thunk::<{ $row + 0x7 }>,
+			$thunk::<{ $row + 0x8 }>, $thunk::<{ $row + 0x9 }>, $thunk::<{ $row + 0xA }>, $thunk::<{ $row + 0xB }>,
+			$thunk::<{ $row + 0xC }>, $thunk::<{ $row + 0xD }>, $thunk::<{ $row + 0xE }>, $thunk::<{ $row + 0xF }>,
+		] $($rest)*)
+	};
+	(@rows $thunk:ident [$($done:expr,)*]) => {
+		[$($done,)*]
+	};
+}
+
+static UNPREFIXED: [DecodeThunk; 0x100] = thunks!(unprefixed);
+static PREFIXED: [DecodeThunk; 0x100] = thunks!(prefixed);
+
+pub(super) fn dispatch_opcode(
+	prefix: Option<DecodedInstructionPrefix>,
+	opcode: u8,
+	reader: &mut InstructionReader,
+) -> Result<InstructionKind, ExecutionError> {
+	let table = match prefix {
+		Some(DecodedInstructionPrefix::CB) => &PREFIXED,
+		None => &UNPREFIXED,
+	};
+
+	table[usize::from(opcode)](reader)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::decoder::fetch::SliceCursor;
+
+	use super::*;
+
+	// Every table entry must decode exactly like the match it was built from
+	#[test]
+	fn matches_opcode_decoder() {
+		for operands in [[0x34, 0x12], [0xFE, 0x80]] {
+			for prefix in [None, Some(DecodedInstructionPrefix::CB)] {
+				for opcode in 0..=u8::MAX {
+					let mut cursor = SliceCursor::new(&operands, 0);
+					let mut reader = InstructionReader::new(&mut cursor);
+					let dispatched = format!("{:?}", dispatch_opcode(prefix, opcode, &mut reader));
+					let consumed = reader.pc();
+
+					let mut match_cursor = SliceCursor::new(&operands, 0);
+					let mut match_reader = InstructionReader::new(&mut match_cursor);
+					let matched = format!("{:?}", decode_opcode(prefix, opcode, &mut match_reader));
+
+					assert_eq!(dispatched, matched, "prefix {prefix:?}, opcode {opcode:#04X}");
+					assert_eq!(consumed, match_reader.pc(), "prefix {prefix:?}, opcode {opcode:#04X}");
+				}
+			}
+		}
+	}
+}
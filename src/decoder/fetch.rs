@@ -0,0 +1,93 @@
+use crate::hardware::cpu::Cpu;
+use crate::hardware::ram::RamError;
+use crate::instructions::ExecutionError;
+
+/// Where the decoder reads instruction bytes from
+pub(super) trait ByteFetcher {
+	/// Address of the next byte, which relative jumps are based on
+	fn address(&self) -> u16;
+
+	fn next_byte(&mut self) -> Result<u8, ExecutionError>;
+}
+
+// Memory as seen by the CPU, from PC onwards. Only the cursor moves, so the CPU's own PC is left alone.
+// Like the address bus, it wraps from 0xFFFF to 0x0000: an instruction at the top of memory takes its operands from
+// the boot ROM while it is mapped, and the HALT bug repeating 0xFFFF reads it twice before wrapping.
+pub(super) struct CpuCursor<'a> {
+	cpu: &'a Cpu,
+	pc: u16,
+	// The HALT bug: the first byte is read without moving past it, so it's read again
+	repeat_first: bool,
+	// Debuggers peek at memory, so looking at an instruction doesn't disturb the hardware
+	peek: bool,
+}
+
+impl<'a> CpuCursor<'a> {
+	pub(super) fn new(cpu: &'a Cpu) -> Self {
+		Self {
+			cpu,
+			pc: cpu.current_pc(),
+			repeat_first: cpu.halt_bug,
+			peek: false,
+		}
+	}
+
+	pub(super) fn peeking(cpu: &'a Cpu) -> Self {
+		Self {
+			peek: true,
+			..Self::new(cpu)
+		}
+	}
+}
+
+impl ByteFetcher for CpuCursor<'_> {
+	fn address(&self) -> u16 {
+		self.pc
+	}
+
+	fn next_byte(&mut self) -> Result<u8, ExecutionError> {
+		let byte = match self.peek {
+			true => self.cpu.peek_byte(self.pc)?,
+			false => self.cpu.read_byte(self.pc)?,
+		};
+		if self.repeat_first {
+			self.repeat_first = false;
+		} else {
+			self.pc = self.pc.wrapping_add(1);
+		}
+
+		Ok(byte)
+	}
+}
+
+// A ROM image or any other buffer, addressed by its offset truncated to 16 bits
+pub(super) struct SliceCursor<'a> {
+	bytes: &'a [u8],
+	offset: usize,
+}
+
+impl<'a> SliceCursor<'a> {
+	pub(super) fn new(bytes: &'a [u8], offset: usize) -> Self {
+		Self { bytes, offset }
+	}
+
+	pub(super) fn offset(&self) -> usize {
+		self.offset
+	}
+}
+
+impl ByteFetcher for SliceCursor<'_> {
+	fn address(&self) -> u16 {
+		self.offset as u16
+	}
+
+	fn next_byte(&mut self) -> Result<u8, ExecutionError> {
+		let byte = *self
+			.bytes
+			.get(self.offset)
+			.ok_or(RamError::InvalidAddress(self.address()))?;
+		self.offset += 1;
+
+		Ok(byte)
+	}
+}
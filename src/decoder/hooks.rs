@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::rc::Rc;
+
+use crate::hardware::cpu::Cpu;
+use crate::hardware::register_bank::SingleRegisters;
+use crate::instructions::changeset::{
+	ChangeKind, ChangeList, ChangesetExecutable, MemoryByteWriteChange, PcChange, SingleRegisterChange,
+};
+use crate::instructions::ExecutionError;
+
+pub type OpcodeHookHandler = dyn Fn(&Cpu) -> HookAction;
+
+/// What the decoder does with a hooked opcode, decided by the handler with PC still on the opcode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HookAction {
+	/// Decode the opcode as usual
+	Continue,
+	/// Treat the opcode as a no-op this many bytes long, opcode included
+	Skip(u16),
+	/// Treat the opcode as a one byte instruction with these effects, applied in order
+	Custom(Vec<HookWrite>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HookWrite {
+	Register(SingleRegisters, u8),
+	Memory(u16, u8),
+	Pc(u16),
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct OpcodeHooks {
+	handlers: HashMap<u8, Rc<OpcodeHookHandler>>,
+}
+
+impl OpcodeHooks {
+	pub(crate) fn register(&mut self, opcode: u8, handler: Box<OpcodeHookHandler>) {
+		self.handlers.insert(opcode, handler.into());
+	}
+
+	pub(crate) fn is_empty(&self) -> bool {
+		self.handlers.is_empty()
+	}
+
+	pub(crate) fn get(&self, opcode: u8) -> Option<Rc<OpcodeHookHandler>> {
+		self.handlers.get(&opcode).cloned()
+	}
+}
+
+impl Debug for OpcodeHooks {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_set().entries(self.handlers.keys()).finish()
+	}
+}
+
+// Handlers can't be compared, so two sets of hooks are equal if they hook the same opcodes
+impl PartialEq for OpcodeHooks {
+	fn eq(&self, other: &Self) -> bool {
+		self.handlers.len() == other.handlers.len() && self.handlers.keys().all(|k| other.handlers.contains_key(k))
+	}
+}
+
+#[derive(Debug)]
+pub(crate) struct HookInstruction {
+	opcode: u8,
+	writes: Vec<HookWrite>,
+}
+
+impl HookInstruction {
+	pub(crate) fn new(opcode: u8, writes: Vec<HookWrite>) -> Self {
+		Self { opcode, writes }
+	}
+}
+
+impl ChangesetExecutable for HookInstruction {
+	type C = ChangeList;
+
+	fn compute_change(&self, _cpu: &Cpu) -> Result<Self::C, ExecutionError> {
+		let changes = self.writes.iter().map(|write| -> ChangeKind {
+			match *write {
+				HookWrite::Register(register, value) => SingleRegisterChange::new(register, value).into(),
+				HookWrite::Memory(address, value) => MemoryByteWriteChange::write_to_immediate(address, value).into(),
+				HookWrite::Pc(address) => PcChange::new(address).into(),
+			}
+		});
+
+		Ok(ChangeList::new(changes))
+	}
+}
+
+impl Display for HookInstruction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "hook {:#04X}", self.opcode)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::decoder::fetch_and_decode;
+	use crate::hardware::ram::WORKING_RAM_START;
+
+	use super::*;
+
+	fn run(cpu: &mut Cpu, program: &[u8], steps: usize) {
+		for (offset, byte) in program.iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, *byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+
+		for _ in 0..steps {
+			let instruction = fetch_and_decode(cpu).unwrap();
+			instruction.execute(cpu).unwrap();
+		}
+	}
+
+	#[test]
+	fn custom_hook_on_illegal_opcode() {
+		let mut cpu = Cpu::new();
+		cpu.register_opcode_hook(
+			0xD3,
+			Box::new(|cpu| {
+				let a = cpu.read_register(SingleRegisters::A);
+				HookAction::Custom(vec![HookWrite::Register(SingleRegisters::B, a)])
+			}),
+		);
+
+		// ld A, 0x42; hooked; inc B
+		run(&mut cpu, &[0x3E, 0x42, 0xD3, 0x04], 3);
+
+		assert_eq!(cpu.read_register(SingleRegisters::B), 0x43);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 4);
+	}
+
+	#[test]
+	fn skip_and_continue_hooks() {
+		let mut cpu = Cpu::new();
+		cpu.register_opcode_hook(0xDB, Box::new(|_| HookAction::Skip(2)));
+		cpu.register_opcode_hook(0x04, Box::new(|_| HookAction::Continue));
+
+		// hooked with an argument byte; inc B
+		run(&mut cpu, &[0xDB, 0xFF, 0x04], 2);
+
+		assert_eq!(cpu.read_register(SingleRegisters::B), 0x01);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 3);
+	}
+}
@@ -1,35 +1,34 @@
-use crate::bits::bits_to_byte;
 use crate::decoder::DecodedInstructionOperand;
-use crate::instructions::base::byte::ByteDestination;
+use crate::instructions::kind::InstructionKind;
 use crate::instructions::shifting::operation::{ByteShiftOperation, ShiftDirection, ShiftType};
 use crate::instructions::shifting::{ByteShiftInstruction, ByteSwapInstruction, ByteSwapOperation};
 use crate::instructions::single_bit::{SingleBitInstruction, SingleBitOperand, SingleBitOperation};
-use crate::instructions::Instruction;
 
-pub(super) fn decode_prefixed_shifting(y: [bool; 3], z: [bool; 3]) -> Box<dyn Instruction> {
-	let source = DecodedInstructionOperand::from_opcode_part(z).into();
+pub(super) fn decode_prefixed_shifting(y: u8, z: u8) -> InstructionKind {
+	// The operand is both read and written back, using the same encoding as the unprefixed instructions
+	let operand = DecodedInstructionOperand::from_index(z);
+	let source = operand.into();
+	let destination = operand.into();
 
-	let shift_direction = match y[0] {
-		false => ShiftDirection::Left,
-		true => ShiftDirection::Right,
+	let shift_direction = match y & 1 {
+		0 => ShiftDirection::Left,
+		_ => ShiftDirection::Right,
 	};
 
-	let shift_type = match y {
-		[_, false, false] /* 0 <= y < 2 */ => ShiftType::Rotate,
-		[_, true, false] /* 2 <= y < 4 */ => ShiftType::RotateWithCarry,
-		[_, false, true]  /* 4 <= y < 6 */ => ShiftType::ArithmeticShift,
-		[_, true, true] /* 6 <= y < 8 */ => ShiftType::LogicalShift
+	let shift_type = match y >> 1 {
+		0 => ShiftType::Rotate,
+		1 => ShiftType::RotateWithCarry,
+		2 => ShiftType::ArithmeticShift,
+		_ => ShiftType::LogicalShift,
 	};
 
 	match (shift_type, shift_direction) {
-		(ShiftType::LogicalShift, ShiftDirection::Left) => Box::new(ByteSwapInstruction::new(
+		(ShiftType::LogicalShift, ShiftDirection::Left) => {
+			InstructionKind::from(ByteSwapInstruction::new(source, destination, ByteSwapOperation::new()))
+		} // Logical left shift does not exist, instead this encodes a swap instruction
+		(_, _) => InstructionKind::from(ByteShiftInstruction::new(
 			source,
-			ByteDestination::write_to_acc(),
-			ByteSwapOperation::new(),
-		)), // Logical left shift does not exist, instead this encodes a swap instruction
-		(_, _) => Box::new(ByteShiftInstruction::new(
-			source,
-			ByteDestination::write_to_acc(),
+			destination,
 			ByteShiftOperation::new(shift_direction, shift_type),
 		)),
 	}
@@ -44,13 +43,8 @@ impl From<DecodedInstructionOperand> for SingleBitOperand {
 	}
 }
 
-pub(super) fn decode_prefixed_single_bit(
-	operation: SingleBitOperation,
-	y: [bool; 3],
-	z: [bool; 3],
-) -> Box<dyn Instruction> {
-	let bitshift = bits_to_byte(&y);
-	let z = DecodedInstructionOperand::from_opcode_part(z);
+pub(super) fn decode_prefixed_single_bit(operation: SingleBitOperation, y: u8, z: u8) -> InstructionKind {
+	let z = DecodedInstructionOperand::from_index(z);
 
-	Box::new(SingleBitInstruction::new(z.into(), operation, bitshift))
+	InstructionKind::from(SingleBitInstruction::new(z.into(), operation, y))
 }
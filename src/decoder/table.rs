@@ -0,0 +1,407 @@
+use super::Opcode;
+
+// Shown for opcodes without an instruction, like the disassembler does
+const ILLEGAL_MNEMONIC: &str = "db";
+
+// Operand tables indexed by the opcode fields, as in the decoder
+const REGISTERS: [OperandKind; 8] = [
+	OperandKind::Register("B"),
+	OperandKind::Register("C"),
+	OperandKind::Register("D"),
+	OperandKind::Register("E"),
+	OperandKind::Register("H"),
+	OperandKind::Register("L"),
+	OperandKind::Indirect("HL"),
+	OperandKind::Register("A"),
+];
+const REGISTER_PAIRS: [&str; 4] = ["BC", "DE", "HL", "SP"];
+const STACK_REGISTER_PAIRS: [&str; 4] = ["BC", "DE", "HL", "AF"];
+const INDIRECT_LOADS: [&str; 4] = ["BC", "DE", "HL+", "HL-"];
+const CONDITIONS: [&str; 4] = ["NZ", "Z", "NC", "C"];
+const ALU_MNEMONICS: [&str; 8] = ["add", "adc", "sub", "sbc", "and", "xor", "or", "cp"];
+const ACCUMULATOR_MNEMONICS: [&str; 8] = ["rlca", "rrca", "rla", "rra", "daa", "cpl", "scf", "ccf"];
+const SHIFT_MNEMONICS: [&str; 8] = ["rlc", "rrc", "rl", "rr", "sla", "sra", "swap", "srl"];
+const BIT_MNEMONICS: [&str; 3] = ["bit", "res", "set"];
+
+const A: OperandKind = OperandKind::Register("A");
+
+/// Immediate read after the opcode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImmediateKind {
+	U8,
+	/// Relative jumps and SP offsets
+	I8,
+	U16,
+}
+
+/// An operand as written in assembly, destination first
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OperandKind {
+	/// A register or register pair, such as `A` or `HL`
+	Register(&'static str),
+	/// Memory at a register pair, or at 0xFF00 + C, such as `HL+` for `(HL+)` or `C` for `(C)`
+	Indirect(&'static str),
+	/// Branch condition, such as `NZ`
+	Condition(&'static str),
+	/// Bit number of BIT, RES and SET
+	Bit(u8),
+	/// Target of RST
+	Vector(u8),
+	/// The immediate itself
+	Immediate,
+	/// Memory at the immediate address, in the high page for LDH
+	IndirectImmediate,
+	/// SP plus the signed immediate
+	SpPlusImmediate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpcodeInfo {
+	pub mnemonic: &'static str,
+	pub operands: [Option<OperandKind>; 2],
+	pub immediate: Option<ImmediateKind>,
+	/// Bytes taken by the instruction, prefix included
+	pub length: u16,
+	pub illegal: bool,
+}
+
+impl OpcodeInfo {
+	const ILLEGAL: Self = Self {
+		mnemonic: ILLEGAL_MNEMONIC,
+		operands: [None, None],
+		immediate: None,
+		length: 1,
+		illegal: true,
+	};
+
+	fn new(mnemonic: &'static str, operands: &[OperandKind], immediate: Option<ImmediateKind>) -> Self {
+		let length = match immediate {
+			None => 1,
+			Some(ImmediateKind::U8 | ImmediateKind::I8) => 2,
+			Some(ImmediateKind::U16) => 3,
+		};
+
+		Self {
+			mnemonic,
+			operands: [operands.first().copied(), operands.get(1).copied()],
+			immediate,
+			length,
+			illegal: false,
+		}
+	}
+
+	fn with_length(self, length: u16) -> Self {
+		Self { length, ..self }
+	}
+}
+
+/// What the decoder makes of each unprefixed opcode, indexed by opcode
+pub fn opcode_table() -> [OpcodeInfo; 256] {
+	build_table(false)
+}
+
+/// Same as [`opcode_table`], for the opcodes following the CB prefix
+pub fn prefixed_opcode_table() -> [OpcodeInfo; 256] {
+	build_table(true)
+}
+
+fn build_table(prefixed: bool) -> [OpcodeInfo; 256] {
+	let mut table = [OpcodeInfo::ILLEGAL; 256];
+	for (opcode, info) in (0..=u8::MAX).zip(table.iter_mut()) {
+		*info = opcode_info(prefixed, opcode).unwrap_or(OpcodeInfo::ILLEGAL);
+	}
+
+	table
+}
+
+/// Mnemonic, operands and length of an opcode, CB-prefixed if `prefixed`, or `None` if it has no instruction
+pub fn opcode_info(prefixed: bool, opcode: u8) -> Option<OpcodeInfo> {
+	let op = Opcode(opcode);
+	if prefixed {
+		return Some(prefixed_info(op));
+	}
+
+	match op.x() {
+		0 => Some(block_0_info(op)),
+		1 if op.y() == 6 && op.z() == 6 => Some(OpcodeInfo::new("halt", &[], None)),
+		1 => Some(OpcodeInfo::new(
+			"ld",
+			&[REGISTERS[op.y() as usize], REGISTERS[op.z() as usize]],
+			None,
+		)),
+		2 => Some(OpcodeInfo::new(
+			ALU_MNEMONICS[op.y() as usize],
+			&[A, REGISTERS[op.z() as usize]],
+			None,
+		)),
+		_ => block_3_info(op),
+	}
+}
+
+// A CB-prefixed opcode: shifts and rotations when x = 0, otherwise BIT, RES and SET with the bit number in y
+fn prefixed_info(op: Opcode) -> OpcodeInfo {
+	let register = REGISTERS[op.z() as usize];
+	let info = match op.x() {
+		0 => OpcodeInfo::new(SHIFT_MNEMONICS[op.y() as usize], &[register], None),
+		x => OpcodeInfo::new(
+			BIT_MNEMONICS[x as usize - 1],
+			&[OperandKind::Bit(op.y()), register],
+			None,
+		),
+	};
+
+	info.with_length(2)
+}
+
+fn block_0_info(op: Opcode) -> OpcodeInfo {
+	use ImmediateKind::*;
+	use OperandKind::*;
+
+	let pair = Register(REGISTER_PAIRS[op.p() as usize]);
+	let register = REGISTERS[op.y() as usize];
+	match (op.z(), op.y()) {
+		(0, 0) => OpcodeInfo::new("nop", &[], None),
+		(0, 1) => OpcodeInfo::new("ld", &[IndirectImmediate, Register("SP")], Some(U16)),
+		// The byte after STOP is skipped
+		(0, 2) => OpcodeInfo::new("stop", &[], None).with_length(2),
+		(0, 3) => OpcodeInfo::new("jr", &[Immediate], Some(I8)),
+		(0, y) => OpcodeInfo::new("jr", &[Condition(CONDITIONS[y as usize - 4]), Immediate], Some(I8)),
+		(1, _) if !op.q() => OpcodeInfo::new("ld", &[pair, Immediate], Some(U16)),
+		(1, _) => OpcodeInfo::new("add", &[Register("HL"), pair], None),
+		(2, _) => {
+			let memory = Indirect(INDIRECT_LOADS[op.p() as usize]);
+			match op.q() {
+				false => OpcodeInfo::new("ld", &[memory, Register("A")], None),
+				true => OpcodeInfo::new("ld", &[Register("A"), memory], None),
+			}
+		}
+		(3, _) => OpcodeInfo::new(if op.q() { "dec" } else { "inc" }, &[pair], None),
+		(4, _) => OpcodeInfo::new("inc", &[register], None),
+		(5, _) => OpcodeInfo::new("dec", &[register], None),
+		(6, _) => OpcodeInfo::new("ld", &[register, Immediate], Some(U8)),
+		(_, y) => OpcodeInfo::new(ACCUMULATOR_MNEMONICS[y as usize], &[], None),
+	}
+}
+
+fn block_3_info(op: Opcode) -> Option<OpcodeInfo> {
+	use ImmediateKind::*;
+	use OperandKind::*;
+
+	let y = op.y() as usize;
+	let info = match (op.z(), op.y()) {
+		(0, 0..=3) => OpcodeInfo::new("ret", &[Condition(CONDITIONS[y])], None),
+		(0, 4) => OpcodeInfo::new("ldh", &[IndirectImmediate, Register("A")], Some(U8)),
+		(0, 5) => OpcodeInfo::new("add", &[Register("SP"), Immediate], Some(I8)),
+		(0, 6) => OpcodeInfo::new("ldh", &[Register("A"), IndirectImmediate], Some(U8)),
+		(0, _) => OpcodeInfo::new("ld", &[Register("HL"), SpPlusImmediate], Some(I8)),
+		(1, _) if !op.q() => OpcodeInfo::new("pop", &[Register(STACK_REGISTER_PAIRS[op.p() as usize])], None),
+		(1, 1) => OpcodeInfo::new("ret", &[], None),
+		(1, 3) => OpcodeInfo::new("reti", &[], None),
+		(1, 5) => OpcodeInfo::new("jp", &[Register("HL")], None),
+		(1, _) => OpcodeInfo::new("ld", &[Register("SP"), Register("HL")], None),
+		(2, 0..=3) => OpcodeInfo::new("jp", &[Condition(CONDITIONS[y]), Immediate], Some(U16)),
+		(2, 4) => OpcodeInfo::new("ld", &[Indirect("C"), Register("A")], None),
+		(2, 5) => OpcodeInfo::new("ld", &[IndirectImmediate, Register("A")], Some(U16)),
+		(2, 6) => OpcodeInfo::new("ld", &[Register("A"), Indirect("C")], None),
+		(2, _) => OpcodeInfo::new("ld", &[Register("A"), IndirectImmediate], Some(U16)),
+		(3, 0) => OpcodeInfo::new("jp", &[Immediate], Some(U16)),
+		// Only the prefix, the instruction is in the prefixed table
+		(3, 1) => OpcodeInfo::new("prefix", &[], None),
+		(3, 6) => OpcodeInfo::new("di", &[], None),
+		(3, 7) => OpcodeInfo::new("ei", &[], None),
+		(4, 0..=3) => OpcodeInfo::new("call", &[Condition(CONDITIONS[y]), Immediate], Some(U16)),
+		(5, _) if !op.q() => OpcodeInfo::new("push", &[Register(STACK_REGISTER_PAIRS[op.p() as usize])], None),
+		(5, 1) => OpcodeInfo::new("call", &[Immediate], Some(U16)),
+		(6, _) => OpcodeInfo::new(ALU_MNEMONICS[y], &[A, Immediate], Some(U8)),
+		(7, _) => OpcodeInfo::new("rst", &[Vector(op.y() * 8)], None),
+		_ => return None,
+	};
+
+	Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::decoder::{decode_from_slice, DecodeError};
+
+	use super::*;
+
+	const CB_PREFIX: u8 = 0xCB;
+
+	#[test]
+	fn illegal_opcodes() {
+		let unprefixed = opcode_table();
+		let prefixed = prefixed_opcode_table();
+
+		assert_eq!(unprefixed.iter().filter(|info| info.illegal).count(), 11);
+		assert!(prefixed.iter().all(|info| !info.illegal));
+		assert_eq!(opcode_info(false, 0xD3), None);
+	}
+
+	#[test]
+	fn lengths_and_immediates() {
+		let unprefixed = opcode_table();
+
+		for (opcode, mnemonic, immediate, length) in [
+			(0x00, "nop", None, 1),
+			(0x01, "ld", Some(ImmediateKind::U16), 3),
+			(0x06, "ld", Some(ImmediateKind::U8), 2),
+			(0x10, "stop", None, 2),
+			(0x18, "jr", Some(ImmediateKind::I8), 2),
+			(0xE0, "ldh", Some(ImmediateKind::U8), 2),
+			(0xE8, "add", Some(ImmediateKind::I8), 2),
+			(0xCD, "call", Some(ImmediateKind::U16), 3),
+			(0xD3, "db", None, 1),
+		] {
+			let info = unprefixed[opcode];
+			assert_eq!(
+				(info.mnemonic, info.immediate, info.length),
+				(mnemonic, immediate, length),
+				"{opcode:#04X}"
+			);
+		}
+
+		for info in prefixed_opcode_table() {
+			assert_eq!((info.immediate, info.length), (None, 2));
+		}
+		assert_eq!(prefixed_opcode_table()[0x37].mnemonic, "swap");
+	}
+
+	#[test]
+	fn operands() {
+		let unprefixed = opcode_table();
+		let prefixed = prefixed_opcode_table();
+
+		assert_eq!(
+			unprefixed[0x2A].operands,
+			[Some(OperandKind::Register("A")), Some(OperandKind::Indirect("HL+"))]
+		);
+		assert_eq!(
+			unprefixed[0x20].operands,
+			[Some(OperandKind::Condition("NZ")), Some(OperandKind::Immediate)]
+		);
+		assert_eq!(unprefixed[0xFF].operands, [Some(OperandKind::Vector(0x38)), None]);
+		assert_eq!(
+			prefixed[0x58].operands,
+			[Some(OperandKind::Bit(3)), Some(OperandKind::Register("B"))]
+		);
+		assert_eq!(prefixed[0x58].mnemonic, "bit");
+	}
+
+	// An entry as the decoder's Display would write it, immediates standing in as IMM
+	fn table_form(info: &OpcodeInfo) -> (String, Vec<String>) {
+		let operands = info
+			.operands
+			.into_iter()
+			.flatten()
+			.map(|operand| match operand {
+				OperandKind::Register(name) | OperandKind::Condition(name) => name.to_string(),
+				OperandKind::Indirect(name) => format!("({name})"),
+				OperandKind::Bit(bit) => bit.to_string(),
+				OperandKind::Vector(vector) => format!("0x{vector:02X}"),
+				OperandKind::Immediate => "IMM".to_string(),
+				OperandKind::IndirectImmediate => "(IMM)".to_string(),
+				OperandKind::SpPlusImmediate => "SP+IMM".to_string(),
+			})
+			.collect();
+
+		(info.mnemonic.to_string(), operands)
+	}
+
+	// The decoder's Display, such as `add A <- A, B` or `jr nz, PC+0x34 ; → 0x0036`, in the table's terms.
+	// Every place where the two spell an instruction differently is listed here.
+	fn decoded_form(prefixed: bool, text: &str) -> (String, Vec<String>) {
+		let text = text.split(" ; ").next().unwrap();
+		let (mnemonic, operands) = text.split_once(' ').unwrap_or((text, ""));
+		let mut mnemonic = mnemonic.to_string();
+		let mut operands: Vec<String> = match operands.split_once(" <- ") {
+			Some((destination, sources)) => {
+				let sources: Vec<_> = sources.split(", ").collect();
+				match sources[..] {
+					// Read-modify-write, such as `inc B <- B` or `add A <- A, B`, but `ld B <- B` is a plain load
+					[source] if source == destination && mnemonic != "ld" => vec![destination.to_string()],
+					[first, second] if first == destination => vec![destination.to_string(), second.to_string()],
+					_ => [destination].into_iter().chain(sources).map(String::from).collect(),
+				}
+			}
+			None => operands
+				.split(", ")
+				.filter(|operand| !operand.is_empty())
+				.map(String::from)
+				.collect(),
+		};
+
+		match mnemonic.as_str() {
+			"ldi" | "ldd" => {
+				let index = if mnemonic == "ldi" { "(HL+)" } else { "(HL-)" };
+				for operand in operands.iter_mut().filter(|operand| *operand == "(HL)") {
+					*operand = index.to_string();
+				}
+				mnemonic = "ld".to_string();
+			}
+			// The accumulator-only forms have their own mnemonics and no operands
+			"rlc" | "rrc" | "rl" | "rr" if !prefixed => {
+				mnemonic.push('a');
+				operands.clear();
+			}
+			"cpl" => operands.clear(),
+			// ld HL, SP+e
+			"add" if operands == ["HL", "SP", "0x34"] => {
+				mnemonic = "ld".to_string();
+				operands = vec!["HL".to_string(), "SP+IMM".to_string()];
+			}
+			_ => {}
+		}
+
+		for operand in &mut operands {
+			match operand.as_str() {
+				"(0xFF34)" => {
+					mnemonic = "ldh".to_string();
+					*operand = "(IMM)".to_string();
+				}
+				"(0x1234)" => *operand = "(IMM)".to_string(),
+				"(C + 0xFF00)" => *operand = "(C)".to_string(),
+				"0x34" | "0x1234" | "PC+0x34" => *operand = "IMM".to_string(),
+				// Conditions are lowercase
+				condition if condition.chars().all(|c| c.is_ascii_lowercase()) => *operand = condition.to_uppercase(),
+				_ => {}
+			}
+		}
+
+		(mnemonic, operands)
+	}
+
+	// Built from the opcode fields rather than by decoding, so check every entry against the decoder
+	#[test]
+	fn agrees_with_decoder() {
+		for (prefixed, table) in [(false, opcode_table()), (true, prefixed_opcode_table())] {
+			for (opcode, info) in (0..=u8::MAX).zip(table) {
+				if !prefixed && opcode == CB_PREFIX {
+					continue;
+				}
+				// Immediates that can't be mistaken for RST vectors or bit numbers
+				let bytes: Vec<_> = prefixed
+					.then_some(CB_PREFIX)
+					.into_iter()
+					.chain([opcode, 0x34, 0x12])
+					.collect();
+
+				match decode_from_slice(&bytes, 0) {
+					Ok((decoded, length)) => {
+						assert!(!info.illegal, "{opcode:#04X}, prefixed {prefixed}");
+						assert_eq!(usize::from(info.length), length, "{opcode:#04X}, prefixed {prefixed}");
+						assert_eq!(
+							table_form(&info),
+							decoded_form(prefixed, &decoded.to_string()),
+							"{opcode:#04X}, prefixed {prefixed}: {decoded}"
+						);
+					}
+					Err(err) => {
+						assert_eq!(err, DecodeError::InvalidOpcode(opcode));
+						assert!(info.illegal, "{opcode:#04X}, prefixed {prefixed}");
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,56 @@
+use crate::decoder::DecodedInstructionPrefix;
+
+// Clock cycles of each unprefixed opcode, for conditional branches when not taken. Invalid opcodes are 0.
+#[rustfmt::skip]
+const UNPREFIXED_CYCLES: [u8; 0x100] = [
+	//  0   1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
+	4,  12, 8,  8,  4,  4,  8,  4,  20, 8,  8,  8,  4,  4,  8,  4,  // 0x
+	4,  12, 8,  8,  4,  4,  8,  4,  12, 8,  8,  8,  4,  4,  8,  4,  // 1x
+	8,  12, 8,  8,  4,  4,  8,  4,  8,  8,  8,  8,  4,  4,  8,  4,  // 2x
+	8,  12, 8,  8,  12, 12, 12, 4,  8,  8,  8,  8,  4,  4,  8,  4,  // 3x
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // 4x
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // 5x
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // 6x
+	8,  8,  8,  8,  8,  8,  4,  8,  4,  4,  4,  4,  4,  4,  8,  4,  // 7x
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // 8x
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // 9x
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // Ax
+	4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,  // Bx
+	8,  12, 12, 16, 12, 16, 8,  16, 8,  16, 12, 4,  12, 24, 8,  16, // Cx
+	8,  12, 12, 0,  12, 16, 8,  16, 8,  16, 12, 0,  12, 0,  8,  16, // Dx
+	12, 12, 8,  0,  0,  16, 8,  16, 16, 4,  16, 0,  0,  0,  8,  16, // Ex
+	12, 12, 8,  4,  0,  16, 8,  16, 12, 8,  16, 4,  0,  0,  8,  16, // Fx
+];
+
+const PREFIXED_REGISTER_CYCLES: u8 = 8;
+const PREFIXED_BIT_HL_CYCLES: u8 = 12;
+const PREFIXED_HL_CYCLES: u8 = 16;
+
+/// Base clock cycles of an instruction, and the extra cycles when it's a conditional branch that is taken
+pub(super) fn cycles(prefix: Option<DecodedInstructionPrefix>, opcode: u8) -> (u8, u8) {
+	match prefix {
+		Some(DecodedInstructionPrefix::CB) => (prefixed_cycles(opcode), 0),
+		None => (UNPREFIXED_CYCLES[usize::from(opcode)], branch_cycles(opcode)),
+	}
+}
+
+fn prefixed_cycles(opcode: u8) -> u8 {
+	let reads_hl = opcode & 0b111 == 0b110;
+	let is_bit_test = opcode & 0b1100_0000 == 0b0100_0000;
+
+	match (reads_hl, is_bit_test) {
+		(false, _) => PREFIXED_REGISTER_CYCLES,
+		(true, true) => PREFIXED_BIT_HL_CYCLES,
+		(true, false) => PREFIXED_HL_CYCLES,
+	}
+}
+
+fn branch_cycles(opcode: u8) -> u8 {
+	match opcode {
+		0x20 | 0x28 | 0x30 | 0x38 => 4,  // jr cc
+		0xC2 | 0xCA | 0xD2 | 0xDA => 4,  // jp cc
+		0xC4 | 0xCC | 0xD4 | 0xDC => 12, // call cc
+		0xC0 | 0xC8 | 0xD0 | 0xD8 => 12, // ret cc
+		_ => 0,
+	}
+}
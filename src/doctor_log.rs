@@ -0,0 +1,101 @@
+// Per-instruction logs in the format gameboy-doctor compares against its reference logs:
+// https://github.com/robert/gameboy-doctor
+
+use std::io::Write;
+
+use crate::hardware::cpu::Cpu;
+use crate::hardware::ram::Rom;
+use crate::hardware::register_bank::SingleRegisters;
+
+// Read for bytes that aren't mapped, as the open bus would
+const UNMAPPED_BYTE: u8 = 0xFF;
+
+/// Writes one line per instruction, with the CPU state before it executes
+pub struct DoctorLogger<W: Write> {
+	writer: W,
+}
+
+impl<W: Write> DoctorLogger<W> {
+	pub fn new(writer: W) -> Self {
+		Self { writer }
+	}
+
+	/// Logs the state with PC on the next instruction, call it before fetching. Each line is flushed on its own.
+	pub fn log(&mut self, cpu: &Cpu) -> std::io::Result<()> {
+		writeln!(self.writer, "{}", doctor_line(cpu))?;
+		self.writer.flush()
+	}
+
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+}
+
+/// `A:00 F:11 B:22 C:33 D:44 E:55 H:66 L:77 SP:8888 PC:9999 PCMEM:AA,BB,CC,DD`, without a line break
+pub fn doctor_line(cpu: &Cpu) -> String {
+	let register = |register| cpu.register_bank.read_single_named(register);
+	let pc = cpu.pc.read();
+	let pc_mem = [0, 1, 2, 3].map(|offset| {
+		cpu.mapped_ram
+			.peek_byte(pc.wrapping_add(offset))
+			.unwrap_or(UNMAPPED_BYTE)
+	});
+
+	format!(
+		"A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} \
+		PCMEM:{:02X},{:02X},{:02X},{:02X}",
+		register(SingleRegisters::A),
+		register(SingleRegisters::F),
+		register(SingleRegisters::B),
+		register(SingleRegisters::C),
+		register(SingleRegisters::D),
+		register(SingleRegisters::E),
+		register(SingleRegisters::H),
+		register(SingleRegisters::L),
+		cpu.sp.read(),
+		pc,
+		pc_mem[0],
+		pc_mem[1],
+		pc_mem[2],
+		pc_mem[3],
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::hardware::register_bank::DoubleRegisters;
+
+	use super::*;
+
+	#[test]
+	fn fixed_state() {
+		let mut cpu = Cpu::new();
+		cpu.write_double_register(DoubleRegisters::AF, 0x01B0);
+		cpu.write_double_register(DoubleRegisters::BC, 0x0013);
+		cpu.write_double_register(DoubleRegisters::DE, 0x00D8);
+		cpu.write_double_register(DoubleRegisters::HL, 0x014D);
+		cpu.set_sp(0xFFFE);
+		for (offset, byte) in [0x00, 0xC3, 0x13, 0x02].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+
+		let mut logger = DoctorLogger::new(Vec::new());
+		logger.log(&cpu).unwrap();
+
+		assert_eq!(
+			String::from_utf8(logger.into_inner()).unwrap(),
+			"A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:C000 PCMEM:00,C3,13,02\n"
+		);
+	}
+
+	#[test]
+	fn unmapped_pc_mem() {
+		let mut cpu = Cpu::new();
+		// Last bytes of OAM, followed by the unusable region
+		cpu.set_pc(0xFE9E);
+
+		assert!(doctor_line(&cpu).ends_with("PC:FE9E PCMEM:00,00,FF,FF"));
+	}
+}
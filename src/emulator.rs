@@ -1,19 +1,36 @@
-use corrosion::decoder::fetch_and_decode;
-use corrosion::hardware::cpu::Cpu;
-use corrosion::instructions::ExecutionError;
+use corrosion::prelude::{Cpu, DoctorLogger, ExecutionError, StepOutcome};
 
-fn update_cpu(cpu: &mut Cpu) -> Result<(), ExecutionError> {
-	let pc = cpu.current_pc();
-	let instruction = fetch_and_decode(cpu)?;
-	println!("{pc:#06X}: {instruction}");
-	instruction.execute(cpu)
+// Prints each instruction as it runs, or with `--doctor` the gameboy-doctor log line before it
+fn update_cpu(cpu: &mut Cpu, doctor: Option<&mut DoctorLogger<std::io::Stdout>>) -> Result<(), ExecutionError> {
+	if let Some(doctor) = doctor {
+		// Only instructions are logged, not the steps that idle or dispatch an interrupt instead
+		let idle = cpu.halted() || cpu.stopped();
+		let dispatching = cpu.ime() && cpu.interrupt_pending()?;
+		if !idle && !dispatching {
+			if let Err(err) = doctor.log(cpu) {
+				eprintln!("Can't write the doctor log: {err}");
+			}
+		}
+		cpu.step()?;
+		return Ok(());
+	}
+
+	match cpu.step()? {
+		StepOutcome::Executed { pc, instruction, .. } => println!("{pc:#06X}: {instruction}"),
+		StepOutcome::Interrupt { .. } => println!("Interrupt, jumping to {:#06X}", cpu.current_pc()),
+		StepOutcome::Idle { .. } => {}
+	}
+	Ok(())
 }
 
 fn main() -> Result<(), String> {
 	let mut cpu = Cpu::new();
+	let mut doctor = std::env::args()
+		.any(|arg| arg == "--doctor")
+		.then(|| DoctorLogger::new(std::io::stdout()));
 
 	loop {
-		let execution_result = update_cpu(&mut cpu);
+		let execution_result = update_cpu(&mut cpu, doctor.as_mut());
 		if let Err(err) = execution_result {
 			println!("ERROR: {err}");
 			break;
@@ -0,0 +1,583 @@
+use crate::hardware::register_bank::{DoubleRegisters, SingleRegisters};
+
+const CB_PREFIX: u8 = 0xCB;
+
+/// 8-bit operand encoded in the opcode: a register, or the byte at the address in HL
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteOperand {
+	/// Any register but F
+	Register(SingleRegisters),
+	AddressInHl,
+}
+
+/// Addresses the accumulator can be loaded from and stored to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AccumulatorAddress {
+	Bc,
+	De,
+	/// (HL), incrementing HL afterwards
+	HlIncrement,
+	/// (HL), decrementing HL afterwards
+	HlDecrement,
+	Immediate(u16),
+	/// 0xFF00 plus the immediate
+	HighPage(u8),
+	/// 0xFF00 plus C
+	HighPageC,
+}
+
+/// 16-bit operand encoded in the opcode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DoubleOperand {
+	/// BC, DE or HL
+	Register(DoubleRegisters),
+	StackPointer,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+	NotZero,
+	Zero,
+	NotCarry,
+	Carry,
+}
+
+/// Operations between the accumulator and another byte, in opcode order
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AluOperation {
+	Add,
+	AddWithCarry,
+	Sub,
+	SubWithCarry,
+	And,
+	Xor,
+	Or,
+	Compare,
+}
+
+/// Shifts, rotations and swaps from the CB page, in opcode order
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShiftOperation {
+	RotateLeftCircular,
+	RotateRightCircular,
+	RotateLeft,
+	RotateRight,
+	ShiftLeftArithmetic,
+	ShiftRightArithmetic,
+	Swap,
+	ShiftRightLogical,
+}
+
+/// An instruction to encode, with its operands
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InstructionSpec {
+	Nop,
+	/// Encoded as 0x10 0x00, the decoder reads the padding byte along with the opcode
+	Stop,
+	Halt,
+	DisableInterrupts,
+	EnableInterrupts,
+	DecimalAdjust,
+	Complement,
+	SetCarry,
+	ToggleCarry,
+	Load {
+		dst: ByteOperand,
+		src: ByteOperand,
+	},
+	LoadImmediate {
+		dst: ByteOperand,
+		value: u8,
+	},
+	LoadAccumulator(AccumulatorAddress),
+	StoreAccumulator(AccumulatorAddress),
+	LoadDoubleImmediate {
+		dst: DoubleOperand,
+		value: u16,
+	},
+	StoreStackPointer(u16),
+	LoadStackPointerFromHl,
+	/// HL <- SP + offset
+	LoadHlFromStackOffset(i8),
+	AddToStackPointer(i8),
+	/// Any pair, AF included
+	Push(DoubleRegisters),
+	/// Any pair, AF included
+	Pop(DoubleRegisters),
+	Alu {
+		operation: AluOperation,
+		operand: ByteOperand,
+	},
+	AluImmediate {
+		operation: AluOperation,
+		value: u8,
+	},
+	Increment(ByteOperand),
+	Decrement(ByteOperand),
+	IncrementDouble(DoubleOperand),
+	DecrementDouble(DoubleOperand),
+	AddToHl(DoubleOperand),
+	RotateAccumulatorLeftCircular,
+	RotateAccumulatorRightCircular,
+	RotateAccumulatorLeft,
+	RotateAccumulatorRight,
+	Jump {
+		condition: Option<Condition>,
+		address: u16,
+	},
+	JumpToHl,
+	/// Offset from the address after the instruction
+	JumpRelative {
+		condition: Option<Condition>,
+		offset: i8,
+	},
+	Call {
+		condition: Option<Condition>,
+		address: u16,
+	},
+	Return {
+		condition: Option<Condition>,
+	},
+	ReturnFromInterrupt,
+	/// Call to one of the fixed vectors: 0x00, 0x08, ..., 0x38
+	Restart(u8),
+	Shift {
+		operation: ShiftOperation,
+		operand: ByteOperand,
+	},
+	TestBit {
+		bit: u8,
+		operand: ByteOperand,
+	},
+	ResetBit {
+		bit: u8,
+		operand: ByteOperand,
+	},
+	SetBit {
+		bit: u8,
+		operand: ByteOperand,
+	},
+}
+
+/// Encodes an instruction into the bytes the decoder reads it from, prefix and immediates included.
+///
+/// Panics if the spec has no encoding: F as an operand, AF outside of push and pop, a load from (HL) into (HL) (that
+/// opcode is HALT), a bit index above 7 or a restart vector that isn't one of the eight fixed ones.
+pub fn encode(instruction: &InstructionSpec) -> Vec<u8> {
+	match *instruction {
+		InstructionSpec::Nop => vec![0x00],
+		InstructionSpec::Stop => vec![0x10, 0x00],
+		InstructionSpec::Halt => vec![0x76],
+		InstructionSpec::DisableInterrupts => vec![0xF3],
+		InstructionSpec::EnableInterrupts => vec![0xFB],
+		InstructionSpec::DecimalAdjust => vec![0x27],
+		InstructionSpec::Complement => vec![0x2F],
+		InstructionSpec::SetCarry => vec![0x37],
+		InstructionSpec::ToggleCarry => vec![0x3F],
+		InstructionSpec::Load { dst, src } => {
+			assert!(
+				!(dst == ByteOperand::AddressInHl && src == ByteOperand::AddressInHl),
+				"ld (HL), (HL) is encoded as halt"
+			);
+			vec![0x40 | operand_index(dst) << 3 | operand_index(src)]
+		}
+		InstructionSpec::LoadImmediate { dst, value } => vec![0x06 | operand_index(dst) << 3, value],
+		InstructionSpec::LoadAccumulator(address) => encode_accumulator_address(address, true),
+		InstructionSpec::StoreAccumulator(address) => encode_accumulator_address(address, false),
+		InstructionSpec::LoadDoubleImmediate { dst, value } => with_u16(0x01 | double_index(dst) << 4, value),
+		InstructionSpec::StoreStackPointer(address) => with_u16(0x08, address),
+		InstructionSpec::LoadStackPointerFromHl => vec![0xF9],
+		InstructionSpec::LoadHlFromStackOffset(offset) => vec![0xF8, offset as u8],
+		InstructionSpec::AddToStackPointer(offset) => vec![0xE8, offset as u8],
+		InstructionSpec::Push(pair) => vec![0xC5 | stack_pair_index(pair) << 4],
+		InstructionSpec::Pop(pair) => vec![0xC1 | stack_pair_index(pair) << 4],
+		InstructionSpec::Alu { operation, operand } => vec![0x80 | (operation as u8) << 3 | operand_index(operand)],
+		InstructionSpec::AluImmediate { operation, value } => vec![0xC6 | (operation as u8) << 3, value],
+		InstructionSpec::Increment(operand) => vec![0x04 | operand_index(operand) << 3],
+		InstructionSpec::Decrement(operand) => vec![0x05 | operand_index(operand) << 3],
+		InstructionSpec::IncrementDouble(operand) => vec![0x03 | double_index(operand) << 4],
+		InstructionSpec::DecrementDouble(operand) => vec![0x0B | double_index(operand) << 4],
+		InstructionSpec::AddToHl(operand) => vec![0x09 | double_index(operand) << 4],
+		InstructionSpec::RotateAccumulatorLeftCircular => vec![0x07],
+		InstructionSpec::RotateAccumulatorRightCircular => vec![0x0F],
+		InstructionSpec::RotateAccumulatorLeft => vec![0x17],
+		InstructionSpec::RotateAccumulatorRight => vec![0x1F],
+		InstructionSpec::Jump { condition, address } => with_u16(conditional_opcode(condition, 0xC3, 0xC2), address),
+		InstructionSpec::JumpToHl => vec![0xE9],
+		InstructionSpec::JumpRelative { condition, offset } => {
+			vec![conditional_opcode(condition, 0x18, 0x20), offset as u8]
+		}
+		InstructionSpec::Call { condition, address } => with_u16(conditional_opcode(condition, 0xCD, 0xC4), address),
+		InstructionSpec::Return { condition } => vec![conditional_opcode(condition, 0xC9, 0xC0)],
+		InstructionSpec::ReturnFromInterrupt => vec![0xD9],
+		InstructionSpec::Restart(vector) => {
+			assert!(vector % 8 == 0 && vector <= 0x38, "No restart to {vector:#04X}");
+			vec![0xC7 | vector]
+		}
+		InstructionSpec::Shift { operation, operand } => {
+			vec![CB_PREFIX, (operation as u8) << 3 | operand_index(operand)]
+		}
+		InstructionSpec::TestBit { bit, operand } => encode_single_bit(0x40, bit, operand),
+		InstructionSpec::ResetBit { bit, operand } => encode_single_bit(0x80, bit, operand),
+		InstructionSpec::SetBit { bit, operand } => encode_single_bit(0xC0, bit, operand),
+	}
+}
+
+// Same order the decoder reads them in: B, C, D, E, H, L, (HL), A
+fn operand_index(operand: ByteOperand) -> u8 {
+	match operand {
+		ByteOperand::Register(SingleRegisters::B) => 0,
+		ByteOperand::Register(SingleRegisters::C) => 1,
+		ByteOperand::Register(SingleRegisters::D) => 2,
+		ByteOperand::Register(SingleRegisters::E) => 3,
+		ByteOperand::Register(SingleRegisters::H) => 4,
+		ByteOperand::Register(SingleRegisters::L) => 5,
+		ByteOperand::AddressInHl => 6,
+		ByteOperand::Register(SingleRegisters::A) => 7,
+		ByteOperand::Register(SingleRegisters::F) => panic!("F can't be an operand"),
+	}
+}
+
+fn double_index(operand: DoubleOperand) -> u8 {
+	match operand {
+		DoubleOperand::Register(DoubleRegisters::BC) => 0,
+		DoubleOperand::Register(DoubleRegisters::DE) => 1,
+		DoubleOperand::Register(DoubleRegisters::HL) => 2,
+		DoubleOperand::StackPointer => 3,
+		DoubleOperand::Register(DoubleRegisters::AF) => panic!("AF is only an operand of push and pop"),
+	}
+}
+
+// Push and pop use AF where the other instructions use SP
+fn stack_pair_index(pair: DoubleRegisters) -> u8 {
+	match pair {
+		DoubleRegisters::AF => 3,
+		pair => double_index(DoubleOperand::Register(pair)),
+	}
+}
+
+fn conditional_opcode(condition: Option<Condition>, unconditional: u8, conditional_base: u8) -> u8 {
+	match condition {
+		None => unconditional,
+		Some(condition) => conditional_base | (condition as u8) << 3,
+	}
+}
+
+fn encode_accumulator_address(address: AccumulatorAddress, load: bool) -> Vec<u8> {
+	// Loads into A are the stores with a single bit set
+	let (store_opcode, load_bit) = match address {
+		AccumulatorAddress::Bc => (0x02, 0x08),
+		AccumulatorAddress::De => (0x12, 0x08),
+		AccumulatorAddress::HlIncrement => (0x22, 0x08),
+		AccumulatorAddress::HlDecrement => (0x32, 0x08),
+		AccumulatorAddress::Immediate(_) => (0xEA, 0x10),
+		AccumulatorAddress::HighPage(_) => (0xE0, 0x10),
+		AccumulatorAddress::HighPageC => (0xE2, 0x10),
+	};
+	let opcode = if load { store_opcode | load_bit } else { store_opcode };
+
+	match address {
+		AccumulatorAddress::Immediate(address) => with_u16(opcode, address),
+		AccumulatorAddress::HighPage(offset) => vec![opcode, offset],
+		_ => vec![opcode],
+	}
+}
+
+fn encode_single_bit(base: u8, bit: u8, operand: ByteOperand) -> Vec<u8> {
+	assert!(bit < 8, "No bit {bit} in a byte");
+	vec![CB_PREFIX, base | bit << 3 | operand_index(operand)]
+}
+
+fn with_u16(opcode: u8, value: u16) -> Vec<u8> {
+	let [low, high] = value.to_le_bytes();
+	vec![opcode, low, high]
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::decoder::decode_from_slice;
+
+	use super::*;
+
+	const OPERANDS: [(ByteOperand, &str); 8] = [
+		(ByteOperand::Register(SingleRegisters::B), "B"),
+		(ByteOperand::Register(SingleRegisters::C), "C"),
+		(ByteOperand::Register(SingleRegisters::D), "D"),
+		(ByteOperand::Register(SingleRegisters::E), "E"),
+		(ByteOperand::Register(SingleRegisters::H), "H"),
+		(ByteOperand::Register(SingleRegisters::L), "L"),
+		(ByteOperand::AddressInHl, "(HL)"),
+		(ByteOperand::Register(SingleRegisters::A), "A"),
+	];
+
+	const DOUBLE_OPERANDS: [(DoubleOperand, &str); 4] = [
+		(DoubleOperand::Register(DoubleRegisters::BC), "BC"),
+		(DoubleOperand::Register(DoubleRegisters::DE), "DE"),
+		(DoubleOperand::Register(DoubleRegisters::HL), "HL"),
+		(DoubleOperand::StackPointer, "SP"),
+	];
+
+	const STACK_PAIRS: [(DoubleRegisters, &str); 4] = [
+		(DoubleRegisters::BC, "BC"),
+		(DoubleRegisters::DE, "DE"),
+		(DoubleRegisters::HL, "HL"),
+		(DoubleRegisters::AF, "AF"),
+	];
+
+	const CONDITIONS: [(Option<Condition>, &str); 5] = [
+		(None, ""),
+		(Some(Condition::NotZero), "nz, "),
+		(Some(Condition::Zero), "z, "),
+		(Some(Condition::NotCarry), "nc, "),
+		(Some(Condition::Carry), "c, "),
+	];
+
+	const ALU_OPERATIONS: [(AluOperation, &str); 8] = [
+		(AluOperation::Add, "add"),
+		(AluOperation::AddWithCarry, "adc"),
+		(AluOperation::Sub, "sub"),
+		(AluOperation::SubWithCarry, "sbc"),
+		(AluOperation::And, "and"),
+		(AluOperation::Xor, "xor"),
+		(AluOperation::Or, "or"),
+		(AluOperation::Compare, "cp"),
+	];
+
+	const SHIFT_OPERATIONS: [(ShiftOperation, &str); 8] = [
+		(ShiftOperation::RotateLeftCircular, "rlc"),
+		(ShiftOperation::RotateRightCircular, "rrc"),
+		(ShiftOperation::RotateLeft, "rl"),
+		(ShiftOperation::RotateRight, "rr"),
+		(ShiftOperation::ShiftLeftArithmetic, "sla"),
+		(ShiftOperation::ShiftRightArithmetic, "sra"),
+		(ShiftOperation::Swap, "swap"),
+		(ShiftOperation::ShiftRightLogical, "srl"),
+	];
+
+	fn alu_disassembly(mnemonic: &str, operand: &str) -> String {
+		match mnemonic {
+			"cp" => format!("cp A, {operand}"),
+			_ => format!("{mnemonic} A <- A, {operand}"),
+		}
+	}
+
+	// Every spec paired with how the decoder should print it back
+	fn cases() -> Vec<(InstructionSpec, String)> {
+		let mut cases: Vec<(InstructionSpec, String)> = [
+			(InstructionSpec::Nop, "nop"),
+			(InstructionSpec::Stop, "stop"),
+			(InstructionSpec::Halt, "halt"),
+			(InstructionSpec::DisableInterrupts, "di"),
+			(InstructionSpec::EnableInterrupts, "ei"),
+			(InstructionSpec::DecimalAdjust, "daa"),
+			(InstructionSpec::Complement, "cpl A <- A"),
+			(InstructionSpec::SetCarry, "scf"),
+			(InstructionSpec::ToggleCarry, "ccf"),
+			(InstructionSpec::RotateAccumulatorLeftCircular, "rlc A <- A"),
+			(InstructionSpec::RotateAccumulatorRightCircular, "rrc A <- A"),
+			(InstructionSpec::RotateAccumulatorLeft, "rl A <- A"),
+			(InstructionSpec::RotateAccumulatorRight, "rr A <- A"),
+			(InstructionSpec::StoreStackPointer(0xC123), "ld (0xC123) <- SP"),
+			(InstructionSpec::LoadStackPointerFromHl, "ld SP <- HL"),
+			(InstructionSpec::LoadHlFromStackOffset(5), "add HL <- SP, 0x05"),
+			(InstructionSpec::AddToStackPointer(5), "add SP <- SP, 0x05"),
+			(InstructionSpec::JumpToHl, "jp HL"),
+			(InstructionSpec::ReturnFromInterrupt, "reti"),
+			(InstructionSpec::LoadAccumulator(AccumulatorAddress::Bc), "ld A <- (BC)"),
+			(InstructionSpec::LoadAccumulator(AccumulatorAddress::De), "ld A <- (DE)"),
+			(
+				InstructionSpec::LoadAccumulator(AccumulatorAddress::HlIncrement),
+				"ldi A <- (HL)",
+			),
+			(
+				InstructionSpec::LoadAccumulator(AccumulatorAddress::HlDecrement),
+				"ldd A <- (HL)",
+			),
+			(
+				InstructionSpec::LoadAccumulator(AccumulatorAddress::Immediate(0xC123)),
+				"ld A <- (0xC123)",
+			),
+			(
+				InstructionSpec::LoadAccumulator(AccumulatorAddress::HighPage(0x44)),
+				"ld A <- (0xFF44)",
+			),
+			(
+				InstructionSpec::LoadAccumulator(AccumulatorAddress::HighPageC),
+				"ld A <- (C + 0xFF00)",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::Bc),
+				"ld (BC) <- A",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::De),
+				"ld (DE) <- A",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::HlIncrement),
+				"ldi (HL) <- A",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::HlDecrement),
+				"ldd (HL) <- A",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::Immediate(0xC123)),
+				"ld (0xC123) <- A",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::HighPage(0x44)),
+				"ld (0xFF44) <- A",
+			),
+			(
+				InstructionSpec::StoreAccumulator(AccumulatorAddress::HighPageC),
+				"ld (C + 0xFF00) <- A",
+			),
+		]
+		.into_iter()
+		.map(|(spec, disassembly)| (spec, disassembly.to_string()))
+		.collect();
+
+		for (dst, dst_name) in OPERANDS {
+			for (src, src_name) in OPERANDS {
+				if dst != ByteOperand::AddressInHl || src != ByteOperand::AddressInHl {
+					cases.push((
+						InstructionSpec::Load { dst, src },
+						format!("ld {dst_name} <- {src_name}"),
+					));
+				}
+			}
+			cases.push((
+				InstructionSpec::LoadImmediate { dst, value: 0x5A },
+				format!("ld {dst_name} <- 0x5A"),
+			));
+			cases.push((InstructionSpec::Increment(dst), format!("inc {dst_name} <- {dst_name}")));
+			cases.push((InstructionSpec::Decrement(dst), format!("dec {dst_name} <- {dst_name}")));
+		}
+
+		for (operation, mnemonic) in ALU_OPERATIONS {
+			for (operand, operand_name) in OPERANDS {
+				cases.push((
+					InstructionSpec::Alu { operation, operand },
+					alu_disassembly(mnemonic, operand_name),
+				));
+			}
+			cases.push((
+				InstructionSpec::AluImmediate { operation, value: 0x5A },
+				alu_disassembly(mnemonic, "0x5A"),
+			));
+		}
+
+		for (operand, name) in DOUBLE_OPERANDS {
+			cases.push((
+				InstructionSpec::LoadDoubleImmediate {
+					dst: operand,
+					value: 0x1234,
+				},
+				format!("ld {name} <- 0x1234"),
+			));
+			cases.push((
+				InstructionSpec::IncrementDouble(operand),
+				format!("inc {name} <- {name}"),
+			));
+			cases.push((
+				InstructionSpec::DecrementDouble(operand),
+				format!("dec {name} <- {name}"),
+			));
+			cases.push((InstructionSpec::AddToHl(operand), format!("add HL <- HL, {name}")));
+		}
+
+		for (pair, name) in STACK_PAIRS {
+			cases.push((InstructionSpec::Push(pair), format!("push {name}")));
+			cases.push((InstructionSpec::Pop(pair), format!("pop {name}")));
+		}
+
+		for (condition, prefix) in CONDITIONS {
+			cases.push((
+				InstructionSpec::Jump {
+					condition,
+					address: 0x1234,
+				},
+				format!("jp {prefix}0x1234"),
+			));
+			cases.push((
+				InstructionSpec::JumpRelative {
+					condition,
+					offset: 0x10,
+				},
+				format!("jr {prefix}PC+0x10 ; → 0x0012"),
+			));
+			cases.push((
+				InstructionSpec::Call {
+					condition,
+					address: 0x1234,
+				},
+				format!("call {prefix}0x1234"),
+			));
+			cases.push((
+				InstructionSpec::Return { condition },
+				format!("ret {prefix}").trim_end_matches([',', ' ']).to_string(),
+			));
+		}
+
+		for vector in (0..=0x38).step_by(8) {
+			cases.push((InstructionSpec::Restart(vector), format!("rst {vector:#04X}")));
+		}
+
+		for (operand, name) in OPERANDS {
+			for (operation, mnemonic) in SHIFT_OPERATIONS {
+				cases.push((
+					InstructionSpec::Shift { operation, operand },
+					format!("{mnemonic} {name} <- {name}"),
+				));
+			}
+			for bit in 0..8 {
+				cases.push((InstructionSpec::TestBit { bit, operand }, format!("bit {bit}, {name}")));
+				cases.push((InstructionSpec::ResetBit { bit, operand }, format!("res {bit}, {name}")));
+				cases.push((InstructionSpec::SetBit { bit, operand }, format!("set {bit}, {name}")));
+			}
+		}
+
+		cases
+	}
+
+	#[test]
+	fn round_trip() {
+		let cases = cases();
+		assert!(cases.len() > 450);
+
+		for (spec, expected) in cases {
+			let bytes = encode(&spec);
+			let (decoded, _) =
+				decode_from_slice(&bytes, 0).unwrap_or_else(|e| panic!("{spec:?} -> {bytes:02X?}: {e:?}"));
+
+			assert_eq!(decoded.length as usize, bytes.len(), "{spec:?} -> {bytes:02X?}");
+			assert_eq!(decoded.to_string(), expected, "{spec:?} -> {bytes:02X?}");
+		}
+	}
+
+	#[test]
+	fn negative_relative_offset() {
+		let bytes = encode(&InstructionSpec::JumpRelative {
+			condition: None,
+			offset: -2,
+		});
+
+		assert_eq!(bytes, [0x18, 0xFE]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn load_from_hl_into_hl() {
+		encode(&InstructionSpec::Load {
+			dst: ByteOperand::AddressInHl,
+			src: ByteOperand::AddressInHl,
+		});
+	}
+
+	#[test]
+	#[should_panic]
+	fn misaligned_restart() {
+		encode(&InstructionSpec::Restart(0x09));
+	}
+}
@@ -1,8 +1,16 @@
 pub(super) mod alu;
 pub(crate) mod audio;
+pub(crate) mod call_stack;
 pub(crate) mod counters;
-pub mod cpu;
+pub(crate) mod cpu;
 pub(crate) mod ime;
+pub(crate) mod interrupts;
+pub(crate) mod joypad;
 pub(crate) mod ram;
-pub mod register_bank;
+pub(crate) mod register_bank;
 pub(crate) mod screen;
+pub(crate) mod serial;
+pub(crate) mod stack_guard;
+pub(crate) mod test_port;
+pub(crate) mod trace;
+pub(crate) mod watchpoints;
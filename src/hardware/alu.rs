@@ -1,5 +1,5 @@
 use crate::instructions::base::byte::ByteDestination;
-use crate::instructions::changeset::{BitFlagsChange, Change};
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct AluU8Result {
@@ -23,7 +23,7 @@ impl AluU8Result {
 		)
 	}
 
-	pub(crate) fn change_dst(&self, dst: &ByteDestination) -> Box<dyn Change> {
+	pub(crate) fn change_dst(&self, dst: &ByteDestination) -> ChangeKind {
 		dst.change_destination(self.result)
 	}
 }
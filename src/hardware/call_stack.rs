@@ -0,0 +1,64 @@
+/// A CALL or RST that hasn't returned yet
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+	/// Address of the calling instruction
+	pub from_pc: u16,
+	pub to_address: u16,
+	/// SP after the call, pointing at the pushed return address
+	pub sp_at_call: u16,
+}
+
+// Shadow of the calls on the stack. Code can drop return addresses without returning (POP, writing SP), so frames
+// whose return address is below SP are treated as gone instead of expecting every call to be matched by a return.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CallStack {
+	max_depth: usize,
+	frames: Vec<CallFrame>,
+}
+
+impl CallStack {
+	pub(crate) fn new(max_depth: usize) -> Self {
+		Self {
+			max_depth,
+			frames: Vec::new(),
+		}
+	}
+
+	pub(crate) fn call(&mut self, frame: CallFrame) {
+		// Whatever was pushed at or below the new return address has been overwritten
+		while self
+			.frames
+			.last()
+			.is_some_and(|last| last.sp_at_call <= frame.sp_at_call)
+		{
+			self.frames.pop();
+		}
+		if self.max_depth == 0 {
+			return;
+		}
+		if self.frames.len() == self.max_depth {
+			self.frames.remove(0);
+		}
+		self.frames.push(frame);
+	}
+
+	/// Return with SP pointing at the return address
+	pub(crate) fn ret(&mut self, sp: u16) {
+		self.drop_stale(sp);
+		// A return address pushed by hand has no frame, so only pop the one it was called with
+		if self.frames.last().is_some_and(|frame| frame.sp_at_call == sp) {
+			self.frames.pop();
+		}
+	}
+
+	pub(crate) fn frames(&self) -> &[CallFrame] {
+		&self.frames
+	}
+
+	// Frames whose return address sits below `sp` were popped off the stack some other way
+	fn drop_stale(&mut self, sp: u16) {
+		while self.frames.last().is_some_and(|frame| frame.sp_at_call < sp) {
+			self.frames.pop();
+		}
+	}
+}
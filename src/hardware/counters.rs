@@ -1,6 +1,18 @@
 pub(crate) mod divider;
 pub(crate) mod timer;
 
-trait Tick {
-	fn tick(&mut self);
+use crate::hardware::interrupts::Interrupt;
+
+/// Peripherals clocked by the CPU, one call per clock cycle. Returns the interrupt the peripheral requested, if any.
+pub(crate) trait Tick {
+	fn tick(&mut self) -> Option<Interrupt>;
+
+	/// Several cycles at once, for peripherals that can do better than ticking one by one
+	fn tick_cycles(&mut self, cycles: u32) -> Option<Interrupt> {
+		let mut requested = None;
+		for _ in 0..cycles {
+			requested = self.tick().or(requested);
+		}
+		requested
+	}
 }
@@ -1,13 +1,16 @@
 use super::Tick;
+use crate::hardware::interrupts::Interrupt;
 use crate::hardware::ram::{Ram, RamError, Rom};
 
+pub(crate) const DIVIDER_ADDRESS: u16 = 0xFF04;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub(crate) struct DividerRegister {
 	value: u16,
 }
 
 impl DividerRegister {
-	fn new(value: u16) -> Self {
+	pub(crate) fn new(value: u16) -> Self {
 		Self { value }
 	}
 }
@@ -19,12 +22,16 @@ impl Default for DividerRegister {
 }
 
 impl Tick for DividerRegister {
-	fn tick(&mut self) {
-		let (new_value, overflow) = self.value.overflowing_add(1);
-		if overflow {
-			// TODO: trigger interrupt
-		}
-		self.value = new_value;
+	// Wrapping around requests nothing, DIV has no interrupt of its own
+	fn tick(&mut self) -> Option<Interrupt> {
+		self.value = self.value.wrapping_add(1);
+		None
+	}
+
+	fn tick_cycles(&mut self, cycles: u32) -> Option<Interrupt> {
+		// Only the low 16 bits of the cycle count can show up in the register
+		self.value = self.value.wrapping_add(cycles as u16);
+		None
 	}
 }
 
@@ -1,4 +1,5 @@
 use super::Tick;
+use crate::hardware::interrupts::Interrupt;
 use crate::hardware::ram::{Ram, RamError, Rom};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
@@ -45,14 +46,14 @@ impl Timer {
 		self.selected_clock_speed = selected_clock_speed;
 		self.enabled = enabled;
 
-		self.ticks = self.ticks % self.selected_clock_speed.freq();
+		self.ticks %= self.selected_clock_speed.freq();
 	}
 }
 
 impl Timer {}
 
 impl Tick for Timer {
-	fn tick(&mut self) {
+	fn tick(&mut self) -> Option<Interrupt> {
 		let freq = self.selected_clock_speed.freq();
 		let new_ticks = (self.ticks + 1) % freq;
 		let update_counter = ((self.ticks + 1) / freq) > 0;
@@ -62,12 +63,14 @@ impl Tick for Timer {
 		if update_counter && self.enabled {
 			let (new_counter, overflow) = self.counter.overflowing_add(1);
 			if overflow {
-				// TODO: trigger interrupt
+				// TIMA reloads from TMA and requests the timer interrupt
 				self.counter = self.modulo;
-			} else {
-				self.counter = new_counter;
+				return Some(Interrupt::Timer);
 			}
+			self.counter = new_counter;
 		}
+
+		None
 	}
 }
 
@@ -1,29 +1,233 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Range, RangeInclusive};
+
+use crate::coverage::CoverageReport;
+use crate::decoder::hooks::{OpcodeHookHandler, OpcodeHooks};
+use crate::decoder::{fetch_and_decode, DecodedInstruction};
+use crate::hardware::call_stack::{CallFrame, CallStack};
 use crate::hardware::ime::Ime;
-use crate::hardware::ram::Rom;
-use crate::hardware::register_bank::{ProgramCounter, StackPointer};
+use crate::hardware::interrupts::{self, Interrupt, DISPATCH_CYCLES, INTERRUPT_FLAGS_ADDRESS};
+use crate::hardware::joypad::{Button, Joypad};
+use crate::hardware::ram::{Ram, RamError, Rom};
+use crate::hardware::register_bank::{BitFlags, DoubleRegisters, ProgramCounter, SingleRegisters, StackPointer};
+use crate::hardware::screen::tile_map::{self, Framebuffer, TileMapAddressMode, Viewport};
+use crate::hardware::stack_guard::{StackGuard, StackViolation};
+use crate::hardware::test_port::{TestPort, TestPortEvent, TEST_PORT_ADDRESS};
+use crate::hardware::trace::{TraceBuffer, TraceEvent};
+use crate::hardware::watchpoints::{WatchHit, WatchSet};
 use crate::instructions::ExecutionError;
 
-use super::ram::MappedMemory;
+#[cfg(feature = "single-step-tests")]
+use super::ram::TestBus;
+use super::ram::{MappedMemory, SystemBus, BOOTSTRAP_RAM_SIZE};
 use super::register_bank::RegisterBank;
 
+/// Clock cycles in one DMG frame: 154 lines of 456 cycles
+pub const CYCLES_PER_FRAME: u32 = 70_224;
+
+/// Unprefixed opcodes, followed by the ones after the CB prefix
+pub const OPCODE_COUNTS_SIZE: usize = 512;
+
+// Clock cycles of a step while halted or stopped
+const HALTED_CYCLES: u8 = 4;
+
+const NO_OPCODE_COUNTS: [u64; OPCODE_COUNTS_SIZE] = [0; OPCODE_COUNTS_SIZE];
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Cpu {
 	pub(crate) register_bank: RegisterBank,
-	pub(crate) mapped_ram: MappedMemory,
+	pub(crate) mapped_ram: SystemBus,
 	pub(crate) pc: ProgramCounter,
 	pub(crate) sp: StackPointer,
 	pub(crate) ime: Ime,
+	/// Set by EI, IME is enabled after the following instruction
+	pub(crate) ime_scheduled: bool,
+	pub(crate) stack_guard: Option<StackGuard>,
+	pub(crate) opcode_hooks: OpcodeHooks,
+	pub(crate) trace: Option<TraceBuffer>,
+	pub(crate) watchpoints: Option<WatchSet>,
+	pub(crate) test_port: Option<TestPort>,
+	pub(crate) opcode_counts: Option<Box<[u64; OPCODE_COUNTS_SIZE]>>,
+	pub(crate) call_stack: Option<CallStack>,
+	pub(crate) halted: bool,
+	pub(crate) stopped: bool,
+	/// Set by the HALT bug, the next fetch reads its first byte twice
+	pub(crate) halt_bug: bool,
+	/// Clock cycles stepped since the CPU was created
+	pub(crate) cycles: u64,
 }
 
 impl Cpu {
 	pub fn new() -> Self {
 		Self {
 			register_bank: RegisterBank::new(),
-			mapped_ram: MappedMemory::new(),
+			mapped_ram: SystemBus::Mapped(MappedMemory::new()),
 			pc: ProgramCounter::new(),
 			sp: StackPointer::new(),
 			ime: Ime::new(),
+			ime_scheduled: false,
+			stack_guard: None,
+			opcode_hooks: OpcodeHooks::default(),
+			trace: None,
+			watchpoints: None,
+			test_port: None,
+			opcode_counts: None,
+			call_stack: None,
+			halted: false,
+			stopped: false,
+			halt_bug: false,
+			cycles: 0,
+		}
+	}
+
+	/// Runs the given boot ROM dump instead of the one embedded at build time
+	pub fn with_boot_rom(boot_rom: [u8; BOOTSTRAP_RAM_SIZE]) -> Self {
+		Self {
+			mapped_ram: SystemBus::Mapped(MappedMemory::with_boot_rom(boot_rom)),
+			..Self::new()
+		}
+	}
+
+	/// Puts the CPU and IO registers in the state the DMG boot ROM leaves them in, ready to run the cartridge from 0x0100.
+	/// Memory is left as it is, and so is the boot ROM mapping.
+	pub fn reset_post_boot(&mut self) {
+		self.register_bank.write_double_named(DoubleRegisters::AF, 0x01B0);
+		self.register_bank.write_double_named(DoubleRegisters::BC, 0x0013);
+		self.register_bank.write_double_named(DoubleRegisters::DE, 0x00D8);
+		self.register_bank.write_double_named(DoubleRegisters::HL, 0x014D);
+		self.sp.write(0xFFFE);
+		self.pc.write(0x0100);
+		self.ime.write(false);
+		self.ime_scheduled = false;
+		self.halted = false;
+		self.stopped = false;
+		self.halt_bug = false;
+		self.mapped_ram.reset_post_boot();
+	}
+
+	/// CPU on a flat 64KiB test bus instead of the Game Boy memory map
+	#[cfg(feature = "single-step-tests")]
+	pub fn with_test_bus() -> Self {
+		Self {
+			mapped_ram: SystemBus::Test(TestBus::default()),
+			..Self::new()
+		}
+	}
+
+	/// Services a pending interrupt, or else executes the next instruction.
+	/// While halted or stopped nothing is fetched, each step waits one machine cycle until the CPU wakes up.
+	/// A failed fetch leaves the CPU as it was.
+	pub fn step(&mut self) -> Result<StepOutcome, ExecutionError> {
+		// Lines pulled low since the last step, by a press or a select write, request the joypad interrupt
+		if self.mapped_ram.joypad_mut().is_some_and(Joypad::take_interrupt) {
+			self.request_interrupt(Interrupt::Joypad)?;
+		}
+
+		let outcome = self.step_instruction()?;
+		self.advance_cycles(u32::from(outcome.cycles()))?;
+		Ok(outcome)
+	}
+
+	fn step_instruction(&mut self) -> Result<StepOutcome, ExecutionError> {
+		if self.stopped {
+			// Only the joypad wakes STOP, the test bus has none so it stays stopped
+			if self.mapped_ram.joypad().is_some_and(Joypad::any_line_low) {
+				self.stopped = false;
+			}
+			return Ok(StepOutcome::Idle { cycles: HALTED_CYCLES });
+		}
+		if self.halted {
+			// Wakes even with IME clear, execution then resumes after the HALT without servicing the interrupt
+			if interrupts::pending(self)? != 0 {
+				self.halted = false;
+			}
+			return Ok(StepOutcome::Idle { cycles: HALTED_CYCLES });
+		}
+
+		if let Some(interrupt) = interrupts::dispatch(self)? {
+			return Ok(StepOutcome::Interrupt {
+				interrupt,
+				cycles: DISPATCH_CYCLES,
+			});
+		}
+
+		let pc = self.current_pc();
+		let instruction = fetch_and_decode(self)?;
+		let cycles = instruction.execute(self)?;
+		Ok(StepOutcome::Executed {
+			pc,
+			instruction,
+			cycles,
+		})
+	}
+
+	/// Clock cycles stepped since the CPU was created
+	pub fn elapsed_cycles(&self) -> u64 {
+		self.cycles
+	}
+
+	// Lets the peripherals catch up with the cycles the CPU just spent, raising the interrupt they requested in IF
+	pub(crate) fn advance_cycles(&mut self, cycles: u32) -> Result<(), ExecutionError> {
+		self.cycles += u64::from(cycles);
+		if let Some(interrupt) = self.mapped_ram.tick_cycles(cycles) {
+			self.request_interrupt(interrupt)?;
 		}
+		Ok(())
+	}
+
+	/// Steps until at least `budget` clock cycles have elapsed, returning how many went past it so the caller can take
+	/// them off the next budget. Halted or stopped, the budget is spent waiting.
+	pub fn run_cycles(&mut self, budget: u32) -> Result<u32, RunError> {
+		let mut cycles = 0;
+		while cycles < budget {
+			match self.step() {
+				Ok(outcome) => cycles += u32::from(outcome.cycles()),
+				Err(error) => return Err(RunError { cycles, error }),
+			}
+		}
+
+		Ok(cycles - budget)
+	}
+
+	/// Jumps to the vector of the highest priority interrupt that is requested and enabled, if IME is set.
+	/// Returns the clock cycles it took, or `None` if nothing was serviced.
+	pub fn dispatch_interrupt(&mut self) -> Result<Option<u8>, ExecutionError> {
+		let dispatched = interrupts::dispatch(self)?;
+		Ok(dispatched.map(|_| DISPATCH_CYCLES))
+	}
+
+	/// Whether an interrupt is both requested in IF and enabled in IE, IME aside
+	pub fn interrupt_pending(&self) -> Result<bool, ExecutionError> {
+		Ok(interrupts::pending(self)? != 0)
+	}
+
+	/// Sets the interrupt's bit in IF, as its source would
+	pub fn request_interrupt(&mut self, interrupt: Interrupt) -> Result<(), ExecutionError> {
+		let requested = self.mapped_ram.read_byte(INTERRUPT_FLAGS_ADDRESS)?;
+		self.mapped_ram
+			.write_byte(INTERRUPT_FLAGS_ADDRESS, requested | interrupt.mask())?;
+		Ok(())
+	}
+
+	/// Renders a whole 256x256 background tile map with the current tile data addressing and palette
+	pub fn debug_render_tile_map(&self, which: TileMapAddressMode) -> Result<Framebuffer, ExecutionError> {
+		tile_map::render_tile_map(self, which)
+	}
+
+	/// Where the screen is on the background, from SCX and SCY
+	pub fn viewport_rect(&self) -> Result<Viewport, ExecutionError> {
+		tile_map::viewport_rect(self)
+	}
+
+	/// Whether a HALT is waiting for an interrupt
+	pub fn halted(&self) -> bool {
+		self.halted
+	}
+
+	/// Whether a STOP is waiting for a button press
+	pub fn stopped(&self) -> bool {
+		self.stopped
 	}
 
 	pub(crate) fn next_pc(&mut self) -> u16 {
@@ -33,14 +237,254 @@ impl Cpu {
 		result
 	}
 
-	pub(crate) fn next_byte(&mut self) -> Result<u8, ExecutionError> {
-		let pc = self.next_pc();
-		let byte = self.mapped_ram.read_byte(pc)?;
+	pub fn current_pc(&self) -> u16 {
+		self.pc.read()
+	}
+
+	pub fn set_pc(&mut self, value: u16) {
+		self.pc.write(value);
+	}
+
+	pub fn read_register(&self, register: SingleRegisters) -> u8 {
+		self.register_bank.read_single_named(register)
+	}
+
+	pub fn write_register(&mut self, register: SingleRegisters, value: u8) {
+		self.register_bank.write_single_named(register, value);
+	}
+
+	pub fn read_double_register(&self, register: DoubleRegisters) -> u16 {
+		self.register_bank.read_double_named(register)
+	}
+
+	pub fn write_double_register(&mut self, register: DoubleRegisters, value: u16) {
+		self.register_bank.write_double_named(register, value);
+	}
+
+	pub fn read_flag(&self, flag: BitFlags) -> bool {
+		self.register_bank.read_bit_flag(flag)
+	}
+
+	pub fn write_flag(&mut self, flag: BitFlags, value: bool) {
+		self.register_bank.write_bit_flag(flag, value);
+	}
+
+	pub fn current_sp(&self) -> u16 {
+		self.sp.read()
+	}
+
+	pub fn set_sp(&mut self, value: u16) {
+		self.sp.write(value);
+	}
+
+	pub fn ime(&self) -> bool {
+		self.ime.read()
+	}
+
+	pub fn set_ime(&mut self, value: bool) {
+		self.ime.write(value);
+	}
+
+	pub fn read_byte(&self, address: u16) -> Result<u8, ExecutionError> {
+		let byte = self.mapped_ram.read_byte(address)?;
 		Ok(byte)
 	}
 
-	pub fn current_pc(&self) -> u16 {
-		self.pc.read()
+	/// Reads memory without any side effect on the hardware, for debuggers and viewers
+	pub fn peek_byte(&self, address: u16) -> Result<u8, ExecutionError> {
+		let byte = self.mapped_ram.peek_byte(address)?;
+		Ok(byte)
+	}
+
+	pub fn write_byte(&mut self, address: u16, value: u8) -> Result<(), ExecutionError> {
+		self.mapped_ram.write_byte(address, value)?;
+		Ok(())
+	}
+
+	/// Write to memory on behalf of the user, such as from a debugger. ROM can only be patched when `force` is set.
+	pub fn write_memory(&mut self, address: u16, value: u8, force: bool) -> Result<(), MemoryEditError> {
+		if force {
+			self.mapped_ram.force_write_byte(address, value)?;
+		} else {
+			self.mapped_ram.write_byte(address, value)?;
+		}
+		Ok(())
+	}
+
+	/// Press or release a button. Does nothing on the test bus, which has no joypad.
+	pub fn set_button(&mut self, button: Button, pressed: bool) {
+		if let Some(joypad) = self.mapped_ram.joypad_mut() {
+			joypad.set_pressed(button, pressed);
+		}
+	}
+
+	/// Record every SP write that lands outside `range`, or stop checking with `None`
+	pub fn set_stack_guard(&mut self, range: Option<Range<u16>>) {
+		self.stack_guard = range.map(StackGuard::new);
+	}
+
+	/// Let `handler` decide how `opcode` is decoded, replacing any previous hook for it.
+	/// Only unprefixed opcodes at the start of an instruction are hooked.
+	pub fn register_opcode_hook(&mut self, opcode: u8, handler: Box<OpcodeHookHandler>) {
+		self.opcode_hooks.register(opcode, handler);
+	}
+
+	/// Violations recorded since the last call, oldest first
+	pub fn take_stack_violations(&mut self) -> Vec<StackViolation> {
+		self.stack_guard
+			.as_mut()
+			.map(StackGuard::take_violations)
+			.unwrap_or_default()
+	}
+
+	/// Record every write to an address in `range`, on top of the ranges already watched
+	pub fn add_watchpoint(&mut self, range: RangeInclusive<u16>) {
+		self.watchpoints.get_or_insert_with(WatchSet::default).add(range);
+	}
+
+	/// Stop watching writes, dropping any hits not taken yet
+	pub fn clear_watchpoints(&mut self) {
+		self.watchpoints = None;
+	}
+
+	/// Watched writes since the last call, oldest first
+	pub fn take_watchpoint_hits(&mut self) -> Vec<WatchHit> {
+		self.watchpoints.as_mut().map(WatchSet::take_hits).unwrap_or_default()
+	}
+
+	// Called before the write is committed, so the old value can still be read
+	pub(crate) fn watch_write(&mut self, address: u16, new_value: u8) {
+		let watched = match self.watchpoints.as_ref() {
+			Some(watchpoints) => watchpoints.is_watched(address),
+			None => return,
+		};
+		if !watched {
+			return;
+		}
+
+		let hit = WatchHit {
+			pc: self.pc.read(),
+			address,
+			old_value: self.mapped_ram.peek_byte(address).unwrap_or(0xFF),
+			new_value,
+		};
+		if let Some(watchpoints) = self.watchpoints.as_mut() {
+			watchpoints.record(hit);
+		}
+	}
+
+	/// Listen for test program commands written to `TEST_PORT_ADDRESS`, or ignore them like hardware does.
+	/// Disabling it drops the events not taken yet.
+	pub fn set_test_port(&mut self, enabled: bool) {
+		if !enabled {
+			self.test_port = None;
+		} else if self.test_port.is_none() {
+			self.test_port = Some(TestPort::default());
+		}
+	}
+
+	/// Test port commands since the last call, oldest first
+	pub fn take_test_port_events(&mut self) -> Vec<TestPortEvent> {
+		self.test_port.as_mut().map(TestPort::take_events).unwrap_or_default()
+	}
+
+	// Called before the write is committed, the port itself is an IO hole that ignores it
+	pub(crate) fn test_port_write(&mut self, address: u16, command: u8) {
+		if address != TEST_PORT_ADDRESS {
+			return;
+		}
+
+		let pc = self.pc.read();
+		let a = self.register_bank.read_single_named(SingleRegisters::A);
+		let b = self.register_bank.read_single_named(SingleRegisters::B);
+		if let Some(test_port) = self.test_port.as_mut() {
+			test_port.command(pc, command, a, b);
+		}
+	}
+
+	/// Track calls and returns, keeping the innermost `max_depth` frames, or stop tracking with `None`
+	pub fn set_call_stack_depth(&mut self, max_depth: Option<usize>) {
+		self.call_stack = max_depth.map(CallStack::new);
+	}
+
+	/// Calls that haven't returned yet, outermost first. Empty when not tracking.
+	pub fn call_stack(&self) -> &[CallFrame] {
+		self.call_stack.as_ref().map(CallStack::frames).unwrap_or_default()
+	}
+
+	/// Count how many times each opcode is fetched. Disabling it drops the counts.
+	pub fn set_opcode_profiling(&mut self, enabled: bool) {
+		if !enabled {
+			self.opcode_counts = None;
+		} else if self.opcode_counts.is_none() {
+			self.opcode_counts = Some(Box::new(NO_OPCODE_COUNTS));
+		}
+	}
+
+	/// Fetches of each opcode, CB-prefixed ones from index 256 on. All zeros when profiling is off.
+	pub fn opcode_counts(&self) -> &[u64; OPCODE_COUNTS_SIZE] {
+		self.opcode_counts.as_deref().unwrap_or(&NO_OPCODE_COUNTS)
+	}
+
+	/// Opcodes never executed since profiling was enabled
+	pub fn coverage_report(&self) -> CoverageReport {
+		CoverageReport::new(self.opcode_counts())
+	}
+
+	// Called with PC on the opcode, before it's fetched
+	pub(crate) fn count_opcode(&mut self) {
+		let counts = match self.opcode_counts.as_mut() {
+			Some(counts) => counts,
+			None => return,
+		};
+
+		let pc = self.pc.read();
+		let index = match self.mapped_ram.peek_byte(pc) {
+			Ok(0xCB) => self
+				.mapped_ram
+				.peek_byte(pc.wrapping_add(1))
+				.map(|opcode| 0x100 + usize::from(opcode)),
+			opcode => opcode.map(usize::from),
+		};
+		// The decoder reports the failed read
+		if let Ok(index) = index {
+			counts[index] += 1;
+		}
+	}
+
+	/// Keep the last `capacity` fetched instructions, or stop tracing with `None`
+	pub fn set_trace_capacity(&mut self, capacity: Option<usize>) {
+		self.trace = capacity.map(TraceBuffer::new);
+	}
+
+	/// Traced instructions, oldest first. Empty when tracing is off.
+	pub fn trace_buffer(&self) -> impl Iterator<Item = &TraceEvent> {
+		self.trace.iter().flat_map(TraceBuffer::events)
+	}
+
+	// Only copies the fetched bytes, the mnemonic is formatted when the trace is read
+	pub(crate) fn record_trace(&mut self, pc: u16, bytes: &[u8]) {
+		if self.trace.is_none() {
+			return;
+		}
+
+		let mut operands = [0; 2];
+		for (operand, byte) in operands.iter_mut().zip(bytes.iter().skip(1)) {
+			*operand = *byte;
+		}
+		let event = TraceEvent {
+			pc,
+			opcode: bytes[0],
+			operands,
+			af: self.register_bank.read_double_named(DoubleRegisters::AF),
+			bc: self.register_bank.read_double_named(DoubleRegisters::BC),
+			de: self.register_bank.read_double_named(DoubleRegisters::DE),
+			hl: self.register_bank.read_double_named(DoubleRegisters::HL),
+			sp: self.sp.read(),
+		};
+		if let Some(trace) = self.trace.as_mut() {
+			trace.push(event);
+		}
 	}
 }
 
@@ -49,3 +493,427 @@ impl Default for Cpu {
 		Self::new()
 	}
 }
+
+/// What a call to `Cpu::step` did, and the clock cycles it took
+#[derive(Debug)]
+pub enum StepOutcome {
+	/// The instruction that was at `pc` executed, branch taken cycles included
+	Executed {
+		pc: u16,
+		instruction: DecodedInstruction,
+		cycles: u8,
+	},
+	/// An interrupt was dispatched instead of executing an instruction
+	Interrupt { interrupt: Interrupt, cycles: u8 },
+	/// Halted or stopped, nothing was fetched
+	Idle { cycles: u8 },
+}
+
+impl StepOutcome {
+	pub fn cycles(&self) -> u8 {
+		match self {
+			Self::Executed { cycles, .. } | Self::Interrupt { cycles, .. } | Self::Idle { cycles } => *cycles,
+		}
+	}
+
+	pub fn interrupt_dispatched(&self) -> bool {
+		matches!(self, Self::Interrupt { .. })
+	}
+}
+
+/// An instruction failed during `Cpu::run_cycles`, after the instructions before it took `cycles`
+#[derive(Debug)]
+pub struct RunError {
+	pub cycles: u32,
+	pub error: ExecutionError,
+}
+
+impl Display for RunError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} after {} cycles", self.error, self.cycles)
+	}
+}
+
+impl Error for RunError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		Some(&self.error)
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MemoryEditError {
+	ReadOnly(u16),
+	Unmapped(u16),
+}
+
+impl Display for MemoryEditError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ReadOnly(address) => write!(
+				f,
+				"{address:#06X} is read only, it can only be patched by forcing the write"
+			),
+			Self::Unmapped(address) => write!(f, "Nothing is mapped at {address:#06X}"),
+		}
+	}
+}
+
+impl Error for MemoryEditError {}
+
+impl From<RamError> for MemoryEditError {
+	fn from(ram_error: RamError) -> Self {
+		match ram_error {
+			RamError::WriteOnRom(address) => Self::ReadOnly(address),
+			RamError::InvalidAddress(address) | RamError::UnmappedRegion(address) => Self::Unmapped(address),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::decoder::fetch_and_decode;
+	use crate::hardware::ram::WORKING_RAM_START;
+
+	use super::*;
+
+	#[test]
+	fn flag_register_lower_nibble() {
+		let mut cpu = Cpu::new();
+
+		cpu.write_register(SingleRegisters::F, 0xFF);
+		assert_eq!(cpu.read_register(SingleRegisters::F), 0xF0);
+
+		cpu.write_double_register(DoubleRegisters::AF, 0x12FF);
+		assert_eq!(cpu.read_double_register(DoubleRegisters::AF), 0x12F0);
+
+		cpu.write_flag(BitFlags::Carry, false);
+		cpu.write_flag(BitFlags::Zero, false);
+		assert_eq!(cpu.read_register(SingleRegisters::F), 0x60);
+		assert!(!cpu.read_flag(BitFlags::Carry));
+		assert!(cpu.read_flag(BitFlags::HalfCarry));
+	}
+
+	#[test]
+	fn unforced_rom_write() {
+		let mut cpu = Cpu::new();
+		let original = cpu.read_byte(0x0000).unwrap();
+
+		assert_eq!(
+			cpu.write_memory(0x0000, 0x04, false),
+			Err(MemoryEditError::ReadOnly(0x0000))
+		);
+		assert_eq!(cpu.read_byte(0x0000).unwrap(), original);
+	}
+
+	#[test]
+	fn forced_rom_patch() {
+		let mut cpu = Cpu::new();
+
+		// inc B
+		cpu.write_memory(0x0000, 0x04, true).unwrap();
+		cpu.set_pc(0x0000);
+
+		let instruction = fetch_and_decode(&mut cpu).unwrap();
+		instruction.execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.read_register(SingleRegisters::B), 0x01);
+		assert_eq!(
+			Cpu::new().read_byte(0x0000).unwrap(),
+			0x31,
+			"Patch leaked into the shared ROM data"
+		);
+	}
+
+	#[test]
+	#[cfg(not(external_boot_rom))]
+	fn boot_stub_post_boot_state() {
+		let mut cpu = Cpu::new();
+
+		let mut steps = 0;
+		while cpu.current_pc() != 0x0100 {
+			fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+			steps += 1;
+			assert!(steps < 0x100, "Stub didn't reach the cartridge");
+		}
+
+		let registers = [
+			(DoubleRegisters::AF, 0x01B0),
+			(DoubleRegisters::BC, 0x0013),
+			(DoubleRegisters::DE, 0x00D8),
+			(DoubleRegisters::HL, 0x014D),
+		];
+		for (register, value) in registers {
+			assert_eq!(cpu.read_double_register(register), value, "{register}");
+		}
+		assert_eq!(cpu.current_sp(), 0xFFFE);
+		assert_eq!(cpu.read_byte(0xFF40).unwrap(), 0x91, "LCDC");
+		assert_eq!(cpu.read_byte(0xFF47).unwrap(), 0xFC, "BGP");
+	}
+
+	#[test]
+	fn reset_post_boot() {
+		let mut cpu = Cpu::new();
+		cpu.write_double_register(DoubleRegisters::HL, 0x1234);
+		cpu.write_byte(0xFF47, 0x00).unwrap();
+		cpu.write_byte(0xFF42, 0x56).unwrap();
+		cpu.write_byte(0xFFFF, 0x1F).unwrap();
+		cpu.set_ime(true);
+
+		cpu.reset_post_boot();
+
+		let registers = [
+			(DoubleRegisters::AF, 0x01B0),
+			(DoubleRegisters::BC, 0x0013),
+			(DoubleRegisters::DE, 0x00D8),
+			(DoubleRegisters::HL, 0x014D),
+		];
+		for (register, value) in registers {
+			assert_eq!(cpu.read_double_register(register), value, "{register}");
+		}
+		assert_eq!(cpu.current_sp(), 0xFFFE);
+		assert_eq!(cpu.current_pc(), 0x0100);
+		assert!(!cpu.ime());
+
+		let io_registers = [
+			(0xFF04, 0xAB, "DIV"),
+			(0xFF05, 0x00, "TIMA"),
+			(0xFF06, 0x00, "TMA"),
+			(0xFF07, 0x00, "TAC"),
+			(0xFF0F, 0xE1, "IF"),
+			(0xFF40, 0x91, "LCDC"),
+			(0xFF41, 0x85, "STAT"),
+			(0xFF42, 0x00, "SCY"),
+			(0xFF43, 0x00, "SCX"),
+			(0xFF47, 0xFC, "BGP"),
+			(0xFF4A, 0x00, "WY"),
+			(0xFFFF, 0x00, "IE"),
+		];
+		for (address, value, name) in io_registers {
+			assert_eq!(cpu.read_byte(address).unwrap(), value, "{name}");
+		}
+	}
+
+	#[test]
+	fn trace_fetched_instructions() {
+		let mut cpu = Cpu::new();
+		cpu.set_trace_capacity(Some(2));
+		// ld B, 0x42; inc B; ld C, B
+		for (offset, byte) in [0x06, 0x42, 0x04, 0x48].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+
+		for _ in 0..3 {
+			fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+		}
+
+		let events: Vec<_> = cpu.trace_buffer().collect();
+		assert_eq!(events.len(), 2);
+		assert_eq!(events[0].pc, WORKING_RAM_START + 2);
+		assert_eq!(events[0].opcode, 0x04);
+		assert_eq!(events[0].mnemonic(), "INC B");
+		assert_eq!(events[0].bc, 0x4200);
+		assert_eq!(events[1].mnemonic(), "LD C,B");
+		assert_eq!(events[1].bc, 0x4300);
+	}
+
+	#[test]
+	fn no_trace_by_default() {
+		let mut cpu = Cpu::new();
+		fetch_and_decode(&mut cpu).unwrap();
+
+		assert_eq!(cpu.trace_buffer().count(), 0);
+	}
+
+	#[test]
+	fn opcode_profiling() {
+		let mut cpu = Cpu::new();
+		cpu.set_opcode_profiling(true);
+		// ld B, 5; loop: dec B; jr nz, loop; swap A
+		for (offset, byte) in [0x06, 0x05, 0x05, 0x20, 0xFD, 0xCB, 0x37].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+
+		while cpu.current_pc() != WORKING_RAM_START + 7 {
+			fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+		}
+
+		let counts = cpu.opcode_counts();
+		assert_eq!(counts[0x06], 1);
+		assert_eq!(counts[0x05], 5);
+		assert_eq!(counts[0x20], 5);
+		assert_eq!(counts[0x100 + 0x37], 1);
+		assert_eq!(counts[0xCB], 0);
+		assert_eq!(counts.iter().sum::<u64>(), 12);
+
+		cpu.set_opcode_profiling(false);
+		assert_eq!(cpu.opcode_counts().iter().sum::<u64>(), 0);
+	}
+
+	#[test]
+	fn joypad_press_requests_interrupt() {
+		let mut cpu = Cpu::new();
+		cpu.set_ime(true);
+		cpu.set_sp(WORKING_RAM_START + 0x10);
+		cpu.set_pc(WORKING_RAM_START);
+		cpu.write_byte(0xFFFF, Interrupt::Joypad.mask()).unwrap();
+		// Action buttons selected
+		cpu.write_byte(0xFF00, 0x10).unwrap();
+
+		cpu.set_button(Button::Start, true);
+		let outcome = cpu.step().unwrap();
+		assert!(outcome.interrupt_dispatched());
+		assert_eq!(cpu.current_pc(), Interrupt::Joypad.vector());
+
+		// Held down, the line stays low and requests nothing more
+		cpu.set_ime(true);
+		let outcome = cpu.step().unwrap();
+		assert!(!outcome.interrupt_dispatched());
+	}
+
+	#[test]
+	fn step_services_interrupt() {
+		let mut cpu = Cpu::new();
+		cpu.set_ime(true);
+		cpu.set_sp(WORKING_RAM_START + 0x10);
+		cpu.set_pc(WORKING_RAM_START);
+		cpu.write_byte(0xFFFF, 0x01).unwrap();
+		cpu.request_interrupt(Interrupt::VBlank).unwrap();
+		assert!(cpu.interrupt_pending().unwrap());
+
+		let outcome = cpu.step().unwrap();
+		assert!(outcome.interrupt_dispatched());
+		assert!(!cpu.interrupt_pending().unwrap());
+		assert_eq!(outcome.cycles(), 20);
+		assert_eq!(cpu.current_pc(), 0x0040);
+		assert_eq!(cpu.read_byte(0xFF0F).unwrap() & 0x1F, 0x00);
+		assert!(!cpu.ime());
+	}
+
+	#[test]
+	fn step_nop() {
+		let mut cpu = Cpu::new();
+		cpu.write_byte(WORKING_RAM_START, 0x00).unwrap();
+		cpu.set_pc(WORKING_RAM_START);
+
+		match cpu.step().unwrap() {
+			StepOutcome::Executed {
+				pc,
+				instruction,
+				cycles,
+			} => {
+				assert_eq!(pc, WORKING_RAM_START);
+				assert_eq!(instruction.bytes(), [0x00]);
+				assert_eq!(instruction.to_string(), "nop");
+				assert_eq!(cycles, 4);
+			}
+			outcome => panic!("Expected an instruction, got {outcome:?}"),
+		}
+	}
+
+	#[test]
+	fn step_at_unmapped_pc() {
+		let mut cpu = Cpu::new();
+		// Past OAM, nothing is mapped until the IO registers
+		cpu.set_pc(0xFEA0);
+		let expected = cpu.clone();
+
+		assert!(cpu.step().is_err());
+		assert_eq!(cpu, expected);
+	}
+
+	#[test]
+	fn run_cycles_in_loop() {
+		let mut cpu = Cpu::new();
+		// jr -2, 12 cycles
+		cpu.write_byte(WORKING_RAM_START, 0x18).unwrap();
+		cpu.write_byte(WORKING_RAM_START + 1, 0xFE).unwrap();
+		cpu.set_pc(WORKING_RAM_START);
+
+		assert_eq!(cpu.run_cycles(100).unwrap(), 8);
+		assert!(cpu.run_cycles(CYCLES_PER_FRAME).unwrap() < 12);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START);
+	}
+
+	#[test]
+	fn run_cycles_halted() {
+		let mut cpu = Cpu::new();
+		cpu.set_pc(WORKING_RAM_START + 1);
+		cpu.halted = true;
+
+		assert_eq!(cpu.run_cycles(CYCLES_PER_FRAME).unwrap(), 0);
+		assert!(cpu.halted());
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 1);
+	}
+
+	#[test]
+	fn run_cycles_error() {
+		let mut cpu = Cpu::new();
+		// Two NOPs at the end of OAM, then nothing is mapped
+		cpu.set_pc(0xFE9E);
+
+		let err = cpu.run_cycles(CYCLES_PER_FRAME).unwrap_err();
+		assert_eq!(err.cycles, 8);
+		assert_eq!(cpu.current_pc(), 0xFEA0);
+	}
+
+	#[test]
+	fn divider_follows_cycles() {
+		let mut cpu = Cpu::new();
+		// Working RAM is all NOPs
+		cpu.set_pc(WORKING_RAM_START);
+
+		assert_eq!(cpu.run_cycles(1024).unwrap(), 0);
+		assert_eq!(cpu.elapsed_cycles(), 1024);
+		assert_eq!(cpu.read_byte(0xFF04).unwrap(), 4);
+	}
+
+	#[test]
+	fn timer_follows_cycles() {
+		let mut cpu = Cpu::new();
+		cpu.set_pc(WORKING_RAM_START);
+		// TMA left alone, TAC enabled at one increment every 16 cycles
+		cpu.write_byte(0xFF07, 0b101).unwrap();
+
+		cpu.run_cycles(1024).unwrap();
+		assert_eq!(cpu.read_byte(0xFF05).unwrap(), 64);
+
+		// Stopped, TIMA keeps its value
+		cpu.write_byte(0xFF07, 0b001).unwrap();
+		cpu.run_cycles(1024).unwrap();
+		assert_eq!(cpu.read_byte(0xFF05).unwrap(), 64);
+		assert_eq!(cpu.elapsed_cycles(), 2048);
+	}
+
+	#[test]
+	fn step_without_ime() {
+		let mut cpu = Cpu::new();
+		cpu.set_ime(false);
+		// inc B
+		cpu.write_byte(WORKING_RAM_START, 0x04).unwrap();
+		cpu.set_pc(WORKING_RAM_START);
+		cpu.write_byte(0xFFFF, 0x01).unwrap();
+		cpu.request_interrupt(Interrupt::VBlank).unwrap();
+
+		assert_eq!(cpu.step().unwrap().cycles(), 4);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 1);
+		assert_eq!(cpu.read_byte(0xFF0F).unwrap() & 0x1F, 0x01);
+	}
+
+	#[test]
+	fn runtime_boot_rom() {
+		let mut boot_rom = [0; BOOTSTRAP_RAM_SIZE];
+		// ld B, 0x42
+		boot_rom[..2].copy_from_slice(&[0x06, 0x42]);
+
+		let mut cpu = Cpu::with_boot_rom(boot_rom);
+		fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+
+		assert_eq!(cpu.read_register(SingleRegisters::B), 0x42);
+		assert_eq!(
+			cpu.write_memory(0x0000, 0x00, false),
+			Err(MemoryEditError::ReadOnly(0x0000))
+		);
+	}
+}
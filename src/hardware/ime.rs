@@ -10,7 +10,6 @@ impl Ime {
 		}
 	}
 
-	#[allow(unused)]
 	pub(crate) fn read(&self) -> bool {
 		self.interruptions_enabled
 	}
@@ -0,0 +1,177 @@
+use crate::hardware::cpu::Cpu;
+use crate::hardware::ram::{Ram, RamError, Rom};
+use crate::instructions::changeset::{
+	Change, ChangeIme, ChangeKind, ChangeList, MemoryByteWriteChange, MemoryDoubleByteWriteChange, PcChange, SpChange,
+};
+use crate::instructions::ExecutionError;
+
+pub(crate) const INTERRUPT_FLAGS_ADDRESS: u16 = 0xFF0F;
+pub(crate) const INTERRUPT_ENABLE_ADDRESS: u16 = 0xFFFF;
+
+/// Clock cycles taken by dispatching an interrupt: two wait states, pushing PC and jumping
+pub(crate) const DISPATCH_CYCLES: u8 = 20;
+
+const INTERRUPT_MASK: u8 = 0x1F;
+
+/// Interrupt sources, in priority order
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+	VBlank,
+	LcdStat,
+	Timer,
+	Serial,
+	Joypad,
+}
+
+impl Interrupt {
+	const PRIORITY: [Self; 5] = [Self::VBlank, Self::LcdStat, Self::Timer, Self::Serial, Self::Joypad];
+
+	/// Bit in IF and IE
+	pub(crate) fn mask(self) -> u8 {
+		1 << self as u8
+	}
+
+	pub(crate) fn vector(self) -> u16 {
+		0x40 + 8 * self as u16
+	}
+
+	// Highest priority interrupt in a set of IF/IE bits
+	fn first(pending: u8) -> Option<Self> {
+		Self::PRIORITY
+			.into_iter()
+			.find(|interrupt| pending & interrupt.mask() != 0)
+	}
+}
+
+// IF, the upper 3 bits aren't wired and read as 1
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct InterruptFlags {
+	requested: u8,
+}
+
+impl Rom for InterruptFlags {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		self.requested.read_byte(address).map(|value| value | !INTERRUPT_MASK)
+	}
+}
+
+impl Ram for InterruptFlags {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		self.requested.write_byte(address, value & INTERRUPT_MASK)
+	}
+}
+
+/// IF and IE, mapped at 0xFF0F and 0xFFFF
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct InterruptController {
+	pub(crate) flags: InterruptFlags,
+	pub(crate) enable: u8,
+}
+
+/// Interrupts both requested and enabled, whether or not IME lets them be serviced
+pub(crate) fn pending(cpu: &Cpu) -> Result<u8, ExecutionError> {
+	let requested = cpu.mapped_ram.read_byte(INTERRUPT_FLAGS_ADDRESS)?;
+	let enabled = cpu.mapped_ram.read_byte(INTERRUPT_ENABLE_ADDRESS)?;
+	Ok(requested & enabled & INTERRUPT_MASK)
+}
+
+/// Services the highest priority interrupt that is both requested and enabled, if IME allows it.
+/// Returns the interrupt, which took `DISPATCH_CYCLES`, or `None` when nothing was dispatched.
+pub(crate) fn dispatch(cpu: &mut Cpu) -> Result<Option<Interrupt>, ExecutionError> {
+	if !cpu.ime.read() {
+		return Ok(None);
+	}
+
+	let interrupt = match Interrupt::first(pending(cpu)?) {
+		Some(interrupt) => interrupt,
+		None => return Ok(None),
+	};
+
+	// Read through the bus, so the flat test bus can raise interrupts too
+	let requested = cpu.mapped_ram.read_byte(INTERRUPT_FLAGS_ADDRESS)?;
+	let sp = cpu.sp.read().wrapping_sub(2);
+	let flags = requested & !interrupt.mask();
+	ChangeList::new([
+		ChangeKind::from(SpChange::new(sp)),
+		ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(sp, cpu.pc.read())),
+		ChangeKind::from(MemoryByteWriteChange::write_to_immediate(
+			INTERRUPT_FLAGS_ADDRESS,
+			flags,
+		)),
+		ChangeKind::from(ChangeIme::new(false)),
+		ChangeKind::from(PcChange::new(interrupt.vector())),
+	])
+	.commit_change(cpu)?;
+
+	Ok(Some(interrupt))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::hardware::ram::WORKING_RAM_START;
+
+	use super::*;
+
+	fn get_cpu(ime: bool, requested: u8, enabled: u8) -> Cpu {
+		let mut cpu = Cpu::new();
+		cpu.set_ime(ime);
+		cpu.set_pc(0x1234);
+		cpu.set_sp(WORKING_RAM_START + 0x10);
+		cpu.write_byte(INTERRUPT_FLAGS_ADDRESS, requested).unwrap();
+		cpu.write_byte(INTERRUPT_ENABLE_ADDRESS, enabled).unwrap();
+		cpu
+	}
+
+	#[test]
+	fn vblank() {
+		let mut cpu = get_cpu(true, Interrupt::VBlank.mask(), 0xFF);
+
+		assert_eq!(dispatch(&mut cpu).unwrap(), Some(Interrupt::VBlank));
+		assert_eq!(cpu.current_pc(), 0x40);
+		assert_eq!(cpu.current_sp(), WORKING_RAM_START + 0x0E);
+		assert_eq!(cpu.read_byte(WORKING_RAM_START + 0x0E).unwrap(), 0x34);
+		assert_eq!(cpu.read_byte(WORKING_RAM_START + 0x0F).unwrap(), 0x12);
+		assert_eq!(cpu.read_byte(INTERRUPT_FLAGS_ADDRESS).unwrap(), 0xE0);
+		assert!(!cpu.ime());
+	}
+
+	#[test]
+	fn ime_clear() {
+		let mut cpu = get_cpu(false, Interrupt::VBlank.mask(), 0xFF);
+		let expected = cpu.clone();
+
+		assert_eq!(dispatch(&mut cpu).unwrap(), None);
+		assert_eq!(cpu, expected);
+	}
+
+	#[test]
+	fn not_enabled() {
+		let mut cpu = get_cpu(true, Interrupt::VBlank.mask(), !Interrupt::VBlank.mask());
+
+		assert_eq!(dispatch(&mut cpu).unwrap(), None);
+		assert_eq!(cpu.current_pc(), 0x1234);
+	}
+
+	#[test]
+	fn priority() {
+		let requested = Interrupt::Timer.mask() | Interrupt::Joypad.mask();
+		let mut cpu = get_cpu(true, requested, 0xFF);
+
+		dispatch(&mut cpu).unwrap();
+		assert_eq!(cpu.current_pc(), 0x50);
+		assert_eq!(
+			cpu.read_byte(INTERRUPT_FLAGS_ADDRESS).unwrap(),
+			0xE0 | Interrupt::Joypad.mask()
+		);
+	}
+
+	#[test]
+	fn flags_register() {
+		let mut flags = InterruptFlags::default();
+		flags.write_byte(0, 0xFF).unwrap();
+		assert_eq!(flags.read_byte(0).unwrap(), 0xFF);
+
+		flags.write_byte(0, Interrupt::Serial.mask()).unwrap();
+		assert_eq!(flags.read_byte(0).unwrap(), 0xE8);
+	}
+}
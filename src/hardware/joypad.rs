@@ -0,0 +1,206 @@
+use crate::hardware::ram::{Ram, RamError, Rom};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+	Right,
+	Left,
+	Up,
+	Down,
+	A,
+	B,
+	Select,
+	Start,
+}
+
+impl Button {
+	// Bit in the low nibble of JOYP, and whether it belongs to the action group rather than the directions
+	fn line(self) -> (u8, bool) {
+		match self {
+			Self::Right => (0, false),
+			Self::Left => (1, false),
+			Self::Up => (2, false),
+			Self::Down => (3, false),
+			Self::A => (0, true),
+			Self::B => (1, true),
+			Self::Select => (2, true),
+			Self::Start => (3, true),
+		}
+	}
+}
+
+const SELECT_DIRECTIONS: u8 = 1 << 4;
+const SELECT_ACTIONS: u8 = 1 << 5;
+const SELECT_MASK: u8 = SELECT_DIRECTIONS | SELECT_ACTIONS;
+const UNUSED_BITS: u8 = 0b1100_0000;
+const LINES_MASK: u8 = 0x0F;
+
+// JOYP. Select bits and lines are active low: a group is selected when its bit is 0, and a pressed button pulls its
+// line to 0. When both groups are selected each line reads the AND of the two, when none is they all read 1.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub(crate) struct Joypad {
+	select: u8,
+	// Pressed buttons of each group, as set bits in line order
+	directions: u8,
+	actions: u8,
+	interrupt_requested: bool,
+}
+
+impl Default for Joypad {
+	fn default() -> Self {
+		Self {
+			select: SELECT_MASK,
+			directions: 0,
+			actions: 0,
+			interrupt_requested: false,
+		}
+	}
+}
+
+impl Joypad {
+	fn lines(&self) -> u8 {
+		let mut pressed = 0;
+		if self.select & SELECT_DIRECTIONS == 0 {
+			pressed |= self.directions;
+		}
+		if self.select & SELECT_ACTIONS == 0 {
+			pressed |= self.actions;
+		}
+
+		!pressed & LINES_MASK
+	}
+
+	// The interrupt is requested when any line goes from high to low, whether a press or a select change caused it
+	fn update(&mut self, change: impl FnOnce(&mut Self)) {
+		let old_lines = self.lines();
+		change(self);
+		let new_lines = self.lines();
+
+		if old_lines & !new_lines != 0 {
+			self.interrupt_requested = true;
+		}
+	}
+
+	pub(crate) fn set_pressed(&mut self, button: Button, pressed: bool) {
+		let (bit, is_action) = button.line();
+
+		self.update(|joypad| {
+			let group = if is_action {
+				&mut joypad.actions
+			} else {
+				&mut joypad.directions
+			};
+			if pressed {
+				*group |= 1 << bit;
+			} else {
+				*group &= !(1 << bit);
+			}
+		});
+	}
+
+	/// Whether a pressed button in a selected group pulls its line low, which wakes the CPU from STOP
+	pub(crate) fn any_line_low(&self) -> bool {
+		self.lines() != LINES_MASK
+	}
+
+	pub(crate) fn take_interrupt(&mut self) -> bool {
+		std::mem::take(&mut self.interrupt_requested)
+	}
+}
+
+impl Rom for Joypad {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		match address {
+			0 => Ok(UNUSED_BITS | self.select | self.lines()),
+			_ => Err(RamError::InvalidAddress(address)),
+		}
+	}
+}
+
+impl Ram for Joypad {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		match address {
+			0 => {
+				self.update(|joypad| joypad.select = value & SELECT_MASK);
+				Ok(())
+			}
+			_ => Err(RamError::InvalidAddress(address)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const NEITHER: u8 = 0x30;
+	const DIRECTIONS: u8 = 0x20;
+	const ACTIONS: u8 = 0x10;
+	const BOTH: u8 = 0x00;
+
+	fn read_lines(joypad: &Joypad) -> u8 {
+		joypad.read_byte(0).expect("Read JOYP") & LINES_MASK
+	}
+
+	#[test]
+	fn select_combinations() {
+		let mut joypad = Joypad::default();
+		// Down and Start share line 3, Left and B share line 1
+		joypad.set_pressed(Button::Down, true);
+		joypad.set_pressed(Button::Start, true);
+		joypad.set_pressed(Button::B, true);
+
+		for (select, expected) in [
+			(NEITHER, 0b1111),
+			(DIRECTIONS, 0b0111),
+			(ACTIONS, 0b0101),
+			(BOTH, 0b0101),
+		] {
+			joypad.write_byte(0, select).expect("Write JOYP");
+			assert_eq!(read_lines(&joypad), expected, "select {select:#04X}");
+			assert_eq!(
+				joypad.read_byte(0).expect("Read JOYP") & !LINES_MASK,
+				UNUSED_BITS | select
+			);
+		}
+	}
+
+	#[test]
+	fn both_groups_and_lines() {
+		let mut joypad = Joypad::default();
+		joypad.write_byte(0, BOTH).expect("Write JOYP");
+
+		joypad.set_pressed(Button::Right, true);
+		joypad.set_pressed(Button::Select, true);
+		assert_eq!(read_lines(&joypad), 0b1010);
+	}
+
+	#[test]
+	fn interrupt_on_falling_edges() {
+		let mut joypad = Joypad::default();
+
+		// Nothing selected: pressing doesn't change any line
+		joypad.set_pressed(Button::Down, true);
+		assert!(!joypad.take_interrupt());
+
+		// Selecting the group with a button held pulls its line low
+		joypad.write_byte(0, DIRECTIONS).expect("Write JOYP");
+		assert!(joypad.take_interrupt());
+
+		// Start shares Down's line, which is already low
+		joypad.write_byte(0, BOTH).expect("Write JOYP");
+		joypad.set_pressed(Button::Start, true);
+		assert!(!joypad.take_interrupt());
+
+		// Releasing Down keeps the line low through Start, releasing both raises it
+		joypad.set_pressed(Button::Down, false);
+		joypad.set_pressed(Button::Start, false);
+		assert!(!joypad.take_interrupt());
+
+		joypad.set_pressed(Button::A, true);
+		assert!(joypad.take_interrupt());
+
+		// Deselecting raises lines, which never requests the interrupt
+		joypad.write_byte(0, NEITHER).expect("Write JOYP");
+		assert!(!joypad.take_interrupt());
+	}
+}
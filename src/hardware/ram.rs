@@ -1,4 +1,6 @@
-use crate::hardware::ram::bootstrap::BOOTSTRAP_DATA;
+use crate::hardware::interrupts::{Interrupt, INTERRUPT_ENABLE_ADDRESS};
+use crate::hardware::joypad::Joypad;
+pub(crate) use crate::hardware::ram::bootstrap::BOOTSTRAP_DATA;
 use crate::hardware::ram::io_registers::IoRegistersMemoryMapping;
 use chips::{RamChip, RomChip};
 
@@ -14,37 +16,49 @@ pub(crate) const ECHO_RAM_START: u16 = 0xE000;
 pub(crate) const OAM_START: u16 = 0xFE00;
 
 pub(crate) const IO_REGISTERS_MAPPING_START: u16 = 0xFF00;
+pub(crate) const HIGH_RAM_START: u16 = 0xFF80;
 
 mod bootstrap;
 mod chips;
 mod error;
 mod io_registers;
 mod memory_mapping;
+mod symbols;
 mod traits;
 
 use crate::hardware::ram::memory_mapping::{MemoryMapping, MemoryMappingEntry, RegionToMemoryMapper, RegionToMemoryMapperError};
-pub(crate) use error::RamError;
+pub use error::RamError;
+pub(crate) use symbols::address_name;
 pub(crate) use traits::{Ram, Rom};
 
-const BOOTSTRAP_RAM_SIZE: usize = 0x100;
+/// Size of the boot ROM, mapped from 0x0000 until the cartridge takes over
+pub const BOOTSTRAP_RAM_SIZE: usize = 0x100;
 const WORKING_RAM_SIZE: usize = (ECHO_RAM_START - WORKING_RAM_START) as usize;
+const ECHO_RAM_SIZE: usize = (OAM_START - ECHO_RAM_START) as usize; // Only 0xE000-0xFDFF mirrors WRAM, OAM follows
 const VIDEO_RAM_SIZE: usize = 8 * 1024;
 const IO_REGISTERS_MAPPING_SIZE: usize = 0x80;
 const OAM_SIZE: usize = 0xA0;
+const HIGH_RAM_SIZE: usize = (INTERRUPT_ENABLE_ADDRESS - HIGH_RAM_START) as usize;
+#[cfg(feature = "single-step-tests")]
+const TEST_BUS_SIZE: usize = 0x10000;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 enum MappedMemoryRegion {
 	Bootstrap,
 	WorkingRam,
+	EchoRam,
 	VideoRam,
 	IoRegisters,
 	Oam,
+	HighRam,
+	InterruptEnable,
 }
 
-const MEMORY_MAPPING_SIZE: usize = 5;
+const MEMORY_MAPPING_SIZE: usize = 8;
 const MEMORY_MAPPING_REGIONS: [MemoryMappingEntry<MappedMemoryRegion>; MEMORY_MAPPING_SIZE] = [
 	MemoryMappingEntry::new(MappedMemoryRegion::Bootstrap, BOOTSTRAP_RAM_START, BOOTSTRAP_RAM_SIZE),
 	MemoryMappingEntry::new(MappedMemoryRegion::WorkingRam, WORKING_RAM_START, WORKING_RAM_SIZE),
+	MemoryMappingEntry::new(MappedMemoryRegion::EchoRam, ECHO_RAM_START, ECHO_RAM_SIZE),
 	MemoryMappingEntry::new(MappedMemoryRegion::VideoRam, VIDEO_RAM_START, VIDEO_RAM_SIZE),
 	MemoryMappingEntry::new(
 		MappedMemoryRegion::IoRegisters,
@@ -52,6 +66,8 @@ const MEMORY_MAPPING_REGIONS: [MemoryMappingEntry<MappedMemoryRegion>; MEMORY_MA
 		IO_REGISTERS_MAPPING_SIZE,
 	),
 	MemoryMappingEntry::new(MappedMemoryRegion::Oam, OAM_START, OAM_SIZE),
+	MemoryMappingEntry::new(MappedMemoryRegion::HighRam, HIGH_RAM_START, HIGH_RAM_SIZE),
+	MemoryMappingEntry::new(MappedMemoryRegion::InterruptEnable, INTERRUPT_ENABLE_ADDRESS, 1),
 ];
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -62,19 +78,58 @@ pub struct MappedMemory {
 	video_ram: RamChip<VIDEO_RAM_SIZE>,
 	mapped_io_registers: IoRegistersMemoryMapping,
 	oam: RamChip<OAM_SIZE>,
+	high_ram: RamChip<HIGH_RAM_SIZE>,
 }
 
 impl MappedMemory {
 	pub(crate) fn new() -> Self {
+		Self::with_bootstrap(RomChip::new(BOOTSTRAP_DATA))
+	}
+
+	/// Maps the given boot ROM instead of the one embedded at build time
+	pub(crate) fn with_boot_rom(boot_rom: [u8; BOOTSTRAP_RAM_SIZE]) -> Self {
+		Self::with_bootstrap(RomChip::owned(boot_rom))
+	}
+
+	fn with_bootstrap(boostrap_ram: RomChip<'static, BOOTSTRAP_RAM_SIZE>) -> Self {
 		Self {
 			mapping: MemoryMapping::new(MEMORY_MAPPING_REGIONS),
-			boostrap_ram: RomChip::new(BOOTSTRAP_DATA),
+			boostrap_ram,
 			working_ram: RamChip::default(),
 			video_ram: RamChip::default(),
 			mapped_io_registers: IoRegistersMemoryMapping::default(),
 			oam: RamChip::default(),
+			high_ram: RamChip::default(),
+		}
+	}
+
+	/// Like a regular write, but ROM gets patched instead of refusing the write
+	pub(crate) fn force_write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		let entry = self.matching_entry(address)?;
+		match entry.region() {
+			MappedMemoryRegion::Bootstrap => self
+				.boostrap_ram
+				.patch_byte(entry.adjust_address(address), value)
+				.map_err(|err| entry.bubble_error(err)),
+			_ => self.write_byte(address, value),
 		}
 	}
+
+	pub(crate) fn joypad(&self) -> &Joypad {
+		self.mapped_io_registers.joypad()
+	}
+
+	pub(crate) fn joypad_mut(&mut self) -> &mut Joypad {
+		self.mapped_io_registers.joypad_mut()
+	}
+
+	pub(crate) fn reset_post_boot(&mut self) {
+		self.mapped_io_registers.reset_post_boot();
+	}
+
+	pub(crate) fn tick_cycles(&mut self, cycles: u32) -> Option<Interrupt> {
+		self.mapped_io_registers.tick_cycles(cycles)
+	}
 }
 
 impl RegionToMemoryMapper for MappedMemory {
@@ -88,9 +143,12 @@ impl RegionToMemoryMapper for MappedMemory {
 		Ok(match region {
 			MappedMemoryRegion::Bootstrap => &self.boostrap_ram,
 			MappedMemoryRegion::WorkingRam => &self.working_ram,
+			MappedMemoryRegion::EchoRam => &self.working_ram,
 			MappedMemoryRegion::VideoRam => &self.video_ram,
 			MappedMemoryRegion::IoRegisters => &self.mapped_io_registers,
 			MappedMemoryRegion::Oam => &self.oam,
+			MappedMemoryRegion::HighRam => &self.high_ram,
+			MappedMemoryRegion::InterruptEnable => &self.mapped_io_registers.interrupts.enable,
 		})
 	}
 
@@ -98,9 +156,184 @@ impl RegionToMemoryMapper for MappedMemory {
 		match region {
 			MappedMemoryRegion::Bootstrap => Err(RegionToMemoryMapperError::WriteOnRom),
 			MappedMemoryRegion::WorkingRam => Ok(&mut self.working_ram),
+			MappedMemoryRegion::EchoRam => Ok(&mut self.working_ram),
 			MappedMemoryRegion::VideoRam => Ok(&mut self.video_ram),
 			MappedMemoryRegion::IoRegisters => Ok(&mut self.mapped_io_registers),
 			MappedMemoryRegion::Oam => Ok(&mut self.oam),
+			MappedMemoryRegion::HighRam => Ok(&mut self.high_ram),
+			MappedMemoryRegion::InterruptEnable => Ok(&mut self.mapped_io_registers.interrupts.enable),
+		}
+	}
+}
+
+/// Memory as seen by the CPU
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[allow(clippy::large_enum_variant)] // Boxing the mapped memory would add an indirection to every access
+pub(crate) enum SystemBus {
+	Mapped(MappedMemory),
+	#[cfg(feature = "single-step-tests")]
+	Test(TestBus),
+}
+
+impl Rom for SystemBus {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		match self {
+			Self::Mapped(memory) => memory.read_byte(address),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(memory) => memory.read_byte(address),
+		}
+	}
+
+	fn peek_byte(&self, address: u16) -> Result<u8, RamError> {
+		match self {
+			Self::Mapped(memory) => memory.peek_byte(address),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(memory) => memory.peek_byte(address),
+		}
+	}
+}
+
+impl SystemBus {
+	pub(crate) fn force_write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		match self {
+			Self::Mapped(memory) => memory.force_write_byte(address, value),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(memory) => memory.write_byte(address, value),
+		}
+	}
+
+	// The flat test bus has no joypad
+	pub(crate) fn joypad(&self) -> Option<&Joypad> {
+		match self {
+			Self::Mapped(memory) => Some(memory.joypad()),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(_) => None,
+		}
+	}
+
+	pub(crate) fn joypad_mut(&mut self) -> Option<&mut Joypad> {
+		match self {
+			Self::Mapped(memory) => Some(memory.joypad_mut()),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(_) => None,
+		}
+	}
+
+	// The flat test bus has no IO registers to reset
+	pub(crate) fn reset_post_boot(&mut self) {
+		match self {
+			Self::Mapped(memory) => memory.reset_post_boot(),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(_) => {}
 		}
 	}
+
+	/// Clocks the peripherals, returning the interrupt they requested
+	pub(crate) fn tick_cycles(&mut self, cycles: u32) -> Option<Interrupt> {
+		match self {
+			Self::Mapped(memory) => memory.tick_cycles(cycles),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(_) => None,
+		}
+	}
+}
+
+impl Ram for SystemBus {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		match self {
+			Self::Mapped(memory) => memory.write_byte(address, value),
+			#[cfg(feature = "single-step-tests")]
+			Self::Test(memory) => memory.write_byte(address, value),
+		}
+	}
+}
+
+/// Flat, writable 64KiB with no mapping at all, so CPU test vectors can put data anywhere
+#[cfg(feature = "single-step-tests")]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub(crate) struct TestBus {
+	memory: RamChip<TEST_BUS_SIZE>,
+}
+
+#[cfg(feature = "single-step-tests")]
+impl Rom for TestBus {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		self.memory.read_byte(address)
+	}
+}
+
+#[cfg(feature = "single-step-tests")]
+impl Ram for TestBus {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		self.memory.write_byte(address, value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn echo_ram_mirrors_working_ram() {
+		let mut memory = MappedMemory::new();
+
+		memory.write_byte(0xFDFE, 0x12).expect("Write to echo RAM");
+		memory.write_byte(0xDDFF, 0x34).expect("Write to working RAM");
+
+		assert_eq!(memory.read_byte(0xDDFE).expect("Read working RAM"), 0x12);
+		assert_eq!(memory.read_byte(0xFDFF).expect("Read echo RAM"), 0x34);
+	}
+
+	#[test]
+	fn echo_ram_ends_before_oam() {
+		let mut memory = MappedMemory::new();
+
+		memory.write_byte(0xFE00, 0x56).expect("Write to OAM");
+
+		assert_eq!(memory.read_byte(0xFE00).expect("Read OAM"), 0x56);
+		assert_eq!(memory.read_byte(0xDE00).expect("Read working RAM"), 0x00);
+	}
+
+	#[test]
+	fn double_byte_within_echo_ram() {
+		let mut memory = MappedMemory::new();
+
+		memory.write_double_byte(0xFDFE, 0x1234).expect("Write to echo RAM");
+
+		assert_eq!(memory.read_byte(0xDDFE).expect("Read working RAM"), 0x34);
+		assert_eq!(memory.read_byte(0xDDFF).expect("Read working RAM"), 0x12);
+		assert_eq!(memory.read_double_byte(0xFDFE).expect("Read echo RAM"), 0x1234);
+	}
+
+	#[test]
+	fn double_byte_straddling_echo_ram_and_oam() {
+		let mut memory = MappedMemory::new();
+
+		memory
+			.write_double_byte(0xFDFF, 0x1234)
+			.expect("Write across echo RAM and OAM");
+
+		assert_eq!(memory.read_byte(0xDDFF).expect("Read working RAM"), 0x34);
+		assert_eq!(memory.read_byte(0xFE00).expect("Read OAM"), 0x12);
+		assert_eq!(memory.read_byte(0xDE00).expect("Read working RAM"), 0x00);
+		assert_eq!(
+			memory.read_double_byte(0xFDFF).expect("Read across echo RAM and OAM"),
+			0x1234
+		);
+	}
+
+	#[test]
+	fn high_ram_up_to_interrupt_enable() {
+		let mut memory = MappedMemory::new();
+
+		memory.write_byte(0xFF80, 0x12).expect("Write to HRAM");
+		memory
+			.write_double_byte(0xFFFE, 0xC03E)
+			.expect("Write across HRAM and IE");
+
+		assert_eq!(memory.read_byte(0xFF80).expect("Read HRAM"), 0x12);
+		assert_eq!(memory.read_byte(0xFFFE).expect("Read HRAM"), 0x3E);
+		assert_eq!(memory.read_byte(0xFFFF).expect("Read IE"), 0xC0);
+		assert_eq!(memory.read_byte(0xFF7F).expect("Read IO hole"), 0xFF);
+	}
 }
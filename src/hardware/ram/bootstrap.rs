@@ -1,3 +1,57 @@
 use super::BOOTSTRAP_RAM_SIZE;
 
-pub(super) static BOOTSTRAP_DATA: &[u8; BOOTSTRAP_RAM_SIZE] = include_bytes!("DMG_ROM.bin");
+/// Boot ROM dump given through `CORROSION_BOOT_ROM` when building
+#[cfg(external_boot_rom)]
+pub(crate) static BOOTSTRAP_DATA: &[u8; BOOTSTRAP_RAM_SIZE] = include_bytes!(concat!(env!("OUT_DIR"), "/boot_rom.bin"));
+
+/// Stand-in for the boot ROM, which can't be distributed. Skips the logo and the header checks, leaving the CPU and
+/// the LCD as the DMG boot ROM leaves them when jumping to the cartridge.
+#[cfg(not(external_boot_rom))]
+pub(crate) static BOOTSTRAP_DATA: &[u8; BOOTSTRAP_RAM_SIZE] = &stub::stub();
+
+#[cfg(not(external_boot_rom))]
+mod stub {
+	use super::BOOTSTRAP_RAM_SIZE;
+
+	// Address of the last instruction, same as in the DMG boot ROM so the cartridge is entered at 0x0100
+	const STUB_HANDOVER: usize = 0xFE;
+
+	#[rustfmt::skip]
+	const STUB_ENTRY: [u8; 0x1D] = [
+		0x31, 0xFE, 0xFF, // ld SP, 0xFFFE
+		0x3E, 0x91,       // ld A, 0x91
+		0xE0, 0x40,       // ldh (LCDC), A: LCD and background on, tiles at 0x8000
+		0x3E, 0xFC,       // ld A, 0xFC
+		0xE0, 0x47,       // ldh (BGP), A
+		0x01, 0x13, 0x00, // ld BC, 0x0013
+		0x11, 0xD8, 0x00, // ld DE, 0x00D8
+		0x21, 0x4D, 0x01, // ld HL, 0x014D
+		0x3E, 0xF8,       // ld A, 0xF8
+		0xC6, 0x08,       // add A, 0x08: zero with both carries, F = 0xB0
+		0x3E, 0x01,       // ld A, 0x01
+		0xC3, 0xFE, 0x00, // jp 0x00FE
+	];
+
+	#[rustfmt::skip]
+	const STUB_EXIT: [u8; 2] = [
+		0xE0, 0x50, // ldh (BOOT), A: unmap the boot ROM
+	];
+
+	pub(super) const fn stub() -> [u8; BOOTSTRAP_RAM_SIZE] {
+		let mut data = [0; BOOTSTRAP_RAM_SIZE];
+
+		let mut i = 0;
+		while i < STUB_ENTRY.len() {
+			data[i] = STUB_ENTRY[i];
+			i += 1;
+		}
+
+		let mut i = 0;
+		while i < STUB_EXIT.len() {
+			data[STUB_HANDOVER + i] = STUB_EXIT[i];
+			i += 1;
+		}
+
+		data
+	}
+}
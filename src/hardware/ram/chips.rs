@@ -1,13 +1,36 @@
+use std::borrow::Cow;
+
 use super::{Ram, RamError, Rom};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct RomChip<'a, const S: usize> {
-	ref_memory: &'a [u8; S],
+	// Borrowed until patched, patching copies the contents first
+	ref_memory: Cow<'a, [u8; S]>,
 }
 
 impl<'a, const S: usize> RomChip<'a, S> {
 	pub(super) fn new(ref_memory: &'a [u8; S]) -> Self {
-		Self { ref_memory }
+		Self {
+			ref_memory: Cow::Borrowed(ref_memory),
+		}
+	}
+
+	pub(super) fn owned(memory: [u8; S]) -> Self {
+		Self {
+			ref_memory: Cow::Owned(memory),
+		}
+	}
+
+	/// Overwrite a byte, which the CPU can't do through regular writes
+	pub(super) fn patch_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		let byte = self
+			.ref_memory
+			.to_mut()
+			.get_mut(usize::from(address))
+			.ok_or(RamError::InvalidAddress(address))?;
+
+		*byte = value;
+		Ok(())
 	}
 }
 
@@ -1,10 +1,15 @@
 use crate::hardware::audio::Audio;
 use crate::hardware::counters::divider::DividerRegister;
 use crate::hardware::counters::timer::Timer;
-use crate::hardware::ram::{Ram, RamError, Rom};
+use crate::hardware::counters::Tick;
+use crate::hardware::interrupts::{Interrupt, InterruptController};
+use crate::hardware::joypad::Joypad;
 use crate::hardware::ram::chips::RamChip;
 use crate::hardware::ram::memory_mapping::RegionToMemoryMapperError;
+use crate::hardware::ram::{Ram, RamError, Rom, IO_REGISTERS_MAPPING_SIZE};
+use crate::hardware::screen::lcd_status::{LcdStatus, PpuMode};
 use crate::hardware::screen::position::ScreenCord;
+use crate::hardware::serial::SerialPort;
 
 use super::memory_mapping::{MemoryMapping, MemoryMappingEntry, RegionToMemoryMapper};
 
@@ -14,6 +19,7 @@ pub(super) enum IoRegistersMemoryMappingRegion {
 	SerialTransfer,
 	DividerRegister,
 	Timers,
+	InterruptFlags,
 	Audio,
 	Wave,
 	LcdControl,
@@ -21,9 +27,10 @@ pub(super) enum IoRegistersMemoryMappingRegion {
 	ScreenScroll,
 	ScreenPosition,
 	Bgp,
+	Hole(u8),
 }
 
-const IO_REGISTER_MAPPING_SIZE: usize = 11;
+const IO_REGISTER_MAPPING_SIZE: usize = 12;
 const IO_REGISTER_MAPPING_ENTRIES: [MemoryMappingEntry<IoRegistersMemoryMappingRegion>; IO_REGISTER_MAPPING_SIZE] = [
 	MemoryMappingEntry::new(IoRegistersMemoryMappingRegion::JoypadInput, 0x0, 1),
 	MemoryMappingEntry::new(
@@ -40,6 +47,7 @@ const IO_REGISTER_MAPPING_ENTRIES: [MemoryMappingEntry<IoRegistersMemoryMappingR
 	MemoryMappingEntry::new(IoRegistersMemoryMappingRegion::ScreenScroll, 0x42, 0x2),
 	MemoryMappingEntry::new(IoRegistersMemoryMappingRegion::ScreenPosition, 0x4A, 0x2),
 	MemoryMappingEntry::new(IoRegistersMemoryMappingRegion::Bgp, 0x47, 0x1),
+	MemoryMappingEntry::new(IoRegistersMemoryMappingRegion::InterruptFlags, 0x0F, 0x1),
 ];
 
 const IO_REGISTER_SERIAL_TRANSFER_SIZE: usize = 0x2;
@@ -47,20 +55,159 @@ const IO_REGISTER_TIMERS_SIZE: usize = 0x3;
 const IO_REGISTER_AUDIO_SIZE: usize = 0x17;
 const IO_REGISTER_WAVE_SIZE: usize = 0x10;
 
+/// How an IO address without a register behind it reacts to accesses
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) struct IoHoleBehavior {
+	read_value: u8,
+	// Writable holes keep the last written value, the rest ignore writes
+	writable: bool,
+}
+
+const DMG_UNUSED_IO: IoHoleBehavior = IoHoleBehavior {
+	read_value: 0xFF,
+	writable: false,
+};
+
+// Behavior of every IO address not backed by a register (None), as found on a DMG.
+// Registers that get implemented must be set to None here, which the tests check against the mapping.
+const IO_HOLES: [Option<IoHoleBehavior>; IO_REGISTERS_MAPPING_SIZE] = io_holes();
+
+const fn io_holes() -> [Option<IoHoleBehavior>; IO_REGISTERS_MAPPING_SIZE] {
+	let mut holes = [Some(DMG_UNUSED_IO); IO_REGISTERS_MAPPING_SIZE];
+
+	let registers: [(usize, usize); IO_REGISTER_MAPPING_SIZE] = [
+		(0x00, 0x01), // Joypad
+		(0x01, 0x03), // Serial transfer
+		(0x04, 0x05), // Divider
+		(0x05, 0x08), // Timers
+		(0x0F, 0x10), // Interrupt flags
+		(0x10, 0x27), // Audio
+		(0x30, 0x40), // Wave
+		(0x40, 0x41), // LCD control
+		(0x41, 0x42), // LCD status
+		(0x42, 0x44), // Screen scroll
+		(0x47, 0x48), // BGP
+		(0x4A, 0x4C), // Window position
+	];
+
+	let mut register = 0;
+	while register < registers.len() {
+		let (start, end) = registers[register];
+		let mut address = start;
+		while address < end {
+			holes[address] = None;
+			address += 1;
+		}
+		register += 1;
+	}
+
+	holes
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct IoHole {
+	behavior: IoHoleBehavior,
+	value: u8,
+}
+
+impl IoHole {
+	fn new(behavior: IoHoleBehavior) -> Self {
+		Self {
+			behavior,
+			value: behavior.read_value,
+		}
+	}
+}
+
+impl Rom for IoHole {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		self.value.read_byte(address)
+	}
+}
+
+impl Ram for IoHole {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		if self.behavior.writable {
+			self.value.write_byte(address, value)
+		} else {
+			self.value.read_byte(address).map(|_| ())
+		}
+	}
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct IoHoles {
+	// Entries for addresses with a register are never accessed
+	holes: [IoHole; IO_REGISTERS_MAPPING_SIZE],
+}
+
+impl Default for IoHoles {
+	fn default() -> Self {
+		Self {
+			holes: IO_HOLES.map(|hole| IoHole::new(hole.unwrap_or(DMG_UNUSED_IO))),
+		}
+	}
+}
+
+// Left behind by the DMG boot ROM. Only the upper byte of the divider is visible, at 0xAB.
+const POST_BOOT_DIVIDER: u16 = 0xABCC;
+const POST_BOOT_INTERRUPT_FLAGS: u8 = 0xE1;
+const POST_BOOT_LCD_CONTROL: u8 = 0x91;
+const POST_BOOT_BGP: u8 = 0xFC;
+
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
 pub(super) struct IoRegistersMemoryMapping {
 	mapping: MemoryMapping<IO_REGISTER_MAPPING_SIZE, IoRegistersMemoryMappingRegion>,
-	joypad_input: u8,
-	serial_transfer: RamChip<IO_REGISTER_SERIAL_TRANSFER_SIZE>,
+	joypad: Joypad,
+	serial_transfer: SerialPort,
 	divider_register: DividerRegister,
 	timer: Timer,
+	pub(super) interrupts: InterruptController,
 	audio: Audio,
 	wave: RamChip<IO_REGISTER_WAVE_SIZE>,
 	lcd_control: u8,
-	lcd_status: u8,
+	lcd_status: LcdStatus,
 	screen_scroll: ScreenCord,
 	screen_position: ScreenCord,
 	bgp: u8,
+	holes: IoHoles,
+}
+
+impl IoRegistersMemoryMapping {
+	pub(super) fn joypad(&self) -> &Joypad {
+		&self.joypad
+	}
+
+	pub(super) fn joypad_mut(&mut self) -> &mut Joypad {
+		&mut self.joypad
+	}
+
+	pub(super) fn tick_cycles(&mut self, cycles: u32) -> Option<Interrupt> {
+		self.divider_register.tick_cycles(cycles);
+		self.timer.tick_cycles(cycles)
+	}
+
+	/// Registers as the boot ROM leaves them when it hands over to the cartridge. The joypad keeps its pressed buttons.
+	pub(super) fn reset_post_boot(&mut self) {
+		self.serial_transfer = SerialPort::default();
+		self.divider_register = DividerRegister::new(POST_BOOT_DIVIDER);
+		// TIMA and TMA at 0, TAC at 0xF8 with the timer stopped
+		self.timer = Timer::default();
+		self.interrupts = InterruptController::default();
+		self.interrupts
+			.flags
+			.write_byte(0, POST_BOOT_INTERRUPT_FLAGS)
+			.expect("IF is a single byte");
+		self.lcd_control = POST_BOOT_LCD_CONTROL;
+		// STAT at 0x85: in VBlank, with LY matching LYC
+		self.lcd_status = LcdStatus::default();
+		self.lcd_status.set_mode(PpuMode::VBlank);
+		self.lcd_status.set_lyc_match(true);
+		self.screen_scroll = ScreenCord::default();
+		self.screen_position = ScreenCord::default();
+		self.bgp = POST_BOOT_BGP;
+		self.holes = IoHoles::default();
+	}
 }
 
 impl Default for MemoryMapping<IO_REGISTER_MAPPING_SIZE, IoRegistersMemoryMappingRegion> {
@@ -73,15 +220,26 @@ impl RegionToMemoryMapper for IoRegistersMemoryMapping {
 	type R = IoRegistersMemoryMappingRegion;
 
 	fn matching_entry(&self, address: u16) -> Result<MemoryMappingEntry<Self::R>, RamError> {
-		self.mapping.find_mapping(address).copied()
+		self.mapping
+			.find_mapping(address)
+			.copied()
+			.or_else(|err| match IO_HOLES.get(usize::from(address)) {
+				Some(Some(_)) => Ok(MemoryMappingEntry::new(
+					IoRegistersMemoryMappingRegion::Hole(address as u8),
+					address,
+					1,
+				)),
+				_ => Err(err),
+			})
 	}
 
 	fn get_rom(&self, region: Self::R) -> Result<&dyn Rom, RegionToMemoryMapperError> {
 		match region {
-			IoRegistersMemoryMappingRegion::JoypadInput => Ok(&self.joypad_input),
+			IoRegistersMemoryMappingRegion::JoypadInput => Ok(&self.joypad),
 			IoRegistersMemoryMappingRegion::SerialTransfer => Ok(&self.serial_transfer),
 			IoRegistersMemoryMappingRegion::DividerRegister => Ok(&self.divider_register),
 			IoRegistersMemoryMappingRegion::Timers => Ok(&self.timer),
+			IoRegistersMemoryMappingRegion::InterruptFlags => Ok(&self.interrupts.flags),
 			IoRegistersMemoryMappingRegion::Audio => Ok(&self.audio),
 			IoRegistersMemoryMappingRegion::Wave => Ok(&self.wave),
 			IoRegistersMemoryMappingRegion::LcdControl => Ok(&self.lcd_control),
@@ -89,15 +247,17 @@ impl RegionToMemoryMapper for IoRegistersMemoryMapping {
 			IoRegistersMemoryMappingRegion::ScreenPosition => Ok(&self.screen_position),
 			IoRegistersMemoryMappingRegion::ScreenScroll => Ok(&self.screen_scroll),
 			IoRegistersMemoryMappingRegion::Bgp => Ok(&self.bgp),
+			IoRegistersMemoryMappingRegion::Hole(index) => Ok(&self.holes.holes[usize::from(index)]),
 		}
 	}
 
 	fn get_ram(&mut self, region: Self::R) -> Result<&mut dyn Ram, RegionToMemoryMapperError> {
 		match region {
-			IoRegistersMemoryMappingRegion::JoypadInput => Ok(&mut self.joypad_input),
+			IoRegistersMemoryMappingRegion::JoypadInput => Ok(&mut self.joypad),
 			IoRegistersMemoryMappingRegion::SerialTransfer => Ok(&mut self.serial_transfer),
-			IoRegistersMemoryMappingRegion::DividerRegister => Ok(&mut self.serial_transfer),
+			IoRegistersMemoryMappingRegion::DividerRegister => Ok(&mut self.divider_register),
 			IoRegistersMemoryMappingRegion::Timers => Ok(&mut self.timer),
+			IoRegistersMemoryMappingRegion::InterruptFlags => Ok(&mut self.interrupts.flags),
 			IoRegistersMemoryMappingRegion::Audio => Ok(&mut self.audio),
 			IoRegistersMemoryMappingRegion::Wave => Ok(&mut self.wave),
 			IoRegistersMemoryMappingRegion::LcdControl => Ok(&mut self.lcd_control),
@@ -105,6 +265,7 @@ impl RegionToMemoryMapper for IoRegistersMemoryMapping {
 			IoRegistersMemoryMappingRegion::ScreenPosition => Ok(&mut self.screen_position),
 			IoRegistersMemoryMappingRegion::ScreenScroll => Ok(&mut self.screen_scroll),
 			IoRegistersMemoryMappingRegion::Bgp => Ok(&mut self.bgp),
+			IoRegistersMemoryMappingRegion::Hole(index) => Ok(&mut self.holes.holes[usize::from(index)]),
 		}
 	}
 }
@@ -128,4 +289,51 @@ mod tests {
 		let mut memory_mapping = IoRegistersMemoryMapping::default();
 		memory_mapping.write_byte(0x26, 0x0).expect("Write to Audio")
 	}
+
+	#[test]
+	fn write_resets_divider() {
+		let mut memory_mapping = IoRegistersMemoryMapping {
+			divider_register: DividerRegister::new(0x1234),
+			..Default::default()
+		};
+		memory_mapping.write_byte(0x01, 0x56).expect("Write to SB");
+		assert_eq!(memory_mapping.read_byte(0x04).expect("Read DIV"), 0x12);
+
+		memory_mapping.write_byte(0x04, 0xFF).expect("Write to DIV");
+		assert_eq!(memory_mapping.read_byte(0x04).expect("Read DIV"), 0x00);
+		assert_eq!(memory_mapping.read_byte(0x01).expect("Read SB"), 0x56);
+	}
+
+	#[test]
+	fn io_holes_match_mapping() {
+		let memory_mapping = IoRegistersMemoryMapping::default();
+
+		for address in 0..IO_REGISTERS_MAPPING_SIZE as u16 {
+			let register = memory_mapping.mapping.find_mapping(address);
+
+			match IO_HOLES[usize::from(address)] {
+				Some(hole) => {
+					assert!(register.is_err(), "Hole at {address:#04X} shadows a register");
+					let actual = memory_mapping.read_byte(address).expect("Read from hole");
+					assert_eq!(actual, hole.read_value, "Read from hole at {address:#04X}");
+				}
+				None => assert!(register.is_ok(), "No register nor hole at {address:#04X}"),
+			}
+		}
+	}
+
+	#[test]
+	fn write_to_hole() {
+		let mut memory_mapping = IoRegistersMemoryMapping::default();
+		memory_mapping.write_byte(0x7F, 0x12).expect("Write to hole");
+		assert_eq!(memory_mapping.read_byte(0x7F).expect("Read from hole"), 0xFF);
+
+		let mut writable = IoHole::new(IoHoleBehavior {
+			read_value: 0x00,
+			writable: true,
+		});
+		assert_eq!(writable.read_byte(0).expect("Read from hole"), 0x00);
+		writable.write_byte(0, 0x12).expect("Write to hole");
+		assert_eq!(writable.read_byte(0).expect("Read from hole"), 0x12);
+	}
 }
@@ -20,15 +20,19 @@ impl<R: MemoryMappingEntryRegion> MemoryMappingEntry<R> {
 		Self { region, offset, size }
 	}
 
+	pub(super) fn region(&self) -> R {
+		self.region
+	}
+
 	fn mapped_here(&self, address: u16) -> bool {
 		(address >= self.offset) && (usize::from(address - self.offset) < self.size)
 	}
 
-	fn adjust_address(&self, address: u16) -> u16 {
+	pub(super) fn adjust_address(&self, address: u16) -> u16 {
 		address - self.offset
 	}
 
-	fn bubble_error(&self, err: RamError) -> RamError {
+	pub(super) fn bubble_error(&self, err: RamError) -> RamError {
 		err.adjust_for_offset(self.offset)
 	}
 }
@@ -72,6 +76,7 @@ pub(super) trait RegionToMemoryMapper {
 	fn get_ram(&mut self, region: Self::R) -> Result<&mut dyn Ram, RegionToMemoryMapperError>;
 }
 
+// Double byte accesses fall back to two single byte accesses, each one mapped on its own, so they can straddle regions
 impl<M: RegionToMemoryMapper> Rom for M {
 	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
 		let entry = self.matching_entry(address)?;
@@ -81,11 +86,11 @@ impl<M: RegionToMemoryMapper> Rom for M {
 			.map_err(|err| entry.bubble_error(err))
 	}
 
-	fn read_double_byte(&self, address: u16) -> Result<u16, RamError> {
+	fn peek_byte(&self, address: u16) -> Result<u8, RamError> {
 		let entry = self.matching_entry(address)?;
 		self.get_rom(entry.region)
 			.map_err(|e| e.as_ram_error(address))
-			.and_then(|rom| rom.read_double_byte(entry.adjust_address(address)))
+			.and_then(|rom| rom.peek_byte(entry.adjust_address(address)))
 			.map_err(|err| entry.bubble_error(err))
 	}
 }
@@ -98,12 +103,4 @@ impl<M: RegionToMemoryMapper> Ram for M {
 			.and_then(|ram| ram.write_byte(entry.adjust_address(address), value))
 			.map_err(|err| entry.bubble_error(err))
 	}
-
-	fn write_double_byte(&mut self, address: u16, value: u16) -> Result<(), RamError> {
-		let entry = self.matching_entry(address)?;
-		self.get_ram(entry.region)
-			.map_err(|e| e.as_ram_error(address))
-			.and_then(|ram| ram.write_double_byte(entry.adjust_address(address), value))
-			.map_err(|err| entry.bubble_error(err))
-	}
 }
@@ -0,0 +1,64 @@
+use crate::hardware::ram::IO_REGISTERS_MAPPING_START;
+
+const HIGH_RAM: std::ops::RangeInclusive<u16> = 0xFF80..=0xFFFE;
+const INTERRUPT_ENABLE: u16 = 0xFFFF;
+
+// Pan Docs names of the IO registers, by offset from the start of the IO region
+const IO_REGISTER_NAMES: [(u8, &str); 25] = [
+	(0x00, "JOYP"),
+	(0x01, "SB"),
+	(0x02, "SC"),
+	(0x04, "DIV"),
+	(0x05, "TIMA"),
+	(0x06, "TMA"),
+	(0x07, "TAC"),
+	(0x0F, "IF"),
+	(0x10, "NR10"),
+	(0x24, "NR50"),
+	(0x25, "NR51"),
+	(0x26, "NR52"),
+	(0x40, "LCDC"),
+	(0x41, "STAT"),
+	(0x42, "SCY"),
+	(0x43, "SCX"),
+	(0x44, "LY"),
+	(0x45, "LYC"),
+	(0x46, "DMA"),
+	(0x47, "BGP"),
+	(0x48, "OBP0"),
+	(0x49, "OBP1"),
+	(0x4A, "WY"),
+	(0x4B, "WX"),
+	(0x50, "BOOT"),
+];
+
+/// Symbolic name of an address in the IO registers or high RAM, for disassembly
+pub(crate) fn address_name(address: u16) -> Option<&'static str> {
+	if HIGH_RAM.contains(&address) {
+		return Some("HRAM");
+	}
+	if address == INTERRUPT_ENABLE {
+		return Some("IE");
+	}
+
+	let offset = address.checked_sub(IO_REGISTERS_MAPPING_START)?;
+	IO_REGISTER_NAMES
+		.iter()
+		.find(|(register, _)| u16::from(*register) == offset)
+		.map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn names() {
+		assert_eq!(address_name(0xFF40), Some("LCDC"));
+		assert_eq!(address_name(0xFF80), Some("HRAM"));
+		assert_eq!(address_name(0xFFFE), Some("HRAM"));
+		assert_eq!(address_name(0xFFFF), Some("IE"));
+		assert_eq!(address_name(0xFF03), None);
+		assert_eq!(address_name(0xC000), None);
+	}
+}
@@ -3,6 +3,13 @@ use super::RamError;
 pub(crate) trait Rom {
 	fn read_byte(&self, address: u16) -> Result<u8, RamError>;
 
+	/// Reads the same value as `read_byte`, but never has the side effects a read has on hardware, so debuggers and
+	/// viewers can look at memory without changing what the game sees. Only registers whose reads do something need
+	/// to override it.
+	fn peek_byte(&self, address: u16) -> Result<u8, RamError> {
+		self.read_byte(address)
+	}
+
 	fn read_double_byte(&self, address: u16) -> Result<u16, RamError> {
 		let low = self.read_byte(address)?;
 		let high = self.read_byte(address.wrapping_add(1))?;
@@ -5,8 +5,9 @@ use num_enum::IntoPrimitive;
 #[cfg(test)]
 mod tests;
 
-pub const SINGLE_REGISTER_BANK_SIZE: usize = 8;
-pub const DOUBLE_REGISTER_BANK_SIZE: usize = SINGLE_REGISTER_BANK_SIZE / 2;
+pub(crate) const SINGLE_REGISTER_BANK_SIZE: usize = 8;
+#[cfg(test)]
+pub(crate) const DOUBLE_REGISTER_BANK_SIZE: usize = SINGLE_REGISTER_BANK_SIZE / 2;
 
 #[derive(IntoPrimitive, Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
@@ -71,6 +72,8 @@ impl Display for DoubleRegisters {
 }
 
 const FLAG_REGISTER: usize = 5;
+// The lower nibble of F doesn't exist in hardware, it always reads as zero
+const FLAG_REGISTER_MASK: u8 = 0xF0;
 
 #[derive(IntoPrimitive, Copy, Clone, PartialEq, Debug)]
 #[repr(u8)]
@@ -93,30 +96,34 @@ impl BitFlags {
 }
 
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
-pub struct RegisterBank {
+pub(crate) struct RegisterBank {
 	register_bank: [u8; SINGLE_REGISTER_BANK_SIZE],
 }
 
 impl RegisterBank {
-	pub fn new() -> Self {
+	pub(crate) fn new() -> Self {
 		Self {
 			register_bank: [0u8; SINGLE_REGISTER_BANK_SIZE],
 		}
 	}
 
-	pub fn read_single(&self, address: usize) -> Result<u8, RegisterBankError> {
+	pub(crate) fn read_single(&self, address: usize) -> Result<u8, RegisterBankError> {
 		self.register_bank
 			.get(address)
 			.copied()
 			.ok_or(RegisterBankError::AddressOutOfRange { address })
 	}
 
-	pub fn write_single(&mut self, address: usize, value: u8) -> Result<(), RegisterBankError> {
+	pub(crate) fn write_single(&mut self, address: usize, value: u8) -> Result<(), RegisterBankError> {
 		let register = self
 			.register_bank
 			.get_mut(address)
 			.ok_or(RegisterBankError::AddressOutOfRange { address })?;
-		*register = value;
+		*register = if address == FLAG_REGISTER {
+			value & FLAG_REGISTER_MASK
+		} else {
+			value
+		};
 		Ok(())
 	}
 
@@ -130,7 +137,7 @@ impl RegisterBank {
 		}
 	}
 
-	pub fn read_double(&self, address: usize) -> Result<u16, RegisterBankError> {
+	pub(crate) fn read_double(&self, address: usize) -> Result<u16, RegisterBankError> {
 		let (high_address, low_address) =
 			Self::get_double_address(address).ok_or(RegisterBankError::InvalidDoubleRegister { address })?;
 		let high = self.read_single(high_address)?;
@@ -138,27 +145,18 @@ impl RegisterBank {
 		Ok(u16::from_be_bytes([high, low]))
 	}
 
-	pub fn write_double(&mut self, address: usize, value: u16) -> Result<(), RegisterBankError> {
+	pub(crate) fn write_double(&mut self, address: usize, value: u16) -> Result<(), RegisterBankError> {
 		let (high_address, low_address) =
 			Self::get_double_address(address).ok_or(RegisterBankError::InvalidDoubleRegister { address })?;
 		let [high, low] = value.to_be_bytes();
 
-		let high_register = self
-			.register_bank
-			.get_mut(high_address)
-			.ok_or(RegisterBankError::AddressOutOfRange { address: high_address })?;
-		*high_register = high;
-
-		let low_register = self
-			.register_bank
-			.get_mut(low_address)
-			.ok_or(RegisterBankError::AddressOutOfRange { address: low_address })?;
-		*low_register = low;
+		self.write_single(high_address, high)?;
+		self.write_single(low_address, low)?;
 
 		Ok(())
 	}
 
-	pub fn read_bit_flag(&self, flag: BitFlags) -> bool {
+	pub(crate) fn read_bit_flag(&self, flag: BitFlags) -> bool {
 		let flag: u8 = flag.into();
 		let bitmask: u8 = 1u8 << flag;
 
@@ -167,7 +165,7 @@ impl RegisterBank {
 		flag_register & bitmask != 0
 	}
 
-	pub fn write_bit_flag(&mut self, flag: BitFlags, bit: bool) {
+	pub(crate) fn write_bit_flag(&mut self, flag: BitFlags, bit: bool) {
 		let flag: u8 = flag.into();
 		let bitmask: u8 = 1u8 << flag;
 		let shifted_bit: u8 = if bit { bitmask } else { 0 };
@@ -178,29 +176,29 @@ impl RegisterBank {
 		self.write_single(FLAG_REGISTER, new_flag_register).unwrap();
 	}
 
-	pub fn read_single_named(&self, single_register: SingleRegisters) -> u8 {
+	pub(crate) fn read_single_named(&self, single_register: SingleRegisters) -> u8 {
 		let address: u8 = single_register.into();
 		self.read_single(address as usize).unwrap()
 	}
 
-	pub fn write_single_named(&mut self, single_register: SingleRegisters, value: u8) {
+	pub(crate) fn write_single_named(&mut self, single_register: SingleRegisters, value: u8) {
 		let address: u8 = single_register.into();
 		self.write_single(address as usize, value).unwrap();
 	}
 
-	pub fn read_double_named(&self, double_register: DoubleRegisters) -> u16 {
+	pub(crate) fn read_double_named(&self, double_register: DoubleRegisters) -> u16 {
 		let address: u8 = double_register.into();
 		self.read_double(address as usize).unwrap()
 	}
 
-	pub fn write_double_named(&mut self, double_register: DoubleRegisters, value: u16) {
+	pub(crate) fn write_double_named(&mut self, double_register: DoubleRegisters, value: u16) {
 		let address: u8 = double_register.into();
 		self.write_double(address as usize, value).unwrap();
 	}
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub enum RegisterBankError {
+pub(crate) enum RegisterBankError {
 	AddressOutOfRange { address: usize },
 	InvalidDoubleRegister { address: usize },
 }
@@ -1,6 +1,6 @@
 use crate::hardware::register_bank::{
 	BitFlags, DoubleRegisters, RegisterBank, RegisterBankError, SingleRegisters, DOUBLE_REGISTER_BANK_SIZE,
-	SINGLE_REGISTER_BANK_SIZE,
+	FLAG_REGISTER, FLAG_REGISTER_MASK, SINGLE_REGISTER_BANK_SIZE,
 };
 
 #[test]
@@ -13,7 +13,10 @@ fn single_register() {
 	}
 
 	for register in 0..SINGLE_REGISTER_BANK_SIZE {
-		let expected_register_value = 0x12u8 + register as u8;
+		let mut expected_register_value = 0x12u8 + register as u8;
+		if register == FLAG_REGISTER {
+			expected_register_value &= FLAG_REGISTER_MASK;
+		}
 		assert_eq!(register_bank.read_single(register), Ok(expected_register_value));
 	}
 
@@ -42,7 +45,10 @@ fn double_register() {
 	}
 
 	for register in 0..DOUBLE_REGISTER_BANK_SIZE {
-		let expected_register_value = 0xab12u16 + register as u16;
+		let mut expected_register_value = 0xab12u16 + register as u16;
+		if register == usize::from(u8::from(DoubleRegisters::AF)) {
+			expected_register_value &= 0xFF00 | u16::from(FLAG_REGISTER_MASK);
+		}
 		assert_eq!(register_bank.read_double(register), Ok(expected_register_value));
 	}
 
@@ -86,10 +92,101 @@ fn named_register() {
 	assert_eq!(register_bank.read_single_named(SingleRegisters::A), 0x12);
 
 	register_bank.write_single_named(SingleRegisters::F, 0x34);
-	assert_eq!(register_bank.read_single_named(SingleRegisters::F), 0x34);
+	assert_eq!(register_bank.read_single_named(SingleRegisters::F), 0x30);
 
-	assert_eq!(register_bank.read_double_named(DoubleRegisters::AF), 0x1234);
+	assert_eq!(register_bank.read_double_named(DoubleRegisters::AF), 0x1230);
 
 	register_bank.write_double_named(DoubleRegisters::BC, 0x5678);
 	assert_eq!(register_bank.read_double_named(DoubleRegisters::BC), 0x5678);
 }
+
+// Pairs and their halves must alias each other whatever the internal layout of the bank
+mod aliasing {
+	use super::*;
+
+	const PAIRS: [(DoubleRegisters, SingleRegisters, SingleRegisters); 4] = [
+		(DoubleRegisters::AF, SingleRegisters::A, SingleRegisters::F),
+		(DoubleRegisters::BC, SingleRegisters::B, SingleRegisters::C),
+		(DoubleRegisters::DE, SingleRegisters::D, SingleRegisters::E),
+		(DoubleRegisters::HL, SingleRegisters::H, SingleRegisters::L),
+	];
+
+	fn low_mask(low: SingleRegisters) -> u8 {
+		match low {
+			SingleRegisters::F => FLAG_REGISTER_MASK,
+			_ => 0xFF,
+		}
+	}
+
+	#[test]
+	fn pair_write_then_single_read() {
+		for (pair, high, low) in PAIRS {
+			let mut register_bank = RegisterBank::new();
+
+			register_bank.write_double_named(pair, 0xABCD);
+
+			assert_eq!(
+				register_bank.read_single_named(high),
+				0xAB,
+				"{high} after writing {pair}"
+			);
+			assert_eq!(
+				register_bank.read_single_named(low),
+				0xCD & low_mask(low),
+				"{low} after writing {pair}"
+			);
+		}
+	}
+
+	#[test]
+	fn single_writes_then_pair_read() {
+		for (pair, high, low) in PAIRS {
+			let mut register_bank = RegisterBank::new();
+
+			register_bank.write_single_named(high, 0x12);
+			register_bank.write_single_named(low, 0x3F);
+
+			let expected = u16::from_be_bytes([0x12, 0x3F & low_mask(low)]);
+			assert_eq!(register_bank.read_double_named(pair), expected, "{pair}");
+		}
+	}
+
+	#[test]
+	fn pairs_do_not_overlap() {
+		let mut register_bank = RegisterBank::new();
+
+		for (index, (pair, _, _)) in PAIRS.into_iter().enumerate() {
+			register_bank.write_double_named(pair, 0x1110 * (index as u16 + 1));
+		}
+
+		for (index, (pair, _, _)) in PAIRS.into_iter().enumerate() {
+			assert_eq!(
+				register_bank.read_double_named(pair),
+				0x1110 * (index as u16 + 1),
+				"{pair}"
+			);
+		}
+	}
+
+	#[test]
+	fn af_masks_low_nibble() {
+		let mut register_bank = RegisterBank::new();
+
+		register_bank.write_double_named(DoubleRegisters::AF, 0xABCD);
+		assert_eq!(register_bank.read_double_named(DoubleRegisters::AF), 0xABC0);
+	}
+
+	#[test]
+	fn bit_flags_visible_through_af() {
+		let mut register_bank = RegisterBank::new();
+		register_bank.write_single_named(SingleRegisters::A, 0x12);
+
+		register_bank.write_bit_flag(BitFlags::Zero, true);
+		register_bank.write_bit_flag(BitFlags::Carry, true);
+		assert_eq!(register_bank.read_double_named(DoubleRegisters::AF), 0x1290);
+
+		register_bank.write_bit_flag(BitFlags::Zero, false);
+		register_bank.write_bit_flag(BitFlags::HalfCarry, true);
+		assert_eq!(register_bank.read_double_named(DoubleRegisters::AF), 0x1230);
+	}
+}
@@ -1 +1,3 @@
+pub(crate) mod lcd_status;
 pub(crate) mod position;
+pub(crate) mod tile_map;
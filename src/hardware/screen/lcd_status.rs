@@ -0,0 +1,96 @@
+use num_enum::IntoPrimitive;
+
+use crate::hardware::ram::{Ram, RamError, Rom};
+
+const INTERRUPT_ENABLES_MASK: u8 = 0b0111_1000;
+const LYC_MATCH_BIT: u8 = 2;
+const UNUSED_BITS: u8 = 0b1000_0000; // Always read as set on a DMG
+
+#[allow(unused)] // Set by the PPU, which doesn't exist yet
+#[derive(Debug, Copy, Clone, Eq, PartialEq, IntoPrimitive, Default)]
+#[repr(u8)]
+pub(crate) enum PpuMode {
+	#[default]
+	HBlank = 0,
+	VBlank = 1,
+	OamScan = 2,
+	Drawing = 3,
+}
+
+// STAT: the game only writes the interrupt enables (bits 3-6), the mode and LYC match bits (0-2) belong to the PPU and
+// are kept apart so a game write can't clobber them
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub(crate) struct LcdStatus {
+	interrupt_enables: u8,
+	lyc_match: bool,
+	mode: PpuMode,
+}
+
+#[allow(unused)]
+impl LcdStatus {
+	pub(crate) fn set_mode(&mut self, mode: PpuMode) {
+		self.mode = mode;
+	}
+
+	pub(crate) fn set_lyc_match(&mut self, lyc_match: bool) {
+		self.lyc_match = lyc_match;
+	}
+
+	pub(crate) fn interrupt_enables(&self) -> u8 {
+		self.interrupt_enables
+	}
+}
+
+impl Rom for LcdStatus {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		match address {
+			0 => Ok(UNUSED_BITS
+				| self.interrupt_enables
+				| u8::from(self.lyc_match) << LYC_MATCH_BIT
+				| u8::from(self.mode)),
+			_ => Err(RamError::InvalidAddress(address)),
+		}
+	}
+}
+
+impl Ram for LcdStatus {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		match address {
+			0 => {
+				self.interrupt_enables = value & INTERRUPT_ENABLES_MASK;
+				Ok(())
+			}
+			_ => Err(RamError::InvalidAddress(address)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn game_write_keeps_ppu_bits() {
+		let mut lcd_status = LcdStatus::default();
+
+		lcd_status.set_mode(PpuMode::Drawing);
+		lcd_status.set_lyc_match(true);
+		lcd_status.write_byte(0, 0xFF).expect("Write to STAT");
+		assert_eq!(lcd_status.read_byte(0).expect("Read STAT"), 0xFF);
+
+		lcd_status.write_byte(0, 0x00).expect("Write to STAT");
+		assert_eq!(lcd_status.read_byte(0).expect("Read STAT"), 0b1000_0111);
+	}
+
+	#[test]
+	fn ppu_update_keeps_interrupt_enables() {
+		let mut lcd_status = LcdStatus::default();
+
+		lcd_status.write_byte(0, 0b0101_0011).expect("Write to STAT");
+		assert_eq!(lcd_status.read_byte(0).expect("Read STAT"), 0b1101_0000);
+
+		lcd_status.set_mode(PpuMode::OamScan);
+		assert_eq!(lcd_status.read_byte(0).expect("Read STAT"), 0b1101_0010);
+		assert_eq!(lcd_status.interrupt_enables(), 0b0101_0000);
+	}
+}
@@ -1,5 +1,8 @@
 use crate::hardware::ram::{Ram, RamError, Rom};
 
+/// SCY/SCX or WY/WX. Any value is stored as written, the renderer gives them meaning: the scroll wraps around the
+/// 256x256 background, while WX below 7 clips the window at the left edge, and WX above 166 or WY past the last line
+/// keep the window off the screen.
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
 pub(crate) struct ScreenCord {
 	y: u8, // Y appears before X in memory mappings
@@ -10,7 +13,7 @@ impl Rom for ScreenCord {
 	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
 		match address {
 			0 => Ok(self.y),
-			1 => Ok(self.y),
+			1 => Ok(self.x),
 			_ => Err(RamError::InvalidAddress(address)),
 		}
 	}
@@ -31,3 +34,30 @@ impl Ram for ScreenCord {
 			.ok_or(RamError::InvalidAddress(address))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn x_and_y_are_separate() {
+		let mut position = ScreenCord::default();
+		position.write_byte(0, 0x12).unwrap();
+		position.write_byte(1, 0x34).unwrap();
+
+		assert_eq!(position.read_byte(0).unwrap(), 0x12);
+		assert_eq!(position.read_byte(1).unwrap(), 0x34);
+	}
+
+	#[test]
+	fn no_clamping() {
+		let mut position = ScreenCord::default();
+		for value in [0, 6, 7, 143, 144, 166, 167, 0xFF] {
+			position.write_byte(0, value).unwrap();
+			position.write_byte(1, value).unwrap();
+
+			assert_eq!(position.read_byte(0).unwrap(), value);
+			assert_eq!(position.read_byte(1).unwrap(), value);
+		}
+	}
+}
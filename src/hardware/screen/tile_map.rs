@@ -0,0 +1,218 @@
+use crate::hardware::cpu::Cpu;
+use crate::hardware::ram::{Rom, VIDEO_RAM_START};
+use crate::instructions::ExecutionError;
+
+const LCD_CONTROL_ADDRESS: u16 = 0xFF40;
+const SCROLL_Y_ADDRESS: u16 = 0xFF42;
+const SCROLL_X_ADDRESS: u16 = 0xFF43;
+const BGP_ADDRESS: u16 = 0xFF47;
+
+// LCDC bit 4: tile data at 0x8000 with unsigned indices, or around 0x9000 with signed ones
+const TILE_DATA_UNSIGNED_BIT: u8 = 1 << 4;
+const SIGNED_TILE_DATA_BASE: u16 = 0x9000;
+
+/// Width and height of the background, in pixels
+pub const TILE_MAP_PIXELS: usize = 256;
+const TILE_MAP_TILES: u16 = 32;
+const TILE_BYTES: u16 = 16;
+
+// Size of the visible screen
+const SCREEN_WIDTH: u8 = 160;
+const SCREEN_HEIGHT: u8 = 144;
+
+/// Which of the two background tile maps to read
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TileMapAddressMode {
+	/// 0x9800-0x9BFF
+	Low,
+	/// 0x9C00-0x9FFF
+	High,
+}
+
+impl TileMapAddressMode {
+	fn start(self) -> u16 {
+		match self {
+			Self::Low => 0x9800,
+			Self::High => 0x9C00,
+		}
+	}
+}
+
+/// Pixels as shades 0 (lightest) to 3 (darkest), row by row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Framebuffer {
+	width: usize,
+	height: usize,
+	pixels: Vec<u8>,
+}
+
+impl Framebuffer {
+	fn new(width: usize, height: usize) -> Self {
+		Self {
+			width,
+			height,
+			pixels: vec![0; width * height],
+		}
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn pixel(&self, x: usize, y: usize) -> u8 {
+		self.pixels[y * self.width + x]
+	}
+
+	pub fn pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+}
+
+/// Address of the first byte of a tile, using the tile data addressing selected in LCDC
+pub(crate) fn tile_data_address(lcd_control: u8, tile_index: u8) -> u16 {
+	if lcd_control & TILE_DATA_UNSIGNED_BIT != 0 {
+		VIDEO_RAM_START + u16::from(tile_index) * TILE_BYTES
+	} else {
+		SIGNED_TILE_DATA_BASE.wrapping_add_signed(i16::from(tile_index as i8) * TILE_BYTES as i16)
+	}
+}
+
+/// The whole 256x256 background of a tile map, as the current LCDC and BGP would draw it
+pub(crate) fn render_tile_map(cpu: &Cpu, which: TileMapAddressMode) -> Result<Framebuffer, ExecutionError> {
+	let lcd_control = cpu.mapped_ram.peek_byte(LCD_CONTROL_ADDRESS)?;
+	let palette = cpu.mapped_ram.peek_byte(BGP_ADDRESS)?;
+	let mut framebuffer = Framebuffer::new(TILE_MAP_PIXELS, TILE_MAP_PIXELS);
+
+	for tile in 0..TILE_MAP_TILES * TILE_MAP_TILES {
+		let tile_index = cpu.mapped_ram.peek_byte(which.start() + tile)?;
+		let data = tile_data_address(lcd_control, tile_index);
+		let left = usize::from(tile % TILE_MAP_TILES) * 8;
+		let top = usize::from(tile / TILE_MAP_TILES) * 8;
+
+		for row in 0..8 {
+			// Two bitplanes per row, the low bit of each color first
+			let low = cpu.mapped_ram.peek_byte(data + 2 * row)?;
+			let high = cpu.mapped_ram.peek_byte(data + 2 * row + 1)?;
+			for column in 0..8u8 {
+				let bit = 7 - column;
+				let color = (low >> bit) & 1 | ((high >> bit) & 1) << 1;
+				let shade = (palette >> (2 * color)) & 0b11;
+				framebuffer.pixels[(top + usize::from(row)) * TILE_MAP_PIXELS + left + usize::from(column)] = shade;
+			}
+		}
+	}
+
+	Ok(framebuffer)
+}
+
+/// Screen position on the background. The 160x144 rectangle wraps around the edges, so the right and bottom edges
+/// can end up left of and above the others.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Viewport {
+	/// SCX
+	pub left: u8,
+	/// SCY
+	pub top: u8,
+	pub right: u8,
+	pub bottom: u8,
+}
+
+pub(crate) fn viewport_rect(cpu: &Cpu) -> Result<Viewport, ExecutionError> {
+	let left = cpu.mapped_ram.peek_byte(SCROLL_X_ADDRESS)?;
+	let top = cpu.mapped_ram.peek_byte(SCROLL_Y_ADDRESS)?;
+	Ok(Viewport {
+		left,
+		top,
+		right: left.wrapping_add(SCREEN_WIDTH - 1),
+		bottom: top.wrapping_add(SCREEN_HEIGHT - 1),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Every row colored 0, 1, 2, 3, 0, 1, 2, 3, as low and high bitplanes
+	const TILE_ROW: [u8; 2] = [0b0101_0101, 0b0011_0011];
+
+	fn write_tile(cpu: &mut Cpu, address: u16) {
+		for row in 0..8 {
+			cpu.write_byte(address + 2 * row, TILE_ROW[0]).unwrap();
+			cpu.write_byte(address + 2 * row + 1, TILE_ROW[1]).unwrap();
+		}
+	}
+
+	fn assert_tile_at(framebuffer: &Framebuffer, left: usize, top: usize) {
+		for y in top..top + 8 {
+			let row: Vec<_> = (left..left + 8).map(|x| framebuffer.pixel(x, y)).collect();
+			assert_eq!(row, [0, 1, 2, 3, 0, 1, 2, 3]);
+		}
+		assert_eq!(framebuffer.pixel(left + 8, top), 0);
+	}
+
+	#[test]
+	fn unsigned_tile_data() {
+		let mut cpu = Cpu::new();
+		cpu.write_byte(LCD_CONTROL_ADDRESS, 0x91).unwrap();
+		cpu.write_byte(BGP_ADDRESS, 0xE4).unwrap();
+		write_tile(&mut cpu, VIDEO_RAM_START + TILE_BYTES);
+		// Tile 1 at column 3 of row 2
+		cpu.write_byte(0x9800 + 2 * 32 + 3, 1).unwrap();
+
+		let framebuffer = render_tile_map(&cpu, TileMapAddressMode::Low).unwrap();
+		assert_eq!(framebuffer.width(), 256);
+		assert_eq!(framebuffer.height(), 256);
+		assert_tile_at(&framebuffer, 24, 16);
+		assert_eq!(framebuffer.pixel(24 + 1, 16 + 256 / 2), 0);
+	}
+
+	#[test]
+	fn signed_tile_data_and_high_map() {
+		let mut cpu = Cpu::new();
+		cpu.write_byte(LCD_CONTROL_ADDRESS, 0x81).unwrap();
+		cpu.write_byte(BGP_ADDRESS, 0xE4).unwrap();
+		write_tile(&mut cpu, 0x8800);
+		// Tile -128 in the bottom right corner
+		cpu.write_byte(0x9C00 + 31 * 32 + 31, 0x80).unwrap();
+
+		let framebuffer = render_tile_map(&cpu, TileMapAddressMode::High).unwrap();
+		let row: Vec<_> = (248..256).map(|x| framebuffer.pixel(x, 255)).collect();
+		assert_eq!(row, [0, 1, 2, 3, 0, 1, 2, 3]);
+		let low_map = render_tile_map(&cpu, TileMapAddressMode::Low).unwrap();
+		assert!(low_map.pixels().iter().all(|&shade| shade == 0));
+	}
+
+	#[test]
+	fn palette_applied() {
+		let mut cpu = Cpu::new();
+		cpu.write_byte(LCD_CONTROL_ADDRESS, 0x91).unwrap();
+		// Inverted palette
+		cpu.write_byte(BGP_ADDRESS, 0x1B).unwrap();
+		write_tile(&mut cpu, VIDEO_RAM_START);
+
+		let framebuffer = render_tile_map(&cpu, TileMapAddressMode::Low).unwrap();
+		let row: Vec<_> = (0..4).map(|x| framebuffer.pixel(x, 0)).collect();
+		assert_eq!(row, [3, 2, 1, 0]);
+	}
+
+	#[test]
+	fn viewport() {
+		let mut cpu = Cpu::new();
+		cpu.write_byte(SCROLL_Y_ADDRESS, 0x12).unwrap();
+		cpu.write_byte(SCROLL_X_ADDRESS, 0x34).unwrap();
+		let viewport = viewport_rect(&cpu).unwrap();
+		assert_eq!((viewport.left, viewport.top), (0x34, 0x12));
+		assert_eq!((viewport.right, viewport.bottom), (0xD3, 0xA1));
+
+		// Past the bottom right corner, the rectangle wraps to the other side
+		cpu.write_byte(SCROLL_Y_ADDRESS, 200).unwrap();
+		cpu.write_byte(SCROLL_X_ADDRESS, 250).unwrap();
+		let viewport = viewport_rect(&cpu).unwrap();
+		assert_eq!((viewport.left, viewport.top), (250, 200));
+		assert_eq!((viewport.right, viewport.bottom), (153, 87));
+	}
+}
@@ -0,0 +1,80 @@
+use std::cell::Cell;
+
+use crate::hardware::ram::{Ram, RamError, Rom};
+
+// SC bit 7, set by the game to start a transfer and cleared when it's done
+const TRANSFER_ACTIVE: u8 = 0b1000_0000;
+
+/// SB and SC. The transfer itself isn't emulated yet, a started transfer just stays active.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub(crate) struct SerialPort {
+	data: u8,
+	control: u8,
+	// Reading SB mid-transfer returns the partly shifted byte, latched for when transfers are emulated
+	read_during_transfer: Cell<bool>,
+}
+
+#[allow(unused)] // Read by the serial transfer, which doesn't exist yet
+impl SerialPort {
+	pub(crate) fn read_during_transfer(&self) -> bool {
+		self.read_during_transfer.get()
+	}
+}
+
+impl Rom for SerialPort {
+	fn read_byte(&self, address: u16) -> Result<u8, RamError> {
+		let value = self.peek_byte(address)?;
+		if address == 0 && self.control & TRANSFER_ACTIVE != 0 {
+			self.read_during_transfer.set(true);
+		}
+
+		Ok(value)
+	}
+
+	fn peek_byte(&self, address: u16) -> Result<u8, RamError> {
+		match address {
+			0 => Ok(self.data),
+			1 => Ok(self.control),
+			_ => Err(RamError::InvalidAddress(address)),
+		}
+	}
+}
+
+impl Ram for SerialPort {
+	fn write_byte(&mut self, address: u16, value: u8) -> Result<(), RamError> {
+		match address {
+			0 => self.data = value,
+			1 => self.control = value,
+			_ => return Err(RamError::InvalidAddress(address)),
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn peek_has_no_side_effect() {
+		let mut serial = SerialPort::default();
+		serial.write_byte(0, 0x42).unwrap();
+		serial.write_byte(1, TRANSFER_ACTIVE | 1).unwrap();
+
+		assert_eq!(serial.peek_byte(0).unwrap(), 0x42);
+		assert!(!serial.read_during_transfer());
+
+		assert_eq!(serial.read_byte(0).unwrap(), 0x42);
+		assert!(serial.read_during_transfer());
+	}
+
+	#[test]
+	fn read_without_transfer() {
+		let mut serial = SerialPort::default();
+		serial.write_byte(0, 0x42).unwrap();
+
+		assert_eq!(serial.read_byte(0).unwrap(), 0x42);
+		assert!(!serial.read_during_transfer());
+	}
+}
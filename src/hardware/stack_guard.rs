@@ -0,0 +1,35 @@
+use std::ops::Range;
+
+/// SP left the guarded range, recorded when the offending change was committed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StackViolation {
+	/// PC at commit time, which already points past the offending instruction
+	pub pc: u16,
+	pub old_sp: u16,
+	pub new_sp: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StackGuard {
+	range: Range<u16>,
+	violations: Vec<StackViolation>,
+}
+
+impl StackGuard {
+	pub(crate) fn new(range: Range<u16>) -> Self {
+		Self {
+			range,
+			violations: Vec::new(),
+		}
+	}
+
+	pub(crate) fn check(&mut self, pc: u16, old_sp: u16, new_sp: u16) {
+		if !self.range.contains(&new_sp) {
+			self.violations.push(StackViolation { pc, old_sp, new_sp });
+		}
+	}
+
+	pub(crate) fn take_violations(&mut self) -> Vec<StackViolation> {
+		std::mem::take(&mut self.violations)
+	}
+}
@@ -0,0 +1,135 @@
+/// Where test programs write their commands. The DMG has no register here, so on hardware the writes do nothing.
+pub const TEST_PORT_ADDRESS: u16 = 0xFF7E;
+
+const ASSERT_EQUAL: u8 = 0x01;
+const PRINT: u8 = 0x02;
+const END: u8 = 0xFF;
+
+/// A command written to the test port, recorded when the write was committed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TestPortEvent {
+	/// 0x01, checks that A equals B. PC already points past the writing instruction.
+	Assertion { pc: u16, a: u8, b: u8, passed: bool },
+	/// 0x02, A as a character for the host's log
+	Print(char),
+	/// 0xFF, the test is over with its result in A
+	End { result: u8 },
+	/// Any other value, most likely a mistake in the test program
+	Unknown { pc: u16, command: u8 },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct TestPort {
+	events: Vec<TestPortEvent>,
+}
+
+impl TestPort {
+	pub(crate) fn command(&mut self, pc: u16, command: u8, a: u8, b: u8) {
+		let event = match command {
+			ASSERT_EQUAL => TestPortEvent::Assertion {
+				pc,
+				a,
+				b,
+				passed: a == b,
+			},
+			PRINT => TestPortEvent::Print(char::from(a)),
+			END => TestPortEvent::End { result: a },
+			command => TestPortEvent::Unknown { pc, command },
+		};
+		self.events.push(event);
+	}
+
+	pub(crate) fn take_events(&mut self) -> Vec<TestPortEvent> {
+		std::mem::take(&mut self.events)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::hardware::cpu::Cpu;
+	use crate::hardware::ram::WORKING_RAM_START;
+
+	use super::*;
+
+	#[rustfmt::skip]
+	const TEST_PROGRAM: [u8; 21] = [
+		0x21, 0x7E, 0xFF, // ld HL, 0xFF7E
+		0x3E, 0x05,       // ld A, 5
+		0x06, 0x05,       // ld B, 5
+		0x36, 0x01,       // ld (HL), 0x01: A == B passes
+		0x06, 0x06,       // ld B, 6
+		0x36, 0x01,       // ld (HL), 0x01: A == B fails
+		0x3E, b'H',       // ld A, 'H'
+		0x36, 0x02,       // ld (HL), 0x02: print A
+		0xAF,             // xor A
+		0x36, 0xFF,       // ld (HL), 0xFF: end with A
+		0x00,
+	];
+
+	fn run_test_program(cpu: &mut Cpu) -> Vec<TestPortEvent> {
+		for (offset, byte) in TEST_PROGRAM.into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+		cpu.set_ime(false);
+
+		let mut events = Vec::new();
+		while cpu.current_pc() < WORKING_RAM_START + TEST_PROGRAM.len() as u16 - 1 {
+			cpu.step().unwrap();
+			events.extend(cpu.take_test_port_events());
+		}
+
+		events
+	}
+
+	#[test]
+	fn every_command() {
+		let mut cpu = Cpu::new();
+		cpu.set_test_port(true);
+
+		let events = run_test_program(&mut cpu);
+		assert_eq!(
+			events,
+			[
+				TestPortEvent::Assertion {
+					pc: WORKING_RAM_START + 9,
+					a: 5,
+					b: 5,
+					passed: true,
+				},
+				TestPortEvent::Assertion {
+					pc: WORKING_RAM_START + 13,
+					a: 5,
+					b: 6,
+					passed: false,
+				},
+				TestPortEvent::Print('H'),
+				TestPortEvent::End { result: 0 },
+			]
+		);
+	}
+
+	#[test]
+	fn unknown_command() {
+		let mut port = TestPort::default();
+		port.command(0x1234, 0x42, 0, 0);
+
+		assert_eq!(
+			port.take_events(),
+			[TestPortEvent::Unknown {
+				pc: 0x1234,
+				command: 0x42
+			}]
+		);
+		assert!(port.take_events().is_empty());
+	}
+
+	#[test]
+	fn ignored_when_disabled() {
+		let mut cpu = Cpu::new();
+
+		assert!(run_test_program(&mut cpu).is_empty());
+		// Like on hardware, the writes went nowhere
+		assert_eq!(cpu.read_byte(TEST_PORT_ADDRESS).unwrap(), 0xFF);
+	}
+}
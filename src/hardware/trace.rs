@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+use crate::decoder::disassemble;
+
+const PREFIX: u8 = 0xCB;
+
+/// An instruction as it was fetched, with the registers before it executed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+	pub pc: u16,
+	/// First byte of the instruction, 0xCB for the prefixed ones
+	pub opcode: u8,
+	/// Bytes fetched after it, the prefixed opcode or the immediates. Zero past the end of the instruction.
+	pub operands: [u8; 2],
+	pub af: u16,
+	pub bc: u16,
+	pub de: u16,
+	pub hl: u16,
+	pub sp: u16,
+}
+
+impl TraceEvent {
+	/// The instruction in assembly. Only formatted when asked for, so recording stays cheap.
+	pub fn mnemonic(&self) -> String {
+		match self.opcode {
+			PREFIX => disassemble(true, self.operands[0], &[]),
+			opcode => disassemble(false, opcode, &self.operands),
+		}
+	}
+}
+
+impl Display for TraceEvent {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{:#06X}: {:<24} AF={:04X} BC={:04X} DE={:04X} HL={:04X} SP={:04X}",
+			self.pc,
+			self.mnemonic(),
+			self.af,
+			self.bc,
+			self.de,
+			self.hl,
+			self.sp
+		)
+	}
+}
+
+// Keeps the last `capacity` events, dropping the oldest ones
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TraceBuffer {
+	capacity: usize,
+	events: VecDeque<TraceEvent>,
+}
+
+impl TraceBuffer {
+	pub(crate) fn new(capacity: usize) -> Self {
+		Self {
+			capacity,
+			events: VecDeque::with_capacity(capacity),
+		}
+	}
+
+	pub(crate) fn push(&mut self, event: TraceEvent) {
+		if self.capacity == 0 {
+			return;
+		}
+		if self.events.len() == self.capacity {
+			self.events.pop_front();
+		}
+		self.events.push_back(event);
+	}
+
+	pub(crate) fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+		self.events.iter()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn event(pc: u16) -> TraceEvent {
+		TraceEvent {
+			pc,
+			opcode: 0x00,
+			operands: [0; 2],
+			af: 0,
+			bc: 0,
+			de: 0,
+			hl: 0,
+			sp: 0,
+		}
+	}
+
+	#[test]
+	fn keeps_last_events() {
+		let mut buffer = TraceBuffer::new(3);
+		for pc in 0..5 {
+			buffer.push(event(pc));
+		}
+
+		let pcs: Vec<_> = buffer.events().map(|event| event.pc).collect();
+		assert_eq!(pcs, [2, 3, 4]);
+	}
+
+	#[test]
+	fn display() {
+		let event = TraceEvent {
+			pc: 0x0150,
+			opcode: 0x06,
+			operands: [0x42, 0x00],
+			af: 0x01B0,
+			bc: 0x0013,
+			de: 0x00D8,
+			hl: 0x014D,
+			sp: 0xFFFE,
+		};
+
+		assert_eq!(
+			event.to_string(),
+			"0x0150: LD B,0x42                AF=01B0 BC=0013 DE=00D8 HL=014D SP=FFFE"
+		);
+	}
+
+	#[test]
+	fn prefixed_mnemonic() {
+		let event = TraceEvent {
+			opcode: PREFIX,
+			operands: [0x7C, 0x00],
+			..event(0x0150)
+		};
+
+		assert_eq!(event.mnemonic(), "BIT 7,H");
+	}
+}
@@ -0,0 +1,36 @@
+use std::ops::RangeInclusive;
+
+/// A write to a watched address, recorded when the change was committed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WatchHit {
+	/// PC at commit time, which already points past the writing instruction
+	pub pc: u16,
+	pub address: u16,
+	/// What the address read as before the write, 0xFF if it can't be read
+	pub old_value: u8,
+	pub new_value: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct WatchSet {
+	ranges: Vec<RangeInclusive<u16>>,
+	hits: Vec<WatchHit>,
+}
+
+impl WatchSet {
+	pub(crate) fn add(&mut self, range: RangeInclusive<u16>) {
+		self.ranges.push(range);
+	}
+
+	pub(crate) fn is_watched(&self, address: u16) -> bool {
+		self.ranges.iter().any(|range| range.contains(&address))
+	}
+
+	pub(crate) fn record(&mut self, hit: WatchHit) {
+		self.hits.push(hit);
+	}
+
+	pub(crate) fn take_hits(&mut self) -> Vec<WatchHit> {
+		std::mem::take(&mut self.hits)
+	}
+}
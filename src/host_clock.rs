@@ -0,0 +1,186 @@
+use std::time::{Duration, Instant};
+
+// Wall-clock time for the frontend only, such as frame pacing. Nothing that affects emulation may read it, so runs
+// stay reproducible; the tests below check that no core source file outside this one reaches for std::time directly.
+pub trait HostClock {
+	/// Time elapsed since the clock was created
+	fn now(&self) -> Duration;
+	fn sleep(&mut self, duration: Duration);
+}
+
+pub struct SystemClock {
+	start: Instant,
+}
+
+impl SystemClock {
+	pub fn new() -> Self {
+		Self { start: Instant::now() }
+	}
+}
+
+impl Default for SystemClock {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl HostClock for SystemClock {
+	fn now(&self) -> Duration {
+		self.start.elapsed()
+	}
+
+	fn sleep(&mut self, duration: Duration) {
+		std::thread::sleep(duration);
+	}
+}
+
+/// Keeps frames evenly spaced, sleeping off whatever is left of each frame's time
+pub struct FramePacer<C: HostClock> {
+	clock: C,
+	frame_duration: Duration,
+	next_frame: Duration,
+}
+
+impl<C: HostClock> FramePacer<C> {
+	pub fn new(clock: C, frame_duration: Duration) -> Self {
+		let next_frame = clock.now() + frame_duration;
+		Self {
+			clock,
+			frame_duration,
+			next_frame,
+		}
+	}
+
+	pub fn clock(&self) -> &C {
+		&self.clock
+	}
+
+	pub fn clock_mut(&mut self) -> &mut C {
+		&mut self.clock
+	}
+
+	/// Wait until the current frame is over
+	pub fn wait_for_next_frame(&mut self) {
+		let now = self.clock.now();
+		if now < self.next_frame {
+			self.clock.sleep(self.next_frame - now);
+			self.next_frame += self.frame_duration;
+		} else {
+			// Running late, don't try to catch up with a burst of frames
+			self.next_frame = now + self.frame_duration;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::path::{Path, PathBuf};
+
+	use super::*;
+
+	const FRAME: Duration = Duration::from_millis(10);
+
+	// Clock that only moves when told to, sleeping advances it instantly
+	#[derive(Debug, Default)]
+	struct MockClock {
+		now: Duration,
+	}
+
+	impl MockClock {
+		fn new() -> Self {
+			Self::default()
+		}
+
+		fn advance(&mut self, duration: Duration) {
+			self.now += duration;
+		}
+	}
+
+	impl HostClock for MockClock {
+		fn now(&self) -> Duration {
+			self.now
+		}
+
+		fn sleep(&mut self, duration: Duration) {
+			self.advance(duration);
+		}
+	}
+
+	#[test]
+	fn sleeps_off_remaining_frame_time() {
+		let mut pacer = FramePacer::new(MockClock::new(), FRAME);
+
+		pacer.clock_mut().advance(Duration::from_millis(4));
+		pacer.wait_for_next_frame();
+		assert_eq!(pacer.clock().now(), Duration::from_millis(10));
+
+		pacer.clock_mut().advance(Duration::from_millis(7));
+		pacer.wait_for_next_frame();
+		assert_eq!(pacer.clock().now(), Duration::from_millis(20));
+	}
+
+	#[test]
+	fn late_frame_does_not_burst() {
+		let mut pacer = FramePacer::new(MockClock::new(), FRAME);
+
+		pacer.clock_mut().advance(Duration::from_millis(35));
+		pacer.wait_for_next_frame();
+		assert_eq!(pacer.clock().now(), Duration::from_millis(35));
+
+		pacer.wait_for_next_frame();
+		assert_eq!(pacer.clock().now(), Duration::from_millis(45));
+	}
+
+	// Host-side files, allowed to read the wall clock: the frontends and this module, which wraps it for them
+	const HOST_FILES: [&str; 3] = ["main.rs", "emulator.rs", "host_clock.rs"];
+
+	fn wall_clock_uses(path: &Path, source: &str) -> Vec<String> {
+		source
+			.lines()
+			.enumerate()
+			.filter(|(_, line)| line.contains("Instant") || line.contains("SystemTime") || line.contains("std::time"))
+			.map(|(number, line)| format!("{}:{}: {}", path.display(), number + 1, line.trim()))
+			.collect()
+	}
+
+	fn find_wall_clock_uses(directory: &Path, skipped: &[PathBuf], uses: &mut Vec<String>) {
+		for entry in fs::read_dir(directory).expect("Read source directory") {
+			let path = entry.expect("Read source directory entry").path();
+			if path.is_dir() {
+				find_wall_clock_uses(&path, skipped, uses);
+			} else if path.extension().is_some_and(|extension| extension == "rs") && !skipped.contains(&path) {
+				let source = fs::read_to_string(&path).expect("Read source file");
+				uses.extend(wall_clock_uses(&path, &source));
+			}
+		}
+	}
+
+	#[test]
+	fn emulation_does_not_use_wall_clock() {
+		let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+
+		let host_files: Vec<_> = HOST_FILES.iter().map(|file| source.join(file)).collect();
+
+		let mut uses = Vec::new();
+		find_wall_clock_uses(&source, &host_files, &mut uses);
+
+		assert!(
+			uses.is_empty(),
+			"Wall-clock time used by emulation:\n{}",
+			uses.join("\n")
+		);
+	}
+
+	#[test]
+	fn wall_clock_scan_flags_instant() {
+		let path = Path::new("hardware/timer.rs");
+		let source = "use std::time::Instant;\n\nfn tick() {}\n";
+
+		assert_eq!(
+			wall_clock_uses(path, source),
+			["hardware/timer.rs:1: use std::time::Instant;"]
+		);
+		assert!(wall_clock_uses(path, "fn tick() {}\n").is_empty());
+	}
+}
@@ -12,16 +12,25 @@ pub(crate) mod control;
 pub(crate) mod double_arithmetic;
 pub(crate) mod flags;
 pub(crate) mod flow;
+pub(crate) mod kind;
 pub(crate) mod load;
 pub(crate) mod logical;
 pub(crate) mod shared;
 pub(crate) mod shifting;
 pub(crate) mod single_bit;
 
+/// Runs an instruction against the CPU. Most instructions implement `ChangesetExecutable` instead, which first
+/// computes the changes from the current state and then commits them, and get this trait from its blanket impl.
 pub trait Executable {
 	fn execute(&self, cpu: &mut Cpu) -> Result<(), ExecutionError>;
+
+	/// Whether executing now takes a conditional branch, which costs extra cycles. Checked before executing.
+	fn branch_taken(&self, _cpu: &Cpu) -> bool {
+		false
+	}
 }
 
+/// What the decoder returns: anything executable that can also be printed and inspected
 pub trait Instruction: Executable + Debug + Display {}
 
 impl<T> Instruction for T where T: Executable + Debug + Display {}
@@ -5,7 +5,7 @@ use crate::hardware::cpu::Cpu;
 use crate::hardware::register_bank::BitFlags;
 use crate::instructions::base::byte::BinaryByteInstruction;
 use crate::instructions::base::byte::{BinaryByteOperation, ByteDestination, ByteSource};
-use crate::instructions::changeset::ChangeList;
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind, ChangePair};
 use crate::instructions::ExecutionError;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -55,7 +55,7 @@ impl BinaryArithmeticOperation {
 }
 
 impl BinaryByteOperation for BinaryArithmeticOperation {
-	type C = ChangeList;
+	type C = ChangePair<ChangeKind, BitFlagsChange>;
 
 	fn compute_changes(
 		&self,
@@ -70,10 +70,7 @@ impl BinaryByteOperation for BinaryArithmeticOperation {
 
 		let result = self.alu_result(left, right, carry);
 
-		Ok(ChangeList::new(vec![
-			result.change_dst(dst),
-			Box::new(result.change_flags()),
-		]))
+		Ok(ChangePair::new(result.change_dst(dst), result.change_flags()))
 	}
 }
 
@@ -93,7 +90,9 @@ mod tests {
 		BinaryArithmeticInstruction, BinaryArithmeticOperation, BinaryArithmeticOperationType,
 	};
 	use crate::instructions::base::byte::{ByteDestination, ByteSource};
-	use crate::instructions::changeset::{BitFlagsChange, ChangeList, ChangesetExecutable, SingleRegisterChange};
+	use crate::instructions::changeset::{
+		BitFlagsChange, ChangeKind, ChangePair, ChangesetExecutable, SingleRegisterChange,
+	};
 
 	#[test]
 	fn add() {
@@ -110,16 +109,14 @@ mod tests {
 			BinaryArithmeticOperation::new(BinaryArithmeticOperationType::Add, false),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(SingleRegisters::A, 0x46)),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_zero_flag(false)
-					.with_half_carry_flag(false)
-					.with_carry_flag(false)
-					.with_subtraction_flag(false),
-			),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(SingleRegisters::A, 0x46)),
+			BitFlagsChange::keep_all()
+				.with_zero_flag(false)
+				.with_half_carry_flag(false)
+				.with_carry_flag(false)
+				.with_subtraction_flag(false),
+		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
@@ -141,16 +138,14 @@ mod tests {
 			BinaryArithmeticOperation::new(BinaryArithmeticOperationType::Add, true),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(SingleRegisters::A, 0x50)),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_zero_flag(false)
-					.with_half_carry_flag(true)
-					.with_carry_flag(false)
-					.with_subtraction_flag(false),
-			),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(SingleRegisters::A, 0x50)),
+			BitFlagsChange::keep_all()
+				.with_zero_flag(false)
+				.with_half_carry_flag(true)
+				.with_carry_flag(false)
+				.with_subtraction_flag(false),
+		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
@@ -172,16 +167,14 @@ mod tests {
 			BinaryArithmeticOperation::new(BinaryArithmeticOperationType::Sub, true),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(SingleRegisters::A, 0xE1)),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_zero_flag(false)
-					.with_half_carry_flag(false)
-					.with_carry_flag(true)
-					.with_subtraction_flag(true),
-			),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(SingleRegisters::A, 0xE1)),
+			BitFlagsChange::keep_all()
+				.with_zero_flag(false)
+				.with_half_carry_flag(false)
+				.with_carry_flag(true)
+				.with_subtraction_flag(true),
+		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
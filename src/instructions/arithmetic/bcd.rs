@@ -2,7 +2,9 @@ use std::fmt::{Display, Formatter};
 
 use crate::hardware::cpu::Cpu;
 use crate::hardware::register_bank::BitFlags;
-use crate::instructions::changeset::{BitFlagsChange, ChangeList, ChangesetExecutable, SingleRegisterChange};
+use crate::instructions::changeset::{
+	BitFlagsChange, ChangeKind, ChangeList, ChangesetExecutable, SingleRegisterChange,
+};
 use crate::instructions::{ExecutionError, ACC_REGISTER};
 
 #[derive(Debug)]
@@ -49,9 +51,9 @@ impl ChangesetExecutable for DecimalAdjust {
 
 		let (next_acc, next_carry_flag) = DecimalAdjust::adjust(acc, sub_flag, carry_flag, half_carry_flag);
 
-		Ok(ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, next_acc)),
-			Box::new(
+		Ok(ChangeList::new([
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, next_acc)),
+			ChangeKind::from(
 				BitFlagsChange::keep_all()
 					.with_zero_flag(next_acc == 0)
 					.with_half_carry_flag(false)
@@ -160,8 +162,8 @@ mod tests {
 		let instruction = DecimalAdjust::new();
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0x50)),
-			Box::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x50)),
+			ChangeKind::from(
 				BitFlagsChange::keep_all()
 					.with_zero_flag(false)
 					.with_carry_flag(false)
@@ -7,7 +7,7 @@ use crate::instructions::changeset::{BitFlagsChange, ChangesetExecutable};
 use crate::instructions::ExecutionError;
 
 #[derive(Debug)]
-pub struct CompareInstruction {
+pub(crate) struct CompareInstruction {
 	left: ByteSource,
 	right: ByteSource,
 }
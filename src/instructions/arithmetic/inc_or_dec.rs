@@ -3,17 +3,17 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::alu::delta_u8;
 use crate::hardware::cpu::Cpu;
 use crate::instructions::base::byte::{ByteDestination, ByteSource, UnaryByteInstruction, UnaryByteOperation};
-use crate::instructions::changeset::{BitFlagsChange, ChangeList};
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind, ChangePair};
 use crate::instructions::shared::IndexUpdateType;
 use crate::instructions::ExecutionError;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct IncOrDecByteOperation {
+pub(crate) struct IncOrDecByteOperation {
 	type_: IndexUpdateType,
 }
 
 impl IncOrDecByteOperation {
-	pub fn new(type_: IndexUpdateType) -> Self {
+	pub(crate) fn new(type_: IndexUpdateType) -> Self {
 		Self { type_ }
 	}
 
@@ -26,7 +26,7 @@ impl IncOrDecByteOperation {
 }
 
 impl UnaryByteOperation for IncOrDecByteOperation {
-	type C = ChangeList;
+	type C = ChangePair<ChangeKind, BitFlagsChange>;
 
 	fn execute(&self, cpu: &Cpu, src: &ByteSource, dst: &ByteDestination) -> Result<Self::C, ExecutionError> {
 		let value = src.read(cpu)?;
@@ -36,10 +36,7 @@ impl UnaryByteOperation for IncOrDecByteOperation {
 		let result = alu_result.result;
 		let bitflags_change = BitFlagsChange::from(alu_result).keep_carry_flag();
 
-		Ok(ChangeList::new(vec![
-			dst.change_destination(result),
-			Box::new(bitflags_change),
-		]))
+		Ok(ChangePair::new(dst.change_destination(result), bitflags_change))
 	}
 }
 
@@ -69,15 +66,13 @@ mod tests {
 			IncOrDecByteOperation::new(IndexUpdateType::Increment),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0x81)),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_subtraction_flag(false)
-					.with_zero_flag(false)
-					.with_half_carry_flag(false),
-			),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x81)),
+			BitFlagsChange::keep_all()
+				.with_subtraction_flag(false)
+				.with_zero_flag(false)
+				.with_half_carry_flag(false),
+		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 		assert_eq!(actual, expected);
@@ -94,15 +89,13 @@ mod tests {
 			IncOrDecByteOperation::new(IndexUpdateType::Decrement),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0x7F)),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_subtraction_flag(true)
-					.with_zero_flag(false)
-					.with_half_carry_flag(true),
-			),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x7F)),
+			BitFlagsChange::keep_all()
+				.with_subtraction_flag(true)
+				.with_zero_flag(false)
+				.with_half_carry_flag(true),
+		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 		assert_eq!(actual, expected);
@@ -3,7 +3,9 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::cpu::Cpu;
 use crate::hardware::ram::Rom;
 use crate::hardware::register_bank::{DoubleRegisters, SingleRegisters};
-use crate::instructions::changeset::{Change, ChangesetExecutable, MemoryByteWriteChange, SingleRegisterChange};
+use crate::instructions::changeset::{
+	Change, ChangeKind, ChangesetExecutable, MemoryByteWriteChange, SingleRegisterChange,
+};
 use crate::instructions::{ExecutionError, ACC_REGISTER};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -78,17 +80,17 @@ impl ByteDestination {
 		Self::SingleRegister(ACC_REGISTER)
 	}
 
-	pub(crate) fn change_destination(&self, value: u8) -> Box<dyn Change> {
+	pub(crate) fn change_destination(&self, value: u8) -> ChangeKind {
 		match self {
-			Self::SingleRegister(single_reg) => Box::new(SingleRegisterChange::new(*single_reg, value)),
+			Self::SingleRegister(single_reg) => ChangeKind::from(SingleRegisterChange::new(*single_reg, value)),
 			Self::AddressImmediate(address_immediate) => {
-				Box::new(MemoryByteWriteChange::write_to_immediate(*address_immediate, value))
+				ChangeKind::from(MemoryByteWriteChange::write_to_immediate(*address_immediate, value))
 			}
 			Self::AddressInRegister(double_reg) => {
-				Box::new(MemoryByteWriteChange::write_to_register(*double_reg, value))
+				ChangeKind::from(MemoryByteWriteChange::write_to_register(*double_reg, value))
 			}
 			Self::OffsetAddressInRegister { base, offset } => {
-				Box::new(MemoryByteWriteChange::write_to_offset(*base, *offset, value))
+				ChangeKind::from(MemoryByteWriteChange::write_to_offset(*base, *offset, value))
 			}
 		}
 	}
@@ -260,7 +262,7 @@ mod tests {
 		let dest = ByteDestination::write_to_acc();
 
 		let actual = dest.change_destination(0x12);
-		let expected: Box<dyn Change> = Box::new(SingleRegisterChange::new(ACC_REGISTER, 0x12));
+		let expected = ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x12));
 
 		assert_eq!(actual, expected);
 	}
@@ -270,7 +272,7 @@ mod tests {
 		let dest = ByteDestination::AddressInRegister(DoubleRegisters::HL);
 
 		let actual = dest.change_destination(0x12);
-		let expected: Box<dyn Change> = Box::new(MemoryByteWriteChange::write_to_register(DoubleRegisters::HL, 0x12));
+		let expected = ChangeKind::from(MemoryByteWriteChange::write_to_register(DoubleRegisters::HL, 0x12));
 
 		assert_eq!(actual, expected);
 	}
@@ -280,7 +282,7 @@ mod tests {
 		let dest = ByteDestination::AddressImmediate(WORKING_RAM_START);
 
 		let actual = dest.change_destination(0x12);
-		let expected: Box<dyn Change> = Box::new(MemoryByteWriteChange::write_to_immediate(WORKING_RAM_START, 0x12));
+		let expected = ChangeKind::from(MemoryByteWriteChange::write_to_immediate(WORKING_RAM_START, 0x12));
 
 		assert_eq!(actual, expected);
 	}
@@ -293,7 +295,7 @@ mod tests {
 		};
 
 		let actual = dest.change_destination(0x12);
-		let expected: Box<dyn Change> = Box::new(MemoryByteWriteChange::write_to_offset(
+		let expected = ChangeKind::from(MemoryByteWriteChange::write_to_offset(
 			WORKING_RAM_START,
 			SingleRegisters::B,
 			0x12,
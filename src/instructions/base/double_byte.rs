@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::cpu::Cpu;
 use crate::hardware::register_bank::DoubleRegisters;
 use crate::instructions::changeset::{
-	Change, ChangesetExecutable, DoubleRegisterChange, MemoryDoubleByteWriteChange, SpChange,
+	Change, ChangeKind, ChangesetExecutable, DoubleRegisterChange, MemoryDoubleByteWriteChange, SpChange,
 };
 use crate::instructions::ExecutionError;
 
@@ -48,12 +48,14 @@ pub(crate) enum DoubleByteDestination {
 }
 
 impl DoubleByteDestination {
-	pub(crate) fn change_destination(&self, value: u16) -> Box<dyn Change> {
+	pub(crate) fn change_destination(&self, value: u16) -> ChangeKind {
 		match self {
-			Self::DoubleRegister(double_register) => Box::new(DoubleRegisterChange::new(*double_register, value)),
-			Self::StackPointer => Box::new(SpChange::new(value)),
+			Self::DoubleRegister(double_register) => {
+				ChangeKind::from(DoubleRegisterChange::new(*double_register, value))
+			}
+			Self::StackPointer => ChangeKind::from(SpChange::new(value)),
 			Self::AddressInImmediate(address) => {
-				Box::new(MemoryDoubleByteWriteChange::write_to_immediate(*address, value))
+				ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(*address, value))
 			}
 		}
 	}
@@ -206,7 +208,7 @@ mod tests {
 		let dest = DoubleByteDestination::DoubleRegister(DoubleRegisters::HL);
 
 		let actual = dest.change_destination(0x1234);
-		let expected: Box<dyn Change> = Box::new(DoubleRegisterChange::new(DoubleRegisters::HL, 0x1234));
+		let expected = ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, 0x1234));
 
 		assert_eq!(actual, expected);
 	}
@@ -216,7 +218,7 @@ mod tests {
 		let dest = DoubleByteDestination::AddressInImmediate(WORKING_RAM_START);
 
 		let actual = dest.change_destination(0x1234);
-		let expected: Box<dyn Change> = Box::new(MemoryDoubleByteWriteChange::write_to_immediate(
+		let expected = ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(
 			WORKING_RAM_START,
 			0x1234,
 		));
@@ -229,7 +231,7 @@ mod tests {
 		let dest = DoubleByteDestination::StackPointer;
 
 		let actual = dest.change_destination(0x1234);
-		let expected: Box<dyn Change> = Box::new(SpChange::new(0x1234));
+		let expected = ChangeKind::from(SpChange::new(0x1234));
 
 		assert_eq!(actual, expected);
 	}
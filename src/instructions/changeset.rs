@@ -5,30 +5,47 @@ use dyn_partial_eq::{dyn_partial_eq, DynPartialEq};
 use crate::hardware::cpu::Cpu;
 use crate::instructions::{Executable, ExecutionError};
 
+pub(crate) use self::call_stack::CallStackChange;
 pub(crate) use self::flags::{BitFlagsChange, ChangeIme};
+pub(crate) use self::kind::ChangeKind;
 pub(crate) use self::list::ChangeList;
+pub(crate) use self::low_power::LowPowerChange;
 pub(crate) use self::memory::{MemoryByteWriteChange, MemoryDoubleByteWriteChange};
 pub(crate) use self::noop::NoChange;
+pub(crate) use self::pair::ChangePair;
 pub(crate) use self::registers::{DoubleRegisterChange, SingleRegisterChange};
 pub(crate) use self::special_registers::{PcChange, SpChange};
 
+// Changes that write relative to SP (pushes, calls, interrupt dispatch) must resolve the address when the change is
+// computed, and write to it with an immediate address. Resolving it at commit time would depend on whether the SpChange
+// in the same list had been committed already.
 #[dyn_partial_eq]
 pub(crate) trait Change: Debug {
 	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError>;
 }
 
 mod boxed;
+mod call_stack;
 mod flags;
+mod kind;
 mod list;
+mod low_power;
 mod memory;
 mod noop;
+mod pair;
 mod registers;
 mod special_registers;
 
-pub(super) trait ChangesetExecutable {
+/// Computes the changes an instruction makes without mutating the CPU, so reading operands and writing results never
+/// interleave. The blanket impl below commits them, making every implementor an `Executable`.
+pub(crate) trait ChangesetExecutable {
 	type C: Change;
 
 	fn compute_change(&self, cpu: &Cpu) -> Result<Self::C, ExecutionError>;
+
+	fn branch_taken(&self, _cpu: &Cpu) -> bool {
+		false
+	}
 }
 
 impl<T> Executable for T
@@ -40,4 +57,8 @@ where
 		change.commit_change(cpu)?;
 		Ok(())
 	}
+
+	fn branch_taken(&self, cpu: &Cpu) -> bool {
+		ChangesetExecutable::branch_taken(self, cpu)
+	}
 }
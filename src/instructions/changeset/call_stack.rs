@@ -0,0 +1,138 @@
+use dyn_partial_eq::DynPartialEq;
+
+use crate::hardware::call_stack::CallFrame;
+use crate::hardware::cpu::Cpu;
+use crate::instructions::ExecutionError;
+
+use super::Change;
+
+// Only computed while the CPU tracks its call stack, so calls and returns cost nothing extra otherwise
+#[derive(PartialEq, DynPartialEq, Debug)]
+pub(crate) enum CallStackChange {
+	Call(CallFrame),
+	/// SP before returning, pointing at the return address
+	Return(u16),
+}
+
+impl Change for CallStackChange {
+	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
+		if let Some(call_stack) = cpu.call_stack.as_mut() {
+			match *self {
+				Self::Call(frame) => call_stack.call(frame),
+				Self::Return(sp) => call_stack.ret(sp),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::decoder::fetch_and_decode;
+	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::hardware::register_bank::DoubleRegisters;
+
+	use super::*;
+
+	fn load(cpu: &mut Cpu, start: u16, bytes: &[u8]) {
+		for (offset, byte) in bytes.iter().enumerate() {
+			cpu.write_byte(start + offset as u16, *byte).unwrap();
+		}
+	}
+
+	fn step(cpu: &mut Cpu, steps: usize) {
+		for _ in 0..steps {
+			fetch_and_decode(cpu).unwrap().execute(cpu).unwrap();
+		}
+	}
+
+	fn get_cpu() -> Cpu {
+		let mut cpu = Cpu::new();
+		cpu.set_call_stack_depth(Some(8));
+		cpu.set_sp(WORKING_RAM_START + 0x1000);
+		cpu.set_pc(WORKING_RAM_START);
+		cpu
+	}
+
+	#[test]
+	fn nested_call_and_return() {
+		let mut cpu = get_cpu();
+		// call 0xC010; 0xC010: call 0xC020; ret; 0xC020: ret
+		load(&mut cpu, WORKING_RAM_START, &[0xCD, 0x10, 0xC0]);
+		load(&mut cpu, WORKING_RAM_START + 0x10, &[0xCD, 0x20, 0xC0, 0xC9]);
+		load(&mut cpu, WORKING_RAM_START + 0x20, &[0xC9]);
+
+		step(&mut cpu, 2);
+		assert_eq!(
+			cpu.call_stack(),
+			[
+				CallFrame {
+					from_pc: WORKING_RAM_START,
+					to_address: WORKING_RAM_START + 0x10,
+					sp_at_call: WORKING_RAM_START + 0x0FFE,
+				},
+				CallFrame {
+					from_pc: WORKING_RAM_START + 0x10,
+					to_address: WORKING_RAM_START + 0x20,
+					sp_at_call: WORKING_RAM_START + 0x0FFC,
+				},
+			]
+		);
+
+		step(&mut cpu, 1);
+		assert_eq!(cpu.call_stack().len(), 1);
+		step(&mut cpu, 1);
+		assert!(cpu.call_stack().is_empty());
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 3);
+	}
+
+	#[test]
+	fn return_with_mismatched_sp() {
+		let mut cpu = get_cpu();
+		// call 0xC010; 0xC010: push BC; ret; 0xC030: ret
+		load(&mut cpu, WORKING_RAM_START, &[0xCD, 0x10, 0xC0]);
+		load(&mut cpu, WORKING_RAM_START + 0x10, &[0xC5, 0xC9]);
+		load(&mut cpu, WORKING_RAM_START + 0x30, &[0xC9]);
+		cpu.write_double_register(DoubleRegisters::BC, WORKING_RAM_START + 0x30);
+
+		// Returning through the pushed address leaves the call's frame, its return address is still on the stack
+		step(&mut cpu, 3);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 0x30);
+		assert_eq!(cpu.call_stack().len(), 1);
+
+		step(&mut cpu, 1);
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 3);
+		assert!(cpu.call_stack().is_empty());
+	}
+
+	#[test]
+	fn popped_frames_dropped() {
+		let mut cpu = get_cpu();
+		// call 0xC010; 0xC010: pop HL; pop HL; call 0xC020
+		load(&mut cpu, WORKING_RAM_START, &[0xCD, 0x10, 0xC0]);
+		load(&mut cpu, WORKING_RAM_START + 0x10, &[0xE1, 0xE1, 0xCD, 0x20, 0xC0]);
+
+		step(&mut cpu, 4);
+		assert_eq!(
+			cpu.call_stack(),
+			[CallFrame {
+				from_pc: WORKING_RAM_START + 0x12,
+				to_address: WORKING_RAM_START + 0x20,
+				sp_at_call: WORKING_RAM_START + 0x1000,
+			}]
+		);
+	}
+
+	#[test]
+	fn depth_capped() {
+		let mut cpu = get_cpu();
+		cpu.set_call_stack_depth(Some(2));
+		// Calls itself forever
+		load(&mut cpu, WORKING_RAM_START, &[0xCD, 0x00, 0xC0]);
+
+		step(&mut cpu, 5);
+		let sps: Vec<_> = cpu.call_stack().iter().map(|frame| frame.sp_at_call).collect();
+		assert_eq!(sps, [WORKING_RAM_START + 0x0FF8, WORKING_RAM_START + 0x0FF6]);
+	}
+}
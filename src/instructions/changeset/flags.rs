@@ -108,20 +108,35 @@ impl Change for BitFlagsChange {
 	}
 }
 
+// EI only takes effect after the next instruction, everything else that writes IME does so right away and cancels a
+// pending EI
 #[derive(PartialEq, DynPartialEq, Debug)]
 pub(crate) struct ChangeIme {
 	value: bool,
+	delayed: bool,
 }
 
 impl ChangeIme {
 	pub(crate) fn new(value: bool) -> Self {
-		Self { value }
+		Self { value, delayed: false }
+	}
+
+	pub(crate) fn delayed_enable() -> Self {
+		Self {
+			value: true,
+			delayed: true,
+		}
 	}
 }
 
 impl Change for ChangeIme {
 	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
-		cpu.ime.write(self.value);
+		if self.delayed {
+			cpu.ime_scheduled = true;
+		} else {
+			cpu.ime.write(self.value);
+			cpu.ime_scheduled = false;
+		}
 		Ok(())
 	}
 }
@@ -0,0 +1,84 @@
+use dyn_partial_eq::DynPartialEq;
+
+use crate::hardware::cpu::Cpu;
+use crate::instructions::ExecutionError;
+
+use super::{
+	BitFlagsChange, CallStackChange, Change, ChangeIme, DoubleRegisterChange, LowPowerChange, MemoryByteWriteChange,
+	MemoryDoubleByteWriteChange, NoChange, PcChange, SingleRegisterChange, SpChange,
+};
+
+// Every change an instruction can make on its own. Instructions that make one of several changes, and change lists,
+// hold them by value instead of boxing each one.
+macro_rules! change_kinds {
+	($($variant:ident($change:ty)),* $(,)?) => {
+		#[derive(PartialEq, DynPartialEq, Debug)]
+		pub(crate) enum ChangeKind {
+			$($variant($change),)*
+		}
+
+		$(impl From<$change> for ChangeKind {
+			fn from(change: $change) -> Self {
+				Self::$variant(change)
+			}
+		})*
+
+		impl Change for ChangeKind {
+			fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
+				match self {
+					$(Self::$variant(change) => change.commit_change(cpu),)*
+				}
+			}
+		}
+	};
+}
+
+change_kinds! {
+	SingleRegister(SingleRegisterChange),
+	DoubleRegister(DoubleRegisterChange),
+	MemoryByteWrite(MemoryByteWriteChange),
+	MemoryDoubleByteWrite(MemoryDoubleByteWriteChange),
+	BitFlags(BitFlagsChange),
+	Ime(ChangeIme),
+	Pc(PcChange),
+	Sp(SpChange),
+	CallStack(CallStackChange),
+	LowPower(LowPowerChange),
+	NoChange(NoChange),
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::hardware::register_bank::DoubleRegisters;
+	use crate::instructions::ACC_REGISTER;
+
+	use super::*;
+
+	#[test]
+	fn equality() {
+		let left = ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x12));
+		let right = ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x12));
+
+		assert_eq!(left, right)
+	}
+
+	#[test]
+	fn inequality() {
+		let left = ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x12));
+		let right = ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, 0x12));
+
+		assert_ne!(left, right)
+	}
+
+	#[test]
+	fn change() {
+		let mut actual = Cpu::new();
+		let mut expected = actual.clone();
+		expected.register_bank.write_single_named(ACC_REGISTER, 0x12);
+
+		let change = ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x12));
+		change.commit_change(&mut actual).unwrap();
+
+		assert_eq!(actual, expected);
+	}
+}
@@ -1,18 +1,29 @@
 use dyn_partial_eq::DynPartialEq;
+use smallvec::SmallVec;
 
 use crate::hardware::cpu::Cpu;
 use crate::instructions::ExecutionError;
 
-use super::Change;
+use super::{Change, ChangeKind};
 
-#[derive(PartialEq, DynPartialEq, Debug)]
+// Enough for every instruction, calls being the longest: SP, the pushed return address and PC.
+// Only hooks making more writes than this spill to the heap.
+const INLINE_CHANGES: usize = 4;
+
+#[derive(PartialEq, DynPartialEq, Debug, Default)]
 pub(crate) struct ChangeList {
-	changes: Vec<Box<dyn Change>>,
+	changes: SmallVec<[ChangeKind; INLINE_CHANGES]>,
 }
 
 impl ChangeList {
-	pub(crate) fn new(changes: Vec<Box<dyn Change>>) -> Self {
-		Self { changes }
+	pub(crate) fn new(changes: impl IntoIterator<Item = ChangeKind>) -> Self {
+		Self {
+			changes: changes.into_iter().collect(),
+		}
+	}
+
+	pub(crate) fn push(&mut self, change: impl Into<ChangeKind>) {
+		self.changes.push(change.into());
 	}
 }
 
@@ -52,8 +63,8 @@ mod tests {
 		expected.mapped_ram.write_byte(WORKING_RAM_START, 0x12).unwrap();
 
 		let change = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0xFF)),
-			Box::new(MemoryByteWriteChange::write_to_immediate(WORKING_RAM_START, 0x12)),
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0xFF)),
+			ChangeKind::from(MemoryByteWriteChange::write_to_immediate(WORKING_RAM_START, 0x12)),
 		]);
 		change.commit_change(&mut actual).unwrap();
 
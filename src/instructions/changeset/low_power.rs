@@ -0,0 +1,29 @@
+use dyn_partial_eq::DynPartialEq;
+
+use crate::hardware::cpu::Cpu;
+use crate::instructions::ExecutionError;
+
+use super::Change;
+
+// HALT and STOP, which stop fetching until something wakes the CPU
+#[derive(PartialEq, DynPartialEq, Debug)]
+pub(crate) enum LowPowerChange {
+	/// Stop fetching until an interrupt is pending
+	Halt,
+	/// HALT with IME clear and an interrupt already pending doesn't halt, but the next byte is read twice
+	HaltBug,
+	/// Stop fetching until a joypad line goes low
+	Stop,
+}
+
+impl Change for LowPowerChange {
+	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
+		match self {
+			Self::Halt => cpu.halted = true,
+			Self::HaltBug => cpu.halt_bug = true,
+			Self::Stop => cpu.stopped = true,
+		}
+
+		Ok(())
+	}
+}
@@ -60,6 +60,8 @@ impl MemoryByteWriteChange {
 impl Change for MemoryByteWriteChange {
 	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
 		let address = self.address.resolve(cpu)?;
+		cpu.watch_write(address, self.value);
+		cpu.test_port_write(address, self.value);
 		cpu.mapped_ram.write_byte(address, self.value)?;
 		Ok(())
 	}
@@ -79,15 +81,16 @@ impl MemoryDoubleByteWriteChange {
 	pub(crate) fn write_to_immediate(address: u16, value: u16) -> Self {
 		Self::new(MemoryWriteAddress::Immediate(address), value)
 	}
-
-	pub(crate) fn write_to_source(src: DoubleByteSource, value: u16) -> Self {
-		Self::new(MemoryWriteAddress::DoubleByteSource(src), value)
-	}
 }
 
 impl Change for MemoryDoubleByteWriteChange {
 	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
 		let address = self.address.resolve(cpu)?;
+		let [low, high] = self.value.to_le_bytes();
+		cpu.watch_write(address, low);
+		cpu.watch_write(address.wrapping_add(1), high);
+		cpu.test_port_write(address, low);
+		cpu.test_port_write(address.wrapping_add(1), high);
 
 		cpu.mapped_ram.write_double_byte(address, self.value)?;
 		Ok(())
@@ -96,7 +99,9 @@ impl Change for MemoryDoubleByteWriteChange {
 
 #[cfg(test)]
 mod tests {
+	use crate::decoder::fetch_and_decode;
 	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::hardware::watchpoints::WatchHit;
 	use crate::instructions::ACC_REGISTER;
 
 	use super::*;
@@ -169,16 +174,66 @@ mod tests {
 	#[test]
 	fn double_write_byte() {
 		let mut actual = Cpu::new();
-		actual.sp.write(WORKING_RAM_START);
 		let mut expected = actual.clone();
 		expected
 			.mapped_ram
 			.write_double_byte(WORKING_RAM_START, 0x1234)
 			.unwrap();
 
-		let change = MemoryDoubleByteWriteChange::write_to_source(DoubleByteSource::StackPointer, 0x1234);
+		let change = MemoryDoubleByteWriteChange::write_to_immediate(WORKING_RAM_START, 0x1234);
 		change.commit_change(&mut actual).unwrap();
 
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn watchpoint_hit() {
+		let mut cpu = Cpu::new();
+		cpu.add_watchpoint(0xFF40..=0xFF40);
+		cpu.register_bank.write_single_named(ACC_REGISTER, 0x91);
+		// ld (0xFF40), A
+		for (offset, byte) in [0xEA, 0x40, 0xFF].into_iter().enumerate() {
+			cpu.mapped_ram
+				.write_byte(WORKING_RAM_START + offset as u16, byte)
+				.unwrap();
+		}
+		cpu.set_pc(WORKING_RAM_START);
+
+		fetch_and_decode(&mut cpu).unwrap().execute(&mut cpu).unwrap();
+
+		assert_eq!(
+			cpu.take_watchpoint_hits(),
+			[WatchHit {
+				pc: WORKING_RAM_START + 3,
+				address: 0xFF40,
+				old_value: 0x00,
+				new_value: 0x91,
+			}]
+		);
+		assert!(cpu.take_watchpoint_hits().is_empty());
+	}
+
+	#[test]
+	fn watchpoint_range() {
+		let mut cpu = Cpu::new();
+		cpu.add_watchpoint(WORKING_RAM_START + 0x10..=WORKING_RAM_START + 0x1F);
+
+		MemoryByteWriteChange::write_to_immediate(WORKING_RAM_START + 0x0F, 0x12)
+			.commit_change(&mut cpu)
+			.unwrap();
+		MemoryByteWriteChange::write_to_immediate(WORKING_RAM_START + 0x20, 0x12)
+			.commit_change(&mut cpu)
+			.unwrap();
+		assert!(cpu.take_watchpoint_hits().is_empty());
+
+		// Only the high byte lands in the range
+		MemoryDoubleByteWriteChange::write_to_immediate(WORKING_RAM_START + 0x0F, 0x1234)
+			.commit_change(&mut cpu)
+			.unwrap();
+		let hits = cpu.take_watchpoint_hits();
+		assert_eq!(hits.len(), 1);
+		assert_eq!(hits[0].address, WORKING_RAM_START + 0x10);
+		assert_eq!(hits[0].old_value, 0x00);
+		assert_eq!(hits[0].new_value, 0x12);
+	}
 }
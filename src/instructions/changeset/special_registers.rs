@@ -20,6 +20,10 @@ impl SpChange {
 
 impl Change for SpChange {
 	fn commit_change(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
+		if let Some(stack_guard) = cpu.stack_guard.as_mut() {
+			stack_guard.check(cpu.pc.read(), cpu.sp.read(), self.value);
+		}
+
 		cpu.sp.write(self.value);
 		Ok(())
 	}
@@ -45,7 +49,10 @@ impl Change for PcChange {
 
 #[cfg(test)]
 mod tests {
+	use crate::decoder::fetch_and_decode;
 	use crate::hardware::cpu::Cpu;
+	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::hardware::stack_guard::StackViolation;
 	use crate::instructions::changeset::{Change, PcChange, SpChange};
 
 	#[test]
@@ -71,4 +78,39 @@ mod tests {
 
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn stack_guard() {
+		let program_start = WORKING_RAM_START + 0x1000;
+		let mut cpu = Cpu::new();
+		// push BC; jr -3
+		for (offset, byte) in [0xC5, 0x18, 0xFD].into_iter().enumerate() {
+			cpu.write_byte(program_start + offset as u16, byte).unwrap();
+		}
+		cpu.pc.write(program_start);
+		cpu.sp.write(WORKING_RAM_START + 0x10);
+		cpu.set_stack_guard(Some(WORKING_RAM_START + 0x08..WORKING_RAM_START + 0x11));
+
+		// Six pushes, the last two leave the guarded range
+		for _ in 0..12 {
+			let instruction = fetch_and_decode(&mut cpu).unwrap();
+			instruction.execute(&mut cpu).unwrap();
+		}
+
+		let expected = vec![
+			StackViolation {
+				pc: program_start + 1,
+				old_sp: WORKING_RAM_START + 0x08,
+				new_sp: WORKING_RAM_START + 0x06,
+			},
+			StackViolation {
+				pc: program_start + 1,
+				old_sp: WORKING_RAM_START + 0x06,
+				new_sp: WORKING_RAM_START + 0x04,
+			},
+		];
+
+		assert_eq!(cpu.take_stack_violations(), expected);
+		assert_eq!(cpu.take_stack_violations(), vec![]);
+	}
 }
@@ -1,7 +1,11 @@
 use std::fmt::{Display, Formatter};
 
+use crate::hardware::counters::divider::DIVIDER_ADDRESS;
 use crate::hardware::cpu::Cpu;
-use crate::instructions::changeset::{ChangeIme, ChangesetExecutable};
+use crate::hardware::interrupts;
+use crate::instructions::changeset::{
+	ChangeIme, ChangeKind, ChangeList, ChangesetExecutable, LowPowerChange, MemoryByteWriteChange,
+};
 use crate::instructions::{Executable, ExecutionError};
 
 #[derive(Debug)]
@@ -25,6 +29,7 @@ impl Display for NopInstruction {
 	}
 }
 
+// Two bytes, the second one is skipped. CGB speed switching isn't emulated, so KEY1 is ignored and STOP always stops.
 #[derive(Debug)]
 pub(crate) struct StopInstruction {}
 
@@ -34,9 +39,15 @@ impl StopInstruction {
 	}
 }
 
-impl Executable for StopInstruction {
-	fn execute(&self, _cpu: &mut Cpu) -> Result<(), ExecutionError> {
-		todo!("Implement STOP instruction")
+impl ChangesetExecutable for StopInstruction {
+	type C = ChangeList;
+
+	fn compute_change(&self, _cpu: &Cpu) -> Result<Self::C, ExecutionError> {
+		// DIV is reset on entering STOP, as any write to it does
+		Ok(ChangeList::new([
+			ChangeKind::from(MemoryByteWriteChange::write_to_immediate(DIVIDER_ADDRESS, 0)),
+			ChangeKind::from(LowPowerChange::Stop),
+		]))
 	}
 }
 
@@ -55,9 +66,15 @@ impl HaltInstruction {
 	}
 }
 
-impl Executable for HaltInstruction {
-	fn execute(&self, _cpu: &mut Cpu) -> Result<(), ExecutionError> {
-		todo!("Implement HALT instruction")
+impl ChangesetExecutable for HaltInstruction {
+	type C = LowPowerChange;
+
+	fn compute_change(&self, cpu: &Cpu) -> Result<Self::C, ExecutionError> {
+		// With IME set a pending interrupt is serviced right away, so halting and waking immediately is the same
+		if !cpu.ime.read() && interrupts::pending(cpu)? != 0 {
+			return Ok(LowPowerChange::HaltBug);
+		}
+		Ok(LowPowerChange::Halt)
 	}
 }
 
@@ -91,7 +108,10 @@ impl ChangesetExecutable for SetImeInstruction {
 	type C = ChangeIme;
 
 	fn compute_change(&self, _cpu: &Cpu) -> Result<Self::C, ExecutionError> {
-		Ok(ChangeIme::new(self.value))
+		match self.value {
+			true => Ok(ChangeIme::delayed_enable()),
+			false => Ok(ChangeIme::new(false)),
+		}
 	}
 }
 
@@ -105,15 +125,180 @@ impl Display for SetImeInstruction {
 #[cfg(test)]
 mod tests {
 	use crate::hardware::cpu::Cpu;
+	use crate::hardware::interrupts::{Interrupt, INTERRUPT_ENABLE_ADDRESS, INTERRUPT_FLAGS_ADDRESS};
+	use crate::hardware::joypad::Button;
+	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::hardware::register_bank::SingleRegisters;
 
 	use super::*;
 
+	// HALT, INC A, NOP in working RAM, with only the timer interrupt enabled
+	fn get_halting_cpu(ime: bool) -> Cpu {
+		let mut cpu = Cpu::new();
+		for (offset, byte) in [0x76, 0x3C, 0x00].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.write_byte(INTERRUPT_ENABLE_ADDRESS, Interrupt::Timer.mask())
+			.unwrap();
+		cpu.set_pc(WORKING_RAM_START);
+		cpu.set_sp(WORKING_RAM_START + 0x100);
+		cpu.set_ime(ime);
+		cpu
+	}
+
+	#[test]
+	fn halt_wakes_on_timer_interrupt() {
+		let mut cpu = get_halting_cpu(true);
+
+		cpu.step().unwrap();
+		assert!(cpu.halted());
+		cpu.step().unwrap();
+		assert!(cpu.halted());
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 1);
+
+		cpu.request_interrupt(Interrupt::Timer).unwrap();
+		cpu.step().unwrap();
+		assert!(!cpu.halted());
+		cpu.step().unwrap();
+		assert_eq!(cpu.current_pc(), Interrupt::Timer.vector());
+		assert_eq!(cpu.read_register(SingleRegisters::A), 0);
+	}
+
+	#[test]
+	fn halt_wakes_on_timer_overflow() {
+		let mut cpu = get_halting_cpu(false);
+		// TMA at 0xF0, TAC enabled at one increment every 16 cycles
+		cpu.write_byte(0xFF06, 0xF0).unwrap();
+		cpu.write_byte(0xFF07, 0b101).unwrap();
+
+		cpu.step().unwrap();
+		while cpu.halted() {
+			assert!(cpu.elapsed_cycles() <= 256 * 16, "TIMA never overflowed");
+			cpu.step().unwrap();
+		}
+
+		assert_eq!(cpu.elapsed_cycles(), 256 * 16 + 4);
+		assert_eq!(cpu.read_byte(0xFF05).unwrap(), 0xF0);
+		assert_ne!(
+			cpu.read_byte(INTERRUPT_FLAGS_ADDRESS).unwrap() & Interrupt::Timer.mask(),
+			0
+		);
+		cpu.step().unwrap();
+		assert_eq!(cpu.read_register(SingleRegisters::A), 1);
+	}
+
+	#[test]
+	fn halt_wakes_without_servicing_when_ime_clear() {
+		let mut cpu = get_halting_cpu(false);
+
+		cpu.step().unwrap();
+		cpu.request_interrupt(Interrupt::Timer).unwrap();
+		cpu.step().unwrap();
+		assert!(!cpu.halted());
+		cpu.step().unwrap();
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 2);
+		assert_eq!(cpu.read_register(SingleRegisters::A), 1);
+	}
+
+	#[test]
+	fn halt_bug() {
+		let mut cpu = get_halting_cpu(false);
+		cpu.request_interrupt(Interrupt::Timer).unwrap();
+
+		cpu.step().unwrap();
+		assert!(!cpu.halted());
+		cpu.step().unwrap();
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 1);
+		cpu.step().unwrap();
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 2);
+		assert_eq!(cpu.read_register(SingleRegisters::A), 2);
+	}
+
+	const JOYPAD_ADDRESS: u16 = 0xFF00;
+
+	// STOP, its padding byte, INC A, with no joypad group selected
+	fn get_stopping_cpu() -> Cpu {
+		let mut cpu = Cpu::new();
+		for (offset, byte) in [0x10, 0x00, 0x3C].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.write_byte(JOYPAD_ADDRESS, 0x30).unwrap();
+		cpu.set_pc(WORKING_RAM_START);
+		cpu
+	}
+
+	#[test]
+	fn stop_resets_divider() {
+		let mut cpu = get_stopping_cpu();
+		// Let DIV count up first, so the reset can't pass by leaving it at its initial 0
+		cpu.advance_cycles(0xAB00).unwrap();
+		assert_eq!(cpu.read_byte(DIVIDER_ADDRESS).unwrap(), 0xAB);
+
+		cpu.step().unwrap();
+		assert!(cpu.stopped());
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 2);
+		assert_eq!(cpu.read_byte(DIVIDER_ADDRESS).unwrap(), 0);
+	}
+
+	#[test]
+	fn stop_wakes_on_joypad_line() {
+		let mut cpu = get_stopping_cpu();
+		cpu.step().unwrap();
+
+		// Pressed, but its group isn't selected so no line changes
+		cpu.set_button(Button::A, true);
+		cpu.step().unwrap();
+		assert!(cpu.stopped());
+
+		cpu.write_byte(JOYPAD_ADDRESS, 0x10).unwrap();
+		cpu.step().unwrap();
+		assert!(!cpu.stopped());
+		cpu.step().unwrap();
+		assert_eq!(cpu.read_register(SingleRegisters::A), 1);
+	}
+
+	// EI, then the given opcode, then NOP, with a timer interrupt pending and IME clear
+	fn get_enabling_cpu(second: u8) -> Cpu {
+		let mut cpu = get_halting_cpu(false);
+		for (offset, byte) in [0xFB, second, 0x00].into_iter().enumerate() {
+			cpu.write_byte(WORKING_RAM_START + offset as u16, byte).unwrap();
+		}
+		cpu.request_interrupt(Interrupt::Timer).unwrap();
+		cpu
+	}
+
+	#[test]
+	fn ei_delayed_by_one_instruction() {
+		let mut cpu = get_enabling_cpu(0x00);
+
+		cpu.step().unwrap();
+		assert!(!cpu.ime());
+		cpu.step().unwrap();
+		assert!(cpu.ime());
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 2);
+
+		cpu.step().unwrap();
+		assert_eq!(cpu.current_pc(), Interrupt::Timer.vector());
+		assert_eq!(cpu.read_byte(cpu.current_sp()).unwrap(), 0x02);
+	}
+
+	#[test]
+	fn di_cancels_pending_ei() {
+		let mut cpu = get_enabling_cpu(0xF3);
+
+		for _ in 0..3 {
+			cpu.step().unwrap();
+		}
+		assert!(!cpu.ime());
+		assert_eq!(cpu.current_pc(), WORKING_RAM_START + 3);
+	}
+
 	#[test]
 	fn change_ime() {
 		let cpu = Cpu::new();
 
 		let actual = SetImeInstruction::new(true).compute_change(&cpu).unwrap();
-		let expected = ChangeIme::new(true);
+		let expected = ChangeIme::delayed_enable();
 
 		assert_eq!(actual, expected);
 	}
@@ -1,12 +1,12 @@
 use std::fmt::{Display, Formatter};
 
-use crate::hardware::alu::{add_u8, delta_u8};
+use crate::hardware::alu::{add_u8, add_with_carry_u8, delta_u8};
 use crate::hardware::cpu::Cpu;
 use crate::instructions::base::double_byte::{
 	BinaryDoubleByteInstruction, BinaryDoubleByteOperation, DoubleByteDestination, DoubleByteSource,
 	UnaryDoubleByteInstruction, UnaryDoubleByteOperation,
 };
-use crate::instructions::changeset::{BitFlagsChange, Change, ChangeList, ChangesetExecutable};
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind, ChangeList, ChangesetExecutable};
 use crate::instructions::shared::IndexUpdateType;
 use crate::instructions::ExecutionError;
 
@@ -32,16 +32,19 @@ impl BinaryDoubleByteOperation for BinaryDoubleByteAddOperation {
 		let left_value = left.read(cpu)?;
 		let right_value = right.read(cpu)?;
 
-		let high_left_value = left_value.to_le_bytes()[1];
-		let high_right_value = left_value.to_le_bytes()[1];
+		let [low_left_value, high_left_value] = left_value.to_le_bytes();
+		let [low_right_value, high_right_value] = right_value.to_le_bytes();
 
-		let (result, _overflow) = left_value.overflowing_add(right_value);
+		// A 16 bit add is a low byte add followed by a high byte add with its carry, whose flags are the ones kept:
+		// half carry out of bit 11 and carry out of bit 15
+		let low_alu_result = add_u8(low_left_value, low_right_value);
+		let high_alu_result = add_with_carry_u8(high_left_value, high_right_value, low_alu_result.carry);
 
-		let high_alu_result = add_u8(high_left_value, high_right_value);
+		let result = u16::from_le_bytes([low_alu_result.result, high_alu_result.result]);
 
-		Ok(ChangeList::new(vec![
+		Ok(ChangeList::new([
 			dst.change_destination(result),
-			Box::new(
+			ChangeKind::from(
 				BitFlagsChange::keep_all()
 					.with_subtraction_flag(false)
 					.with_half_carry_flag(high_alu_result.half_carry)
@@ -78,7 +81,7 @@ impl IncOrDecDoubleByteOperation {
 }
 
 impl UnaryDoubleByteOperation for IncOrDecDoubleByteOperation {
-	type C = Box<dyn Change>;
+	type C = ChangeKind;
 
 	fn execute(
 		&self,
@@ -138,16 +141,16 @@ impl ChangesetExecutable for AddSignedByteToDoubleByte {
 			.with_zero_flag(false)
 			.with_subtraction_flag(false);
 
-		Ok(ChangeList::new(vec![
+		Ok(ChangeList::new([
 			self.dst.change_destination(result),
-			Box::new(bitflag_changes),
+			ChangeKind::from(bitflag_changes),
 		]))
 	}
 }
 
 impl Display for AddSignedByteToDoubleByte {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		write!(f, "add {} <- {}, {:#04X}", self.delta, self.src, self.delta)
+		write!(f, "add {} <- {}, {:#04X}", self.dst, self.src, self.delta)
 	}
 }
 
@@ -173,8 +176,8 @@ mod tests {
 
 		let actual = instruction.compute_change(&cpu).unwrap();
 		let expected = ChangeList::new(vec![
-			Box::new(DoubleRegisterChange::new(DoubleRegisters::HL, 0x1234 + 0x4321)),
-			Box::new(
+			ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, 0x1234 + 0x4321)),
+			ChangeKind::from(
 				BitFlagsChange::keep_all()
 					.with_subtraction_flag(false)
 					.with_half_carry_flag(false)
@@ -185,6 +188,52 @@ mod tests {
 		assert_eq!(actual, expected);
 	}
 
+	fn add_hl(cpu: &Cpu, right: DoubleRegisters) -> ChangeList {
+		BinaryDoubleByteAddInstruction::new(
+			DoubleByteSource::DoubleRegister(DoubleRegisters::HL),
+			DoubleByteSource::DoubleRegister(right),
+			DoubleByteDestination::DoubleRegister(DoubleRegisters::HL),
+			BinaryDoubleByteAddOperation::new(),
+		)
+		.compute_change(cpu)
+		.unwrap()
+	}
+
+	fn add_hl_result(result: u16, half_carry: bool, carry: bool) -> ChangeList {
+		ChangeList::new(vec![
+			ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, result)),
+			ChangeKind::from(
+				BitFlagsChange::keep_all()
+					.with_subtraction_flag(false)
+					.with_half_carry_flag(half_carry)
+					.with_carry_flag(carry),
+			),
+		])
+	}
+
+	#[test]
+	fn double_byte_add_flags() {
+		let mut cpu = Cpu::new();
+
+		// Carry out of the low byte into bit 11
+		cpu.register_bank.write_double_named(DoubleRegisters::HL, 0x0FFF);
+		cpu.register_bank.write_double_named(DoubleRegisters::BC, 0x0001);
+		assert_eq!(add_hl(&cpu, DoubleRegisters::BC), add_hl_result(0x1000, true, false));
+
+		cpu.register_bank.write_double_named(DoubleRegisters::HL, 0x8000);
+		assert_eq!(add_hl(&cpu, DoubleRegisters::HL), add_hl_result(0x0000, false, true));
+
+		// The right operand matters, not only the left one
+		cpu.register_bank.write_double_named(DoubleRegisters::HL, 0x0100);
+		cpu.register_bank.write_double_named(DoubleRegisters::DE, 0xFF00);
+		assert_eq!(add_hl(&cpu, DoubleRegisters::DE), add_hl_result(0x0000, true, true));
+
+		// Half carry is taken from bit 11, not bit 3 of the low byte
+		cpu.register_bank.write_double_named(DoubleRegisters::HL, 0x000F);
+		cpu.register_bank.write_double_named(DoubleRegisters::BC, 0x0001);
+		assert_eq!(add_hl(&cpu, DoubleRegisters::BC), add_hl_result(0x0010, false, false));
+	}
+
 	#[test]
 	fn inc() {
 		let mut cpu = Cpu::new();
@@ -197,7 +246,7 @@ mod tests {
 		);
 
 		let actual = instruction.compute_change(&cpu).unwrap();
-		let expected: Box<dyn Change> = Box::new(DoubleRegisterChange::new(DoubleRegisters::HL, 0x1234 + 1));
+		let expected = ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, 0x1234 + 1));
 
 		assert_eq!(actual, expected);
 	}
@@ -212,8 +261,8 @@ mod tests {
 
 		let actual = instruction.compute_change(&cpu).unwrap();
 		let expected = ChangeList::new(vec![
-			Box::new(SpChange::new(0x11FF)),
-			Box::new(
+			ChangeKind::from(SpChange::new(0x11FF)),
+			ChangeKind::from(
 				BitFlagsChange::zero_all()
 					.with_half_carry_flag(true)
 					.with_carry_flag(true),
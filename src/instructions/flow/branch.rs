@@ -1,29 +1,36 @@
 use std::fmt::{Display, Formatter, LowerHex, UpperHex};
 
 use crate::hardware::cpu::Cpu;
+use crate::hardware::ram::address_name;
 use crate::instructions::base::double_byte::DoubleByteSource;
-use crate::instructions::changeset::{Change, ChangesetExecutable, NoChange, PcChange};
+use crate::instructions::changeset::{ChangeKind, ChangesetExecutable, NoChange, PcChange};
 use crate::instructions::flow::BranchCondition;
 use crate::instructions::ExecutionError;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub(crate) enum JumpInstructionDestination {
 	FromSource(DoubleByteSource),
-	RelativeToPc(i8),
+	// Relative to the PC right after the operand, captured at decode time so executing doesn't depend on the PC then
+	RelativeToPc { base: u16, delta: i8 },
 }
 
 impl JumpInstructionDestination {
+	/// Jump relative to the address right after the operand
+	pub(crate) fn relative_to(base: u16, delta: i8) -> Self {
+		Self::RelativeToPc { base, delta }
+	}
+
 	fn resolve(&self, cpu: &Cpu) -> Result<u16, ExecutionError> {
 		match self {
 			Self::FromSource(source) => source.read(cpu),
-			Self::RelativeToPc(delta) => Ok(cpu.pc.read().wrapping_add_signed((*delta).into())),
+			Self::RelativeToPc { base, delta } => Ok(base.wrapping_add_signed((*delta).into())),
 		}
 	}
 
 	fn is_relative(&self) -> bool {
 		match self {
 			Self::FromSource(_) => false,
-			Self::RelativeToPc(_) => true,
+			Self::RelativeToPc { .. } => true,
 		}
 	}
 }
@@ -64,8 +71,8 @@ impl Display for JumpInstructionDestination {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Self::FromSource(s) => write!(f, "{s}"),
-			Self::RelativeToPc(r) => {
-				let signed_i8 = SignedI8 { value: *r };
+			Self::RelativeToPc { delta, .. } => {
+				let signed_i8 = SignedI8 { value: *delta };
 				write!(f, "PC{signed_i8:#04X}")
 			}
 		}
@@ -85,32 +92,53 @@ impl JumpInstruction {
 }
 
 impl ChangesetExecutable for JumpInstruction {
-	type C = Box<dyn Change>;
+	type C = ChangeKind;
 
 	fn compute_change(&self, cpu: &Cpu) -> Result<Self::C, ExecutionError> {
 		if self.condition.satisfied(cpu) {
 			let destination = self.dst.resolve(cpu)?;
-			Ok(Box::new(PcChange::new(destination)))
+			Ok(ChangeKind::from(PcChange::new(destination)))
 		} else {
-			Ok(Box::new(NoChange::new()))
+			Ok(ChangeKind::from(NoChange::new()))
 		}
 	}
+
+	fn branch_taken(&self, cpu: &Cpu) -> bool {
+		self.condition.satisfied(cpu)
+	}
 }
 
 impl Display for JumpInstruction {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		if self.dst.is_relative() {
-			write!(f, "jr")?;
+			write!(f, "jr ")?;
 		} else {
-			write!(f, "jp")?;
+			write!(f, "jp ")?;
 		}
 
 		if let Some(condition) = self.condition.as_maybe_string() {
-			write!(f, " {condition}, ")?;
+			write!(f, "{condition}, ")?;
 		}
 
 		write!(f, "{}", self.dst)?;
 
+		// Resolved target as a trailing comment, so the operand itself still reads as encoded
+		match self.dst {
+			JumpInstructionDestination::RelativeToPc { base, delta } => {
+				let target = base.wrapping_add_signed(delta.into());
+				write!(f, " ; → {target:#06X}")?;
+				if let Some(name) = address_name(target) {
+					write!(f, " ({name})")?;
+				}
+			}
+			JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(address)) => {
+				if let Some(name) = address_name(address) {
+					write!(f, " ; {name}")?;
+				}
+			}
+			JumpInstructionDestination::FromSource(_) => {}
+		}
+
 		Ok(())
 	}
 }
@@ -143,7 +171,7 @@ mod tests {
 			BranchCondition::Unconditional,
 		);
 
-		let expected: Box<dyn Change> = Box::new(PcChange::new(0xABCD));
+		let expected = ChangeKind::from(PcChange::new(0xABCD));
 		let actual = instruction.compute_change(&cpu).expect("Compute change");
 
 		assert_eq!(actual, expected);
@@ -154,14 +182,14 @@ mod tests {
 		let cpu = get_cpu();
 
 		let instruction = JumpInstruction::new(
-			JumpInstructionDestination::RelativeToPc(-0x7F),
+			JumpInstructionDestination::relative_to(cpu.pc.read(), -0x7F),
 			BranchCondition::TestFlag {
 				flag: BitFlags::Carry,
 				branch_if_equals: false,
 			},
 		);
 
-		let expected: Box<dyn Change> = Box::new(PcChange::new(0x11B5));
+		let expected = ChangeKind::from(PcChange::new(0x11B5));
 		let actual = instruction.compute_change(&cpu).expect("Compute change");
 
 		assert_eq!(actual, expected);
@@ -172,16 +200,66 @@ mod tests {
 		let cpu = get_cpu();
 
 		let instruction = JumpInstruction::new(
-			JumpInstructionDestination::RelativeToPc(-0x7F),
+			JumpInstructionDestination::relative_to(cpu.pc.read(), -0x7F),
 			BranchCondition::TestFlag {
 				flag: BitFlags::Carry,
 				branch_if_equals: true,
 			},
 		);
 
-		let expected: Box<dyn Change> = Box::new(NoChange::new());
+		let expected = ChangeKind::from(NoChange::new());
 		let actual = instruction.compute_change(&cpu).expect("Compute change");
 
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn relative_jump_ignores_pc_at_execution() {
+		let mut cpu = get_cpu();
+
+		let instruction = JumpInstruction::new(
+			JumpInstructionDestination::relative_to(cpu.pc.read(), 0x10),
+			BranchCondition::Unconditional,
+		);
+		cpu.pc.write(0x4000);
+
+		let expected = ChangeKind::from(PcChange::new(0x1244));
+		let actual = instruction.compute_change(&cpu).expect("Compute change");
+
+		assert_eq!(actual, expected);
+		assert_eq!(instruction.to_string(), "jr PC+0x10 ; → 0x1244");
+	}
+
+	#[test]
+	fn display_resolved_targets() {
+		let relative = |base, delta| {
+			JumpInstruction::new(
+				JumpInstructionDestination::relative_to(base, delta),
+				BranchCondition::Unconditional,
+			)
+			.to_string()
+		};
+		let absolute = |address| {
+			JumpInstruction::new(
+				JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(address)),
+				BranchCondition::Unconditional,
+			)
+			.to_string()
+		};
+
+		assert_eq!(relative(0x0150, -5), "jr PC-0x5 ; → 0x014B");
+		// JR at 0xFFF0, so the base right after the operand is 0xFFF2
+		assert_eq!(relative(0xFFF2, 0x20), "jr PC+0x20 ; → 0x0012");
+		assert_eq!(relative(0xFF70, 0x10), "jr PC+0x10 ; → 0xFF80 (HRAM)");
+		assert_eq!(absolute(0xFF80), "jp 0xFF80 ; HRAM");
+		assert_eq!(absolute(0x0150), "jp 0x0150");
+		assert_eq!(
+			JumpInstruction::new(
+				JumpInstructionDestination::FromSource(DoubleByteSource::DoubleRegister(DoubleRegisters::HL)),
+				BranchCondition::Unconditional,
+			)
+			.to_string(),
+			"jp HL"
+		);
+	}
 }
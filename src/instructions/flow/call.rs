@@ -1,11 +1,11 @@
 use std::fmt::{Display, Formatter};
 
-use crate::bits::bits_to_byte;
+use crate::hardware::call_stack::CallFrame;
 use crate::hardware::cpu::Cpu;
+use crate::hardware::ram::address_name;
 use crate::hardware::register_bank::BitFlags;
-use crate::instructions::base::double_byte::DoubleByteSource;
 use crate::instructions::changeset::{
-	Change, ChangeList, ChangesetExecutable, MemoryDoubleByteWriteChange, PcChange, SpChange,
+	CallStackChange, ChangeList, ChangesetExecutable, MemoryDoubleByteWriteChange, PcChange, SpChange,
 };
 use crate::instructions::flow::BranchCondition;
 use crate::instructions::ExecutionError;
@@ -14,11 +14,16 @@ use crate::instructions::ExecutionError;
 pub(crate) struct CallInstruction {
 	condition: BranchCondition,
 	address: u16,
+	restart: bool,
 }
 
 impl CallInstruction {
 	pub(crate) fn new(condition: BranchCondition, address: u16) -> Self {
-		Self { condition, address }
+		Self {
+			condition,
+			address,
+			restart: false,
+		}
 	}
 
 	pub(crate) fn call(address: u16) -> Self {
@@ -29,10 +34,13 @@ impl CallInstruction {
 		Self::new(BranchCondition::TestFlag { flag, branch_if_equals }, address)
 	}
 
-	pub(crate) fn restart(bits: [bool; 3]) -> Self {
-		let bits_as_byte: u16 = bits_to_byte(&bits).into();
-		let address = 8u16 * bits_as_byte;
-		Self::call(address)
+	/// Call to one of the 8 fixed vectors, `index` being the 3 bits encoded in the opcode
+	pub(crate) fn restart(index: u8) -> Self {
+		let address = 8 * u16::from(index);
+		Self {
+			restart: true,
+			..Self::call(address)
+		}
 	}
 }
 
@@ -40,33 +48,50 @@ impl ChangesetExecutable for CallInstruction {
 	type C = ChangeList;
 
 	fn compute_change(&self, cpu: &Cpu) -> Result<Self::C, ExecutionError> {
-		let mut changes: Vec<Box<dyn Change>> = Vec::new();
+		let mut changes = ChangeList::default();
 
 		if self.condition.satisfied(cpu) {
 			let mut sp = cpu.sp.read();
 			sp = sp.wrapping_add_signed(-2);
-			changes.push(Box::new(SpChange::new(sp)));
+			changes.push(SpChange::new(sp));
 
 			let old_pc = cpu.pc.read();
-			changes.push(Box::new(MemoryDoubleByteWriteChange::write_to_source(
-				DoubleByteSource::StackPointer,
-				old_pc,
-			)));
-
-			changes.push(Box::new(PcChange::new(self.address)))
+			changes.push(MemoryDoubleByteWriteChange::write_to_immediate(sp, old_pc));
+
+			changes.push(PcChange::new(self.address));
+
+			if cpu.call_stack.is_some() {
+				let length = if self.restart { 1 } else { 3 };
+				changes.push(CallStackChange::Call(CallFrame {
+					from_pc: old_pc.wrapping_sub(length),
+					to_address: self.address,
+					sp_at_call: sp,
+				}));
+			}
 		}
 
-		Ok(ChangeList::new(changes))
+		Ok(changes)
+	}
+
+	fn branch_taken(&self, cpu: &Cpu) -> bool {
+		self.condition.satisfied(cpu)
 	}
 }
 
 impl Display for CallInstruction {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		write!(f, "call")?;
+		if self.restart {
+			return write!(f, "rst {:#04X}", self.address);
+		}
+
+		write!(f, "call ")?;
 		if let Some(condition) = self.condition.as_maybe_string() {
 			write!(f, "{condition}, ")?;
 		}
 		write!(f, "{:#06X}", self.address)?;
+		if let Some(name) = address_name(self.address) {
+			write!(f, " ; {name}")?;
+		}
 
 		Ok(())
 	}
@@ -76,6 +101,12 @@ impl Display for CallInstruction {
 mod tests {
 	use crate::hardware::cpu::Cpu;
 	use crate::hardware::ram::WORKING_RAM_START;
+	use crate::instructions::base::double_byte::DoubleByteSource;
+	use crate::instructions::flow::{JumpInstruction, JumpInstructionDestination};
+	use crate::instructions::load::double_byte_load::PushInstruction;
+	use crate::instructions::Executable;
+
+	use crate::instructions::changeset::ChangeKind;
 
 	use super::*;
 
@@ -94,17 +125,24 @@ mod tests {
 
 		let actual = instruction.compute_change(&cpu).unwrap();
 		let expected = ChangeList::new(vec![
-			Box::new(SpChange::new(WORKING_RAM_START + 8)),
-			Box::new(MemoryDoubleByteWriteChange::write_to_source(
-				DoubleByteSource::StackPointer,
+			ChangeKind::from(SpChange::new(WORKING_RAM_START + 8)),
+			ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(
+				WORKING_RAM_START + 8,
 				0x1234,
 			)),
-			Box::new(PcChange::new(0x4321)),
+			ChangeKind::from(PcChange::new(0x4321)),
 		]);
 
 		assert_eq!(actual, expected);
 	}
 
+	#[test]
+	fn display() {
+		assert_eq!(CallInstruction::call(0x4321).to_string(), "call 0x4321");
+		assert_eq!(CallInstruction::call(0xFF80).to_string(), "call 0xFF80 ; HRAM");
+		assert_eq!(CallInstruction::restart(5).to_string(), "rst 0x28");
+	}
+
 	#[test]
 	fn failed_call() {
 		let cpu = get_cpu();
@@ -116,4 +154,23 @@ mod tests {
 
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn call_matches_push_and_jump() {
+		let mut actual = get_cpu();
+		CallInstruction::call(0x4321).execute(&mut actual).unwrap();
+
+		let mut expected = get_cpu();
+		PushInstruction::new(DoubleByteSource::Immediate(0x1234))
+			.execute(&mut expected)
+			.unwrap();
+		JumpInstruction::new(
+			JumpInstructionDestination::FromSource(DoubleByteSource::Immediate(0x4321)),
+			BranchCondition::Unconditional,
+		)
+		.execute(&mut expected)
+		.unwrap();
+
+		assert_eq!(actual, expected);
+	}
 }
@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::cpu::Cpu;
 use crate::hardware::ram::Rom;
 use crate::hardware::register_bank::BitFlags;
-use crate::instructions::changeset::{Change, ChangeIme, ChangeList, ChangesetExecutable, PcChange, SpChange};
+use crate::instructions::changeset::{CallStackChange, ChangeIme, ChangeList, ChangesetExecutable, PcChange, SpChange};
 use crate::instructions::flow::BranchCondition;
 use crate::instructions::ExecutionError;
 
@@ -50,20 +50,27 @@ impl ChangesetExecutable for ReturnInstruction {
 	type C = ChangeList;
 
 	fn compute_change(&self, cpu: &Cpu) -> Result<Self::C, ExecutionError> {
-		let mut changes: Vec<Box<dyn Change>> = Vec::new();
+		let mut changes = ChangeList::default();
 		if self.condition.satisfied(cpu) {
 			let sp_value = cpu.sp.read();
 			let address = cpu.mapped_ram.read_double_byte(sp_value)?;
 
-			changes.push(Box::new(PcChange::new(address)));
-			changes.push(Box::new(SpChange::new(sp_value + 2)));
+			changes.push(PcChange::new(address));
+			changes.push(SpChange::new(sp_value + 2));
 
 			if self.enable_interrupts {
-				changes.push(Box::new(ChangeIme::new(true)));
+				changes.push(ChangeIme::new(true));
+			}
+			if cpu.call_stack.is_some() {
+				changes.push(CallStackChange::Return(sp_value));
 			}
 		}
 
-		Ok(ChangeList::new(changes))
+		Ok(changes)
+	}
+
+	fn branch_taken(&self, cpu: &Cpu) -> bool {
+		self.condition.satisfied(cpu)
 	}
 }
 
@@ -72,7 +79,7 @@ mod tests {
 	use crate::hardware::cpu::Cpu;
 	use crate::hardware::ram::{Ram, WORKING_RAM_START};
 	use crate::hardware::register_bank::BitFlags;
-	use crate::instructions::changeset::{ChangeIme, ChangeList, ChangesetExecutable, PcChange, SpChange};
+	use crate::instructions::changeset::{ChangeIme, ChangeKind, ChangeList, ChangesetExecutable, PcChange, SpChange};
 	use crate::instructions::flow::{BranchCondition, ReturnInstruction};
 
 	fn get_cpu() -> Cpu {
@@ -93,8 +100,8 @@ mod tests {
 
 		let actual = instruction.compute_change(&cpu).unwrap();
 		let expected = ChangeList::new(vec![
-			Box::new(PcChange::new(0x4321)),
-			Box::new(SpChange::new(WORKING_RAM_START + 12)),
+			ChangeKind::from(PcChange::new(0x4321)),
+			ChangeKind::from(SpChange::new(WORKING_RAM_START + 12)),
 		]);
 
 		assert_eq!(actual, expected);
@@ -114,9 +121,9 @@ mod tests {
 
 		let actual = instruction.compute_change(&cpu).unwrap();
 		let expected = ChangeList::new(vec![
-			Box::new(PcChange::new(0x4321)),
-			Box::new(SpChange::new(WORKING_RAM_START + 12)),
-			Box::new(ChangeIme::new(true)),
+			ChangeKind::from(PcChange::new(0x4321)),
+			ChangeKind::from(SpChange::new(WORKING_RAM_START + 12)),
+			ChangeKind::from(ChangeIme::new(true)),
 		]);
 
 		assert_eq!(actual, expected);
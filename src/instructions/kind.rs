@@ -0,0 +1,105 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::decoder::hooks::HookInstruction;
+use crate::hardware::cpu::Cpu;
+use crate::instructions::arithmetic::add_or_sub::BinaryArithmeticInstruction;
+use crate::instructions::arithmetic::bcd::DecimalAdjust;
+use crate::instructions::arithmetic::compare::CompareInstruction;
+use crate::instructions::arithmetic::inc_or_dec::IncOrDecByteInstruction;
+use crate::instructions::control::{HaltInstruction, NopInstruction, SetImeInstruction, StopInstruction};
+use crate::instructions::double_arithmetic::{
+	AddSignedByteToDoubleByte, BinaryDoubleByteAddInstruction, IncOrDecDoubleInstruction,
+};
+use crate::instructions::flags::ChangeCarryFlagInstruction;
+use crate::instructions::flow::{CallInstruction, JumpInstruction, ReturnInstruction};
+use crate::instructions::load::byte_load::ByteLoadInstruction;
+use crate::instructions::load::double_byte_load::{DoubleByteLoadInstruction, PopInstruction, PushInstruction};
+use crate::instructions::logical::{BinaryLogicalInstruction, LogicalNegateInstruction};
+use crate::instructions::shifting::{ByteShiftInstruction, ByteSwapInstruction};
+use crate::instructions::single_bit::SingleBitInstruction;
+use crate::instructions::{Executable, ExecutionError, Instruction};
+
+// Every instruction type the decoder produces. Executing matches on the variant instead of going through a boxed trait
+// object, so decoding doesn't allocate and the calls can be inlined.
+macro_rules! instruction_kinds {
+	($($variant:ident($instruction:ty)),* $(,)?) => {
+		pub(crate) enum InstructionKind {
+			$($variant($instruction),)*
+		}
+
+		$(impl From<$instruction> for InstructionKind {
+			fn from(instruction: $instruction) -> Self {
+				Self::$variant(instruction)
+			}
+		})*
+
+		impl InstructionKind {
+			pub(crate) fn as_dyn(&self) -> &dyn Instruction {
+				match self {
+					$(Self::$variant(instruction) => instruction,)*
+				}
+			}
+
+			pub(crate) fn into_boxed(self) -> Box<dyn Instruction> {
+				match self {
+					$(Self::$variant(instruction) => Box::new(instruction),)*
+				}
+			}
+		}
+
+		impl Executable for InstructionKind {
+			fn execute(&self, cpu: &mut Cpu) -> Result<(), ExecutionError> {
+				match self {
+					$(Self::$variant(instruction) => instruction.execute(cpu),)*
+				}
+			}
+
+			fn branch_taken(&self, cpu: &Cpu) -> bool {
+				match self {
+					$(Self::$variant(instruction) => instruction.branch_taken(cpu),)*
+				}
+			}
+		}
+	};
+}
+
+instruction_kinds! {
+	ByteLoad(ByteLoadInstruction),
+	DoubleByteLoad(DoubleByteLoadInstruction),
+	Push(PushInstruction),
+	Pop(PopInstruction),
+	Arithmetic(BinaryArithmeticInstruction),
+	Compare(CompareInstruction),
+	IncOrDecByte(IncOrDecByteInstruction),
+	DecimalAdjust(DecimalAdjust),
+	DoubleByteAdd(BinaryDoubleByteAddInstruction),
+	IncOrDecDouble(IncOrDecDoubleInstruction),
+	AddSignedByte(AddSignedByteToDoubleByte),
+	Logical(BinaryLogicalInstruction),
+	Negate(LogicalNegateInstruction),
+	Shift(ByteShiftInstruction),
+	Swap(ByteSwapInstruction),
+	SingleBit(SingleBitInstruction),
+	ChangeCarryFlag(ChangeCarryFlagInstruction),
+	Jump(JumpInstruction),
+	Call(CallInstruction),
+	Return(ReturnInstruction),
+	Nop(NopInstruction),
+	Stop(StopInstruction),
+	Halt(HaltInstruction),
+	SetIme(SetImeInstruction),
+	Hook(HookInstruction),
+}
+
+// Shown as the wrapped instruction, the variant adds nothing
+impl Debug for InstructionKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Debug::fmt(self.as_dyn(), f)
+	}
+}
+
+impl Display for InstructionKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(self.as_dyn(), f)
+	}
+}
@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::cpu::Cpu;
 use crate::hardware::register_bank::DoubleRegisters;
 use crate::instructions::base::byte::{ByteDestination, ByteSource, UnaryByteInstruction, UnaryByteOperation};
-use crate::instructions::changeset::{ChangeList, DoubleRegisterChange};
+use crate::instructions::changeset::{ChangeKind, ChangePair, DoubleRegisterChange, NoChange};
 use crate::instructions::shared::IndexUpdateType;
 use crate::instructions::ExecutionError;
 
@@ -53,17 +53,16 @@ impl ByteLoadOperation {
 }
 
 impl UnaryByteOperation for ByteLoadOperation {
-	type C = ChangeList;
+	type C = ChangePair<ChangeKind, ChangeKind>;
 
 	fn execute(&self, cpu: &Cpu, src: &ByteSource, dst: &ByteDestination) -> Result<Self::C, ExecutionError> {
 		let value = src.read(cpu)?;
-		let mut changes = vec![dst.change_destination(value)];
+		let update = match self.update {
+			Some(update) => ChangeKind::from(update.compute_change(cpu)),
+			None => ChangeKind::from(NoChange::new()),
+		};
 
-		if let Some(update) = self.update {
-			changes.push(Box::new(update.compute_change(cpu)));
-		}
-
-		Ok(ChangeList::new(changes))
+		Ok(ChangePair::new(dst.change_destination(value), update))
 	}
 }
 
@@ -90,7 +89,9 @@ impl ByteLoadInstruction {
 mod tests {
 	use crate::hardware::ram::{Ram, WORKING_RAM_START};
 	use crate::hardware::register_bank::SingleRegisters;
-	use crate::instructions::changeset::{ChangesetExecutable, MemoryByteWriteChange, SingleRegisterChange};
+	use crate::instructions::changeset::{
+		ChangeKind, ChangesetExecutable, MemoryByteWriteChange, SingleRegisterChange,
+	};
 	use crate::instructions::ACC_REGISTER;
 
 	use super::*;
@@ -105,7 +106,10 @@ mod tests {
 			ByteDestination::write_to_acc(),
 		);
 
-		let expected = ChangeList::new(vec![Box::new(SingleRegisterChange::new(ACC_REGISTER, 0x80))]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x80)),
+			ChangeKind::from(NoChange::new()),
+		);
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
 		assert_eq!(actual, expected);
@@ -126,10 +130,10 @@ mod tests {
 			ByteLoadOperation::with_update(ByteLoadUpdate::new(DoubleRegisters::HL, IndexUpdateType::Increment)),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0x80)),
-			Box::new(DoubleRegisterChange::new(DoubleRegisters::HL, WORKING_RAM_START + 1)),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0x80)),
+			ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, WORKING_RAM_START + 1)),
+		);
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
 		assert_eq!(actual, expected);
@@ -148,10 +152,10 @@ mod tests {
 			ByteLoadOperation::with_update(ByteLoadUpdate::new(DoubleRegisters::HL, IndexUpdateType::Decrement)),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(MemoryByteWriteChange::write_to_register(DoubleRegisters::HL, 0x80)),
-			Box::new(DoubleRegisterChange::new(DoubleRegisters::HL, WORKING_RAM_START)),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(MemoryByteWriteChange::write_to_register(DoubleRegisters::HL, 0x80)),
+			ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::HL, WORKING_RAM_START)),
+		);
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
 		assert_eq!(actual, expected);
@@ -5,7 +5,9 @@ use crate::hardware::ram::Rom;
 use crate::instructions::base::double_byte::{
 	DoubleByteDestination, DoubleByteSource, UnaryDoubleByteInstruction, UnaryDoubleByteOperation,
 };
-use crate::instructions::changeset::{Change, ChangeList, ChangesetExecutable, MemoryDoubleByteWriteChange, SpChange};
+use crate::instructions::changeset::{
+	ChangeKind, ChangeList, ChangesetExecutable, MemoryDoubleByteWriteChange, SpChange,
+};
 use crate::instructions::ExecutionError;
 
 #[derive(Debug)]
@@ -18,7 +20,7 @@ impl DoubleByteLoadOperation {
 }
 
 impl UnaryDoubleByteOperation for DoubleByteLoadOperation {
-	type C = Box<dyn Change>;
+	type C = ChangeKind;
 
 	fn execute(
 		&self,
@@ -58,9 +60,9 @@ impl ChangesetExecutable for PushInstruction {
 		let address = address.wrapping_sub(2);
 		let value = self.source.read(cpu)?;
 
-		Ok(ChangeList::new(vec![
-			Box::new(SpChange::new(address)),
-			Box::new(MemoryDoubleByteWriteChange::write_to_immediate(address, value)),
+		Ok(ChangeList::new([
+			ChangeKind::from(SpChange::new(address)),
+			ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(address, value)),
 		]))
 	}
 }
@@ -90,9 +92,9 @@ impl ChangesetExecutable for PopInstruction {
 		let value = cpu.mapped_ram.read_double_byte(address)?;
 		let address = address.wrapping_add(2);
 
-		Ok(ChangeList::new(vec![
+		Ok(ChangeList::new([
 			self.destination.change_destination(value),
-			Box::new(SpChange::new(address)),
+			ChangeKind::from(SpChange::new(address)),
 		]))
 	}
 }
@@ -110,7 +112,7 @@ mod tests {
 	use crate::hardware::register_bank::DoubleRegisters;
 	use crate::instructions::base::double_byte::{DoubleByteDestination, DoubleByteSource};
 	use crate::instructions::changeset::{
-		Change, ChangeList, ChangesetExecutable, DoubleRegisterChange, MemoryDoubleByteWriteChange, SpChange,
+		ChangeKind, ChangeList, ChangesetExecutable, DoubleRegisterChange, MemoryDoubleByteWriteChange, SpChange,
 	};
 	use crate::instructions::load::double_byte_load::{
 		DoubleByteLoadInstruction, DoubleByteLoadOperation, PopInstruction, PushInstruction,
@@ -127,7 +129,7 @@ mod tests {
 			DoubleByteLoadOperation::new(),
 		);
 
-		let expected: Box<dyn Change> = Box::new(MemoryDoubleByteWriteChange::write_to_immediate(
+		let expected = ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(
 			WORKING_RAM_START,
 			0x1234,
 		));
@@ -145,8 +147,8 @@ mod tests {
 		let instruction = PushInstruction::new(DoubleByteSource::DoubleRegister(DoubleRegisters::BC));
 
 		let expected = ChangeList::new(vec![
-			Box::new(SpChange::new(WORKING_RAM_START)),
-			Box::new(MemoryDoubleByteWriteChange::write_to_immediate(
+			ChangeKind::from(SpChange::new(WORKING_RAM_START)),
+			ChangeKind::from(MemoryDoubleByteWriteChange::write_to_immediate(
 				WORKING_RAM_START,
 				0x1234,
 			)),
@@ -167,8 +169,8 @@ mod tests {
 		let instruction = PopInstruction::new(DoubleByteDestination::DoubleRegister(DoubleRegisters::BC));
 
 		let expected = ChangeList::new(vec![
-			Box::new(DoubleRegisterChange::new(DoubleRegisters::BC, 0x1234)),
-			Box::new(SpChange::new(WORKING_RAM_START + 2)),
+			ChangeKind::from(DoubleRegisterChange::new(DoubleRegisters::BC, 0x1234)),
+			ChangeKind::from(SpChange::new(WORKING_RAM_START + 2)),
 		]);
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
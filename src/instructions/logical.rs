@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::cpu::Cpu;
 use crate::instructions::base::byte::{BinaryByteInstruction, UnaryByteInstruction, UnaryByteOperation};
 use crate::instructions::base::byte::{BinaryByteOperation, ByteDestination, ByteSource};
-use crate::instructions::changeset::{BitFlagsChange, ChangeList};
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind, ChangePair};
 use crate::instructions::ExecutionError;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -47,7 +47,7 @@ impl BinaryLogicalOperation {
 }
 
 impl BinaryByteOperation for BinaryLogicalOperation {
-	type C = ChangeList;
+	type C = ChangePair<ChangeKind, BitFlagsChange>;
 
 	fn compute_changes(
 		&self,
@@ -60,14 +60,12 @@ impl BinaryByteOperation for BinaryLogicalOperation {
 		let right_value = right.read(cpu)?;
 		let result = self.type_.compute(left_value, right_value);
 
-		Ok(ChangeList::new(vec![
+		Ok(ChangePair::new(
 			dst.change_destination(result),
-			Box::new(
-				BitFlagsChange::zero_all()
-					.with_zero_flag(result == 0)
-					.with_half_carry_flag(self.type_.is_and()),
-			),
-		]))
+			BitFlagsChange::zero_all()
+				.with_zero_flag(result == 0)
+				.with_half_carry_flag(self.type_.is_and()),
+		))
 	}
 }
 
@@ -83,20 +81,18 @@ pub(crate) type BinaryLogicalInstruction = BinaryByteInstruction<BinaryLogicalOp
 pub(crate) struct LogicalNegateOperation;
 
 impl UnaryByteOperation for LogicalNegateOperation {
-	type C = ChangeList;
+	type C = ChangePair<ChangeKind, BitFlagsChange>;
 
 	fn execute(&self, cpu: &Cpu, src: &ByteSource, dst: &ByteDestination) -> Result<Self::C, ExecutionError> {
 		let value = src.read(cpu)?;
 		let new_value = !value;
 
-		Ok(ChangeList::new(vec![
+		Ok(ChangePair::new(
 			dst.change_destination(new_value),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_subtraction_flag(true)
-					.with_half_carry_flag(true),
-			),
-		]))
+			BitFlagsChange::keep_all()
+				.with_subtraction_flag(true)
+				.with_half_carry_flag(true),
+		))
 	}
 }
 
@@ -134,14 +130,12 @@ mod tests {
 		let instruction = LogicalNegateInstruction::negate_acc();
 
 		let actual = instruction.compute_change(&cpu).unwrap();
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, !0b11001010)),
-			Box::new(
-				BitFlagsChange::keep_all()
-					.with_subtraction_flag(true)
-					.with_half_carry_flag(true),
-			),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, !0b11001010)),
+			BitFlagsChange::keep_all()
+				.with_subtraction_flag(true)
+				.with_half_carry_flag(true),
+		);
 
 		assert_eq!(actual, expected);
 	}
@@ -160,10 +154,10 @@ mod tests {
 		);
 
 		let actual = instruction.compute_change(&cpu).unwrap();
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b10001000)),
-			Box::new(BitFlagsChange::zero_all().with_half_carry_flag(true)),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b10001000)),
+			BitFlagsChange::zero_all().with_half_carry_flag(true),
+		);
 
 		assert_eq!(actual, expected);
 	}
@@ -182,10 +176,10 @@ mod tests {
 		);
 
 		let actual = instruction.compute_change(&cpu).unwrap();
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b11101110)),
-			Box::new(BitFlagsChange::zero_all()),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b11101110)),
+			BitFlagsChange::zero_all(),
+		);
 
 		assert_eq!(actual, expected);
 	}
@@ -204,10 +198,10 @@ mod tests {
 		);
 
 		let actual = instruction.compute_change(&cpu).unwrap();
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0110_0110)),
-			Box::new(BitFlagsChange::zero_all()),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0110_0110)),
+			BitFlagsChange::zero_all(),
+		);
 
 		assert_eq!(actual, expected);
 	}
@@ -1,5 +1,5 @@
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum IndexUpdateType {
+pub(crate) enum IndexUpdateType {
 	Increment,
 	Decrement,
 }
@@ -3,14 +3,14 @@ use std::fmt::{Display, Formatter};
 use crate::hardware::cpu::Cpu;
 use crate::hardware::register_bank::BitFlags;
 use crate::instructions::base::byte::{ByteDestination, ByteSource, UnaryByteInstruction, UnaryByteOperation};
-use crate::instructions::changeset::{Change, ChangeList};
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind, ChangePair};
 use crate::instructions::shifting::operation::ByteShiftOperation;
 use crate::instructions::ExecutionError;
 
 pub(crate) mod operation;
 
 impl UnaryByteOperation for ByteShiftOperation {
-	type C = ChangeList;
+	type C = ChangePair<ChangeKind, BitFlagsChange>;
 
 	fn execute(&self, cpu: &Cpu, src: &ByteSource, dst: &ByteDestination) -> Result<Self::C, ExecutionError> {
 		let value = src.read(cpu)?;
@@ -33,7 +33,7 @@ impl ByteSwapOperation {
 }
 
 impl UnaryByteOperation for ByteSwapOperation {
-	type C = Box<dyn Change>;
+	type C = ChangeKind;
 
 	fn execute(&self, cpu: &Cpu, src: &ByteSource, dst: &ByteDestination) -> Result<Self::C, ExecutionError> {
 		let byte = src.read(cpu)?;
@@ -47,7 +47,7 @@ impl UnaryByteOperation for ByteSwapOperation {
 
 impl Display for ByteSwapOperation {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		write!(f, "SWAP")
+		write!(f, "swap")
 	}
 }
 
@@ -74,10 +74,10 @@ mod tests {
 			ByteShiftOperation::new(ShiftDirection::Left, ShiftType::RotateWithCarry),
 		);
 
-		let expected = ChangeList::new(vec![
-			Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0110_1011)),
-			Box::new(BitFlagsChange::zero_all().with_carry_flag(false).with_zero_flag(false)),
-		]);
+		let expected = ChangePair::new(
+			ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0110_1011)),
+			BitFlagsChange::zero_all().with_carry_flag(false).with_zero_flag(false),
+		);
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
 
 		assert_eq!(actual, expected);
@@ -98,7 +98,7 @@ mod tests {
 			)
 			.expect("Operation to execute");
 
-		let expected: Box<dyn Change> = Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0101_0011));
+		let expected = ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0101_0011));
 
 		assert_eq!(actual, expected);
 	}
@@ -1,11 +1,10 @@
 use std::fmt::{Display, Formatter};
 
 use crate::instructions::base::byte::ByteDestination;
-use crate::instructions::changeset::{BitFlagsChange, ChangeList};
-use crate::instructions::ACC_REGISTER;
+use crate::instructions::changeset::{BitFlagsChange, ChangeKind, ChangePair};
 
 #[derive(Debug, Copy, Clone)]
-pub enum ShiftDirection {
+pub(crate) enum ShiftDirection {
 	Left,
 	Right,
 }
@@ -20,7 +19,7 @@ impl ShiftDirection {
 }
 
 #[derive(Debug, Copy, Clone)]
-pub enum ShiftType {
+pub(crate) enum ShiftType {
 	Rotate,
 	RotateWithCarry,
 	LogicalShift,
@@ -28,14 +27,27 @@ pub enum ShiftType {
 }
 
 #[derive(Debug)]
-pub struct ByteShiftOperation {
+pub(crate) struct ByteShiftOperation {
 	direction: ShiftDirection,
 	type_: ShiftType,
+	clear_zero_flag: bool,
 }
 
 impl ByteShiftOperation {
 	pub(crate) fn new(direction: ShiftDirection, type_: ShiftType) -> Self {
-		Self { direction, type_ }
+		Self {
+			direction,
+			type_,
+			clear_zero_flag: false,
+		}
+	}
+
+	/// RLCA, RRCA, RLA and RRA, which always reset the zero flag unlike their prefixed versions
+	pub(crate) fn accumulator(direction: ShiftDirection, type_: ShiftType) -> Self {
+		Self {
+			clear_zero_flag: true,
+			..Self::new(direction, type_)
+		}
 	}
 
 	fn shift_result(&self, value: u8) -> (u8, bool) {
@@ -60,15 +72,13 @@ impl ByteShiftOperation {
 		)
 	}
 
-	fn zero_flag_for_result(&self, destination: &ByteDestination, result: u8) -> bool {
-		match destination {
-			ByteDestination::SingleRegister(ACC_REGISTER) => false,
-			_ => result == 0,
-		}
-	}
-
-	pub(super) fn compute_changes(&self, value: u8, old_carry: bool, dst: &ByteDestination) -> ChangeList {
-		let old_sign = value & 80 != 0;
+	pub(super) fn compute_changes(
+		&self,
+		value: u8,
+		old_carry: bool,
+		dst: &ByteDestination,
+	) -> ChangePair<ChangeKind, BitFlagsChange> {
+		let old_sign = value & 0x80 != 0;
 		let (mut result, shifted_out) = self.shift_result(value);
 
 		let shift_in_bit = self.shift_in(shifted_out, old_carry).unwrap_or(false);
@@ -81,14 +91,14 @@ impl ByteShiftOperation {
 		}
 
 		let new_carry = shifted_out;
-		let new_zero = self.zero_flag_for_result(dst, result);
+		let new_zero = !self.clear_zero_flag && result == 0;
 
 		let result_change = dst.change_destination(result);
 		let bit_flags_change = BitFlagsChange::zero_all()
 			.with_carry_flag(new_carry)
 			.with_zero_flag(new_zero);
 
-		ChangeList::new(vec![result_change, Box::new(bit_flags_change)])
+		ChangePair::new(result_change, bit_flags_change)
 	}
 }
 
@@ -120,21 +130,22 @@ impl Display for ByteShiftOperation {
 mod tests {
 	use crate::hardware::register_bank::SingleRegisters;
 	use crate::instructions::changeset::SingleRegisterChange;
+	use crate::instructions::ACC_REGISTER;
 
 	use super::*;
 
 	#[test]
 	fn zero_flag() {
 		assert_eq!(
-			ByteShiftOperation::new(ShiftDirection::Right, ShiftType::Rotate).compute_changes(
+			ByteShiftOperation::accumulator(ShiftDirection::Right, ShiftType::Rotate).compute_changes(
 				0,
 				false,
 				&ByteDestination::write_to_acc(),
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)
+			)
 		);
 
 		assert_eq!(
@@ -143,10 +154,10 @@ mod tests {
 				false,
 				&ByteDestination::SingleRegister(SingleRegisters::B),
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(SingleRegisters::B, 0)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(true).with_carry_flag(false)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(SingleRegisters::B, 0)),
+				BitFlagsChange::zero_all().with_zero_flag(true).with_carry_flag(false)
+			)
 		);
 	}
 
@@ -158,10 +169,10 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc(),
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0110_0101)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0110_0101)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)
+			)
 		);
 
 		assert_eq!(
@@ -170,10 +181,10 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc(),
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b1001_0101)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b1001_0101)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)
+			)
 		);
 	}
 
@@ -185,10 +196,10 @@ mod tests {
 				true,
 				&ByteDestination::write_to_acc()
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b1001_1101)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b1001_1101)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)
+			)
 		);
 
 		assert_eq!(
@@ -197,10 +208,10 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc()
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0011_1010)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0011_1010)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)
+			)
 		);
 	}
 
@@ -212,10 +223,10 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc()
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0001_1101)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0001_1101)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)
+			)
 		);
 
 		assert_eq!(
@@ -224,10 +235,10 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc(),
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0011_1010)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0011_1010)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)
+			)
 		);
 	}
 
@@ -239,10 +250,10 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc()
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b1110_0101)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b1110_0101)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(false)
+			)
 		);
 
 		assert_eq!(
@@ -251,10 +262,62 @@ mod tests {
 				false,
 				&ByteDestination::write_to_acc()
 			),
-			ChangeList::new(vec![
-				Box::new(SingleRegisterChange::new(ACC_REGISTER, 0b0001_1010)),
-				Box::new(BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)),
-			])
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0b0001_1010)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(true)
+			)
 		);
 	}
+
+	#[test]
+	fn prefixed_accumulator_sets_zero_flag() {
+		assert_eq!(
+			ByteShiftOperation::new(ShiftDirection::Left, ShiftType::RotateWithCarry).compute_changes(
+				0b1000_0000,
+				false,
+				&ByteDestination::write_to_acc(),
+			),
+			ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(ACC_REGISTER, 0)),
+				BitFlagsChange::zero_all().with_zero_flag(true).with_carry_flag(true)
+			)
+		);
+	}
+
+	#[test]
+	fn shifted_out_bit_differs_from_old_carry() {
+		use ShiftDirection::{Left, Right};
+		use ShiftType::{ArithmeticShift, LogicalShift, Rotate, RotateWithCarry};
+
+		// (direction, type, value, old carry, result, new carry), following the Pan Docs definitions
+		let cases = [
+			(Left, Rotate, 0b1000_0010, false, 0b0000_0101, true),
+			(Left, Rotate, 0b0100_0001, true, 0b1000_0010, false),
+			(Right, Rotate, 0b0000_0011, false, 0b1000_0001, true),
+			(Right, Rotate, 0b1000_0010, true, 0b0100_0001, false),
+			(Left, RotateWithCarry, 0b1000_0010, false, 0b0000_0100, true),
+			(Left, RotateWithCarry, 0b0100_0001, true, 0b1000_0011, false),
+			(Right, RotateWithCarry, 0b0000_0011, false, 0b0000_0001, true),
+			(Right, RotateWithCarry, 0b1000_0010, true, 0b1100_0001, false),
+			(Left, ArithmeticShift, 0b1000_0011, false, 0b0000_0110, true),
+			(Left, ArithmeticShift, 0b0100_0001, true, 0b1000_0010, false),
+			(Right, ArithmeticShift, 0b1000_0011, false, 0b1100_0001, true),
+			(Right, ArithmeticShift, 0b0101_0010, true, 0b0010_1001, false),
+			(Right, LogicalShift, 0b1000_0011, false, 0b0100_0001, true),
+			(Right, LogicalShift, 0b0100_0010, true, 0b0010_0001, false),
+		];
+
+		for (direction, type_, value, old_carry, result, carry) in cases {
+			let operation = ByteShiftOperation::new(direction, type_);
+
+			let actual =
+				operation.compute_changes(value, old_carry, &ByteDestination::SingleRegister(SingleRegisters::B));
+			let expected = ChangePair::new(
+				ChangeKind::from(SingleRegisterChange::new(SingleRegisters::B, result)),
+				BitFlagsChange::zero_all().with_zero_flag(false).with_carry_flag(carry),
+			);
+
+			assert_eq!(actual, expected, "{operation} {value:#010b}, carry {old_carry}");
+		}
+	}
 }
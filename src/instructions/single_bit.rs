@@ -4,7 +4,7 @@ use crate::hardware::cpu::Cpu;
 use crate::hardware::ram::Rom;
 use crate::hardware::register_bank::{DoubleRegisters, SingleRegisters};
 use crate::instructions::changeset::{
-	BitFlagsChange, Change, ChangesetExecutable, MemoryByteWriteChange, SingleRegisterChange,
+	BitFlagsChange, ChangeKind, ChangesetExecutable, MemoryByteWriteChange, SingleRegisterChange,
 };
 use crate::instructions::ExecutionError;
 
@@ -35,10 +35,12 @@ impl SingleBitOperand {
 		}
 	}
 
-	fn write_change(&self, byte: u8) -> Box<dyn Change> {
+	fn write_change(&self, byte: u8) -> ChangeKind {
 		match self {
-			Self::SingleRegister(reg) => Box::new(SingleRegisterChange::new(*reg, byte)),
-			Self::MemoryAddress => Box::new(MemoryByteWriteChange::write_to_register(DoubleRegisters::HL, byte)),
+			Self::SingleRegister(reg) => ChangeKind::from(SingleRegisterChange::new(*reg, byte)),
+			Self::MemoryAddress => {
+				ChangeKind::from(MemoryByteWriteChange::write_to_register(DoubleRegisters::HL, byte))
+			}
 		}
 	}
 }
@@ -67,7 +69,7 @@ impl Display for SingleBitOperation {
 }
 
 impl SingleBitOperation {
-	fn compute_change(&self, byte: u8, bitmask: u8, operand: &SingleBitOperand) -> Box<dyn Change> {
+	fn compute_change(&self, byte: u8, bitmask: u8, operand: &SingleBitOperand) -> ChangeKind {
 		match self {
 			Self::Test => {
 				// Since we're setting the zero flag, the zero flag is set (zero == true) if the bit is zero
@@ -78,7 +80,7 @@ impl SingleBitOperation {
 					.with_subtraction_flag(false)
 					.with_half_carry_flag(true);
 
-				Box::new(flags_change)
+				ChangeKind::from(flags_change)
 			}
 			Self::Write(bit) => {
 				let result = if *bit { byte | bitmask } else { byte & (!bitmask) };
@@ -116,7 +118,7 @@ impl Display for SingleBitInstruction {
 }
 
 impl ChangesetExecutable for SingleBitInstruction {
-	type C = Box<dyn Change>;
+	type C = ChangeKind;
 
 	fn compute_change(&self, cpu: &Cpu) -> Result<Self::C, ExecutionError> {
 		let byte = self.operand.read_byte(cpu)?;
@@ -152,7 +154,7 @@ mod tests {
 		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
-		let expected: Box<dyn Change> = Box::new(
+		let expected = ChangeKind::from(
 			BitFlagsChange::keep_all()
 				.with_zero_flag(false)
 				.with_subtraction_flag(false)
@@ -164,7 +166,7 @@ mod tests {
 		let instruction = SingleBitInstruction::new(SingleBitOperand::MemoryAddress, SingleBitOperation::Test, 4);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
-		let expected: Box<dyn Change> = Box::new(
+		let expected = ChangeKind::from(
 			BitFlagsChange::keep_all()
 				.with_zero_flag(true)
 				.with_subtraction_flag(false)
@@ -194,7 +196,7 @@ mod tests {
 		);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
-		let expected: Box<dyn Change> = Box::new(SingleRegisterChange::new(SingleRegisters::B, 0b11001011));
+		let expected = ChangeKind::from(SingleRegisterChange::new(SingleRegisters::B, 0b11001011));
 
 		assert_eq!(actual, expected);
 
@@ -202,11 +204,104 @@ mod tests {
 			SingleBitInstruction::new(SingleBitOperand::MemoryAddress, SingleBitOperation::Write(false), 7);
 
 		let actual = instruction.compute_change(&cpu).expect("Compute changes");
-		let expected: Box<dyn Change> = Box::new(MemoryByteWriteChange::write_to_register(
+		let expected = ChangeKind::from(MemoryByteWriteChange::write_to_register(
 			DoubleRegisters::HL,
 			0b01001010,
 		));
 
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn test_every_bit() {
+		let operands = [
+			SingleBitOperand::SingleRegister(SingleRegisters::C),
+			SingleBitOperand::MemoryAddress,
+		];
+
+		for operand in operands {
+			for bit_shift in 0..8 {
+				for bit_set in [false, true] {
+					// Every other bit is the opposite of the one being tested, so only the right bit can match
+					let byte = if bit_set { 1 << bit_shift } else { !(1 << bit_shift) };
+
+					let mut cpu = Cpu::new();
+					cpu.register_bank.write_single_named(SingleRegisters::C, byte);
+					cpu.register_bank
+						.write_double_named(DoubleRegisters::HL, WORKING_RAM_START);
+					cpu.mapped_ram
+						.write_byte(WORKING_RAM_START, byte)
+						.expect("Write to RAM");
+
+					let instruction = SingleBitInstruction::new(operand, SingleBitOperation::Test, bit_shift);
+
+					let actual = instruction.compute_change(&cpu).expect("Compute changes");
+					// Built on top of keep_all, so the carry flag is left untouched
+					let expected = ChangeKind::from(
+						BitFlagsChange::keep_all()
+							.with_zero_flag(!bit_set)
+							.with_subtraction_flag(false)
+							.with_half_carry_flag(true),
+					);
+
+					assert_eq!(actual, expected, "bit {bit_shift}, {operand} = {byte:#010b}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn write_every_bit_leaves_flags() {
+		let operands = [
+			SingleBitOperand::SingleRegister(SingleRegisters::C),
+			SingleBitOperand::MemoryAddress,
+		];
+
+		for operand in operands {
+			for bit_shift in 0..8 {
+				for bit in [false, true] {
+					let byte = 0b10100101;
+
+					let mut cpu = Cpu::new();
+					cpu.register_bank.write_single_named(SingleRegisters::C, byte);
+					cpu.register_bank
+						.write_double_named(DoubleRegisters::HL, WORKING_RAM_START);
+					cpu.mapped_ram
+						.write_byte(WORKING_RAM_START, byte)
+						.expect("Write to RAM");
+
+					let instruction = SingleBitInstruction::new(operand, SingleBitOperation::Write(bit), bit_shift);
+
+					let result = if bit {
+						byte | (1 << bit_shift)
+					} else {
+						byte & !(1 << bit_shift)
+					};
+					let actual = instruction.compute_change(&cpu).expect("Compute changes");
+					// Only the operand is written, with no flag change at all
+					let expected = operand.write_change(result);
+
+					assert_eq!(actual, expected, "{instruction}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn display_masks_bit_shift() {
+		for bit_shift in 0..8 {
+			let instruction =
+				SingleBitInstruction::new(SingleBitOperand::MemoryAddress, SingleBitOperation::Test, bit_shift + 8);
+
+			assert_eq!(instruction.to_string(), format!("bit {bit_shift}, (HL)"));
+		}
+
+		let instruction = SingleBitInstruction::new(
+			SingleBitOperand::SingleRegister(SingleRegisters::A),
+			SingleBitOperation::Write(true),
+			0xFF,
+		);
+
+		assert_eq!(instruction.to_string(), "set 7, A");
+	}
 }
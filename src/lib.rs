@@ -1,4 +1,9 @@
-mod bits;
-pub mod decoder;
-pub mod hardware;
-pub mod instructions;
+pub(crate) mod coverage;
+pub(crate) mod debugger;
+pub(crate) mod decoder;
+pub(crate) mod doctor_log;
+pub(crate) mod encoder;
+pub(crate) mod hardware;
+pub(crate) mod host_clock;
+pub(crate) mod instructions;
+pub mod prelude;
@@ -1,65 +1,307 @@
 extern crate sdl2;
 
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 use std::time::Duration;
 
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::surface::Surface;
 
-use corrosion::decoder::fetch_and_decode;
-use corrosion::hardware::cpu::Cpu;
-use corrosion::instructions::ExecutionError;
+use corrosion::prelude::{
+	opcode_table, prefixed_opcode_table, Cpu, FramePacer, RunError, SystemClock, BOOTSTRAP_RAM_SIZE, CYCLES_PER_FRAME,
+};
 
-fn update_cpu(cpu: &mut Cpu) -> Result<(), ExecutionError> {
-	let instruction = fetch_and_decode(cpu)?;
-	instruction.execute(cpu)?;
-	Ok(())
+use crate::settings::{Settings, DEFAULT_SETTINGS_PATH};
+
+mod settings;
+
+// Which part of the frontend failed, SDL only reports a message
+#[derive(Debug)]
+enum FrontendError {
+	SdlInit(String),
+	Video(String),
+	Window(String),
+	Canvas(String),
+	EventPump(String),
+	Execution(RunError),
+}
+
+impl Display for FrontendError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::SdlInit(err) => write!(f, "Failed to initialize SDL: {err}"),
+			Self::Video(err) => write!(f, "Failed to initialize the video subsystem: {err}"),
+			Self::Window(err) => write!(f, "Failed to create the window: {err}"),
+			Self::Canvas(err) => write!(f, "Failed to create the canvas: {err}"),
+			Self::EventPump(err) => write!(f, "Failed to create the event pump: {err}"),
+			Self::Execution(err) => write!(f, "Emulation stopped: {err}"),
+		}
+	}
+}
+
+impl std::error::Error for FrontendError {}
+
+// Instructions dumped when emulation stops on an error
+const CRASH_TRACE_LENGTH: usize = 32;
+// Calls kept for the backtrace printed along with them
+const CALL_STACK_DEPTH: usize = 64;
+
+// Opcodes listed by --profile on exit
+const PROFILE_LENGTH: usize = 20;
+
+fn print_profile(cpu: &Cpu) {
+	let mnemonics: Vec<_> = opcode_table()
+		.iter()
+		.chain(prefixed_opcode_table().iter())
+		.map(|info| info.mnemonic)
+		.collect();
+	let mut counts: Vec<_> = cpu.opcode_counts().iter().copied().enumerate().collect();
+	counts.sort_by(|(_, left), (_, right)| right.cmp(left));
+
+	println!("{:>12}  opcode  mnemonic", "count");
+	for (index, count) in counts.into_iter().take(PROFILE_LENGTH).filter(|(_, count)| *count > 0) {
+		let opcode = match index {
+			0x000..=0x0FF => format!("{index:02X}"),
+			_ => format!("CB {:02X}", index - 0x100),
+		};
+		println!("{count:>12}  {opcode:<6}  {}", mnemonics[index]);
+	}
+}
+
+// Game Boy screen, scaled up by the window
+const SCREEN_WIDTH: u32 = 160;
+const SCREEN_HEIGHT: u32 = 144;
+const FRAMES_PER_SECOND: u32 = 60;
+
+// Window icon, a Game Boy drawn with two bits per pixel from the left: 0 is transparent, 1 to 3 are palette shades
+const ICON_SIZE: u32 = 32;
+#[rustfmt::skip]
+const ICON: [u64; ICON_SIZE as usize] = [
+	0x0000000000000000,
+	0x0001555555554000,
+	0x0005555555555000,
+	0x0005FFFFFFFF5000,
+	0x0005FFFFFFFF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FAAAAAAF5000,
+	0x0005FFFFFFFF5000,
+	0x0005FFFFFFFF5000,
+	0x0005555555555000,
+	0x0005555555555000,
+	0x000557D555555000,
+	0x000557D5555F5000,
+	0x00057FFD555F5000,
+	0x00057FFD57D55000,
+	0x000557D557D55000,
+	0x000557D555555000,
+	0x0005555555555000,
+	0x0005555555555000,
+	0x0005557D7D555000,
+	0x0005555555555000,
+	0x0005555555555000,
+	0x0005555555554000,
+	0x0005555555550000,
+	0x0001555555540000,
+	0x0000000000000000,
+];
+
+// RGBA bytes of the icon, row by row, in the shades of the configured palette
+fn icon_rgba(icon: &[u64; ICON_SIZE as usize], palette: &[u32; 4]) -> Vec<u8> {
+	icon.iter()
+		.flat_map(|row| (0..ICON_SIZE).map(move |x| (row >> (62 - 2 * x)) & 0b11))
+		.flat_map(|shade| match shade {
+			0 => [0; 4],
+			shade => {
+				let [_, red, green, blue] = palette[shade as usize].to_be_bytes();
+				[red, green, blue, 0xFF]
+			}
+		})
+		.collect()
+}
+
+fn settings_path() -> PathBuf {
+	let mut args = std::env::args().skip_while(|arg| arg != "--config");
+	args.nth(1).unwrap_or_else(|| DEFAULT_SETTINGS_PATH.to_string()).into()
+}
+
+fn boot_cpu(settings: &Settings) -> Result<Cpu, String> {
+	let path = match &settings.boot_rom {
+		Some(path) => path,
+		None => return Ok(Cpu::new()),
+	};
+	let boot_rom =
+		std::fs::read(path).map_err(|err| format!("Can't read the boot ROM at {}: {err}", path.display()))?;
+	let boot_rom: [u8; BOOTSTRAP_RAM_SIZE] = boot_rom
+		.try_into()
+		.map_err(|_| format!("The boot ROM should be {BOOTSTRAP_RAM_SIZE} bytes"))?;
+
+	Ok(Cpu::with_boot_rom(boot_rom))
 }
 
-pub fn main() -> Result<(), String> {
-	let mut cpu = Cpu::new();
+pub fn main() {
+	let profile = std::env::args().any(|arg| arg == "--profile");
+	let settings_path = settings_path();
+	// Settings that failed to load are left alone on exit, they may come from a newer version
+	let (settings, loaded) = match Settings::load(&settings_path) {
+		Ok(settings) => (settings, true),
+		Err(err) => {
+			eprintln!(
+				"Can't read the settings at {}, using the defaults: {err}",
+				settings_path.display()
+			);
+			(Settings::default(), false)
+		}
+	};
+
+	let mut cpu = boot_cpu(&settings).unwrap_or_else(|err| {
+		eprintln!("{err}, using the embedded boot ROM");
+		Cpu::new()
+	});
+	cpu.set_trace_capacity(Some(CRASH_TRACE_LENGTH));
+	cpu.set_call_stack_depth(Some(CALL_STACK_DEPTH));
+	cpu.set_opcode_profiling(profile);
+
+	let result = run(&mut cpu, &settings);
+	if profile {
+		print_profile(&cpu);
+	}
+	if loaded {
+		if let Err(err) = settings.save(&settings_path) {
+			eprintln!("Can't save the settings at {}: {err}", settings_path.display());
+		}
+	}
+	if let Err(err) = result {
+		eprintln!("{err}");
+		std::process::exit(1);
+	}
+}
 
-	let sdl_context = sdl2::init()?;
-	let video_subsystem = sdl_context.video()?;
+fn run(cpu: &mut Cpu, settings: &Settings) -> Result<(), FrontendError> {
+	let sdl_context = sdl2::init().map_err(FrontendError::SdlInit)?;
+	let video_subsystem = sdl_context.video().map_err(FrontendError::Video)?;
 
-	let window = video_subsystem
-		.window("rust-sdl2 demo: Video", 800, 600)
+	let mut window = video_subsystem
+		.window(
+			"corrosion",
+			SCREEN_WIDTH * settings.scale,
+			SCREEN_HEIGHT * settings.scale,
+		)
 		.position_centered()
 		.opengl()
 		.build()
-		.map_err(|e| e.to_string())?;
+		.map_err(|e| FrontendError::Window(e.to_string()))?;
+
+	let mut icon = icon_rgba(&ICON, &settings.palette);
+	match Surface::from_data(&mut icon, ICON_SIZE, ICON_SIZE, ICON_SIZE * 4, PixelFormatEnum::RGBA32) {
+		Ok(icon) => window.set_icon(icon),
+		Err(err) => eprintln!("Can't create the window icon: {err}"),
+	}
 
-	let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+	let mut canvas = window
+		.into_canvas()
+		.build()
+		.map_err(|e| FrontendError::Canvas(e.to_string()))?;
 
 	canvas.set_draw_color(Color::RGB(255, 0, 0));
 	canvas.clear();
 	canvas.present();
-	let mut event_pump = sdl_context.event_pump()?;
-
-	let mut running = true;
+	let mut event_pump = sdl_context.event_pump().map_err(FrontendError::EventPump)?;
 
-	while running {
+	let frame_duration = Duration::from_secs(1) * 100 / (FRAMES_PER_SECOND * settings.speed.max(1));
+	let mut pacer = FramePacer::new(SystemClock::new(), frame_duration);
+	// Cycles the last frame ran past its budget, taken off the next one
+	let mut overshoot = 0;
+	loop {
 		for event in event_pump.poll_iter() {
 			match event {
 				Event::Quit { .. }
 				| Event::KeyDown {
 					keycode: Some(Keycode::Escape),
 					..
-				} => running = false,
+				} => return Ok(()),
+				Event::KeyDown {
+					keycode: Some(keycode),
+					repeat: false,
+					..
+				} => {
+					if let Some(button) = settings.button_for_key(&keycode.name()) {
+						cpu.set_button(button, true);
+					}
+				}
+				Event::KeyUp {
+					keycode: Some(keycode), ..
+				} => {
+					if let Some(button) = settings.button_for_key(&keycode.name()) {
+						cpu.set_button(button, false);
+					}
+				}
 				_ => {}
 			}
 		}
 
 		canvas.clear();
 		canvas.present();
-		std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 30));
+		pacer.wait_for_next_frame();
 
-		let execution_result = update_cpu(&mut cpu);
-		if let Err(err) = execution_result {
-			println!("{err}");
-			running = false;
+		match cpu.run_cycles(CYCLES_PER_FRAME - overshoot) {
+			Ok(cycles) => overshoot = cycles,
+			Err(err) => {
+				for event in cpu.trace_buffer() {
+					eprintln!("{event}");
+				}
+				eprintln!("Call stack, innermost first:");
+				for frame in cpu.call_stack().iter().rev() {
+					eprintln!(
+						"  {:#06X} called from {:#06X} (SP {:#06X})",
+						frame.to_address, frame.from_pc, frame.sp_at_call
+					);
+				}
+				return Err(FrontendError::Execution(err));
+			}
 		}
 	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn icon_conversion() {
+		let mut icon = [0; ICON_SIZE as usize];
+		// Shades 1, 2 and 3 in the first three pixels of the second row, the last pixel dark
+		icon[1] = 0x6400_0000_0000_0003;
+		let palette = [0x000000, 0x112233, 0x445566, 0x778899];
+
+		let rgba = icon_rgba(&icon, &palette);
+		assert_eq!(rgba.len(), 32 * 32 * 4);
+		assert!(rgba[..32 * 4].iter().all(|&byte| byte == 0));
 
-	Ok(())
+		let second_row = &rgba[32 * 4..64 * 4];
+		assert_eq!(
+			second_row[..16],
+			[0x11, 0x22, 0x33, 0xFF, 0x44, 0x55, 0x66, 0xFF, 0x77, 0x88, 0x99, 0xFF, 0, 0, 0, 0]
+		);
+		assert_eq!(second_row[31 * 4..], [0x77, 0x88, 0x99, 0xFF]);
+	}
+
+	#[test]
+	fn icon_has_every_shade() {
+		let rgba = icon_rgba(&ICON, &Settings::default().palette);
+		let pixels: Vec<_> = rgba.chunks(4).collect();
+
+		assert_eq!(pixels[0], [0, 0, 0, 0]);
+		for shade in &Settings::default().palette[1..] {
+			let [_, red, green, blue] = shade.to_be_bytes();
+			assert!(pixels.contains(&[red, green, blue, 0xFF].as_slice()));
+		}
+	}
 }
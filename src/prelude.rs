@@ -0,0 +1,204 @@
+//! The intended public API. Anything a frontend needs should be re-exported here, changes to this list are API
+//! changes. The example names every export through the glob import alone, so it stops compiling if one goes missing;
+//! the test below compares the list against the source, so an addition fails too.
+//!
+//! ```
+//! use std::any::type_name;
+//!
+//! use corrosion::prelude::*;
+//!
+//! let _ = [
+//!     type_name::<AccumulatorAddress>(),
+//!     type_name::<AluOperation>(),
+//!     type_name::<BitFlags>(),
+//!     type_name::<BreakReason>(),
+//!     type_name::<Button>(),
+//!     type_name::<ByteOperand>(),
+//!     type_name::<CallFrame>(),
+//!     type_name::<Condition>(),
+//!     type_name::<CoverageReport>(),
+//!     type_name::<Cpu>(),
+//!     type_name::<Debugger>(),
+//!     type_name::<DecodeError>(),
+//!     type_name::<DecodedInstruction>(),
+//!     type_name::<DoctorLogger<Vec<u8>>>(),
+//!     type_name::<DoubleOperand>(),
+//!     type_name::<DoubleRegisters>(),
+//!     type_name::<ExecutionError>(),
+//!     type_name::<Framebuffer>(),
+//!     type_name::<FrameConfidence>(),
+//!     type_name::<FramePacer<SystemClock>>(),
+//!     type_name::<HookAction>(),
+//!     type_name::<HookWrite>(),
+//!     type_name::<ImmediateKind>(),
+//!     type_name::<InstructionSpec>(),
+//!     type_name::<Interrupt>(),
+//!     type_name::<MemoryEditError>(),
+//!     type_name::<OpcodeHookHandler>(),
+//!     type_name::<OpcodeInfo>(),
+//!     type_name::<OperandKind>(),
+//!     type_name::<RamError>(),
+//!     type_name::<RunError>(),
+//!     type_name::<ShiftOperation>(),
+//!     type_name::<SingleRegisters>(),
+//!     type_name::<StackFrame>(),
+//!     type_name::<StackViolation>(),
+//!     type_name::<StepOutcome>(),
+//!     type_name::<SystemClock>(),
+//!     type_name::<TestPortEvent>(),
+//!     type_name::<TileMapAddressMode>(),
+//!     type_name::<TraceEvent>(),
+//!     type_name::<Viewport>(),
+//!     type_name::<WatchHit>(),
+//! ];
+//!
+//! fn traits<E: Executable, I: Instruction, C: HostClock>() {}
+//!
+//! let _ = (
+//!     decode_from_slice,
+//!     disassemble,
+//!     doctor_line,
+//!     encode,
+//!     fetch_and_decode,
+//!     opcode_info,
+//!     opcode_table,
+//!     peek_and_decode,
+//!     prefixed_opcode_table,
+//!     walk_stack,
+//! );
+//! let _ = (
+//!     BOOTSTRAP_RAM_SIZE,
+//!     CYCLES_PER_FRAME,
+//!     OPCODE_COUNTS_SIZE,
+//!     TEST_PORT_ADDRESS,
+//!     TILE_MAP_PIXELS,
+//! );
+//!
+//! assert_eq!(disassemble(false, 0x2A, &[]), "LD A,(HL+)");
+//! ```
+
+pub use crate::coverage::CoverageReport;
+pub use crate::debugger::{walk_stack, BreakReason, Debugger, FrameConfidence, StackFrame};
+pub use crate::decoder::hooks::{HookAction, HookWrite, OpcodeHookHandler};
+pub use crate::decoder::table::{
+	opcode_info, opcode_table, prefixed_opcode_table, ImmediateKind, OpcodeInfo, OperandKind,
+};
+pub use crate::decoder::{
+	decode_from_slice, disassemble, fetch_and_decode, peek_and_decode, DecodeError, DecodedInstruction,
+};
+pub use crate::doctor_log::{doctor_line, DoctorLogger};
+pub use crate::encoder::{
+	encode, AccumulatorAddress, AluOperation, ByteOperand, Condition, DoubleOperand, InstructionSpec, ShiftOperation,
+};
+pub use crate::hardware::call_stack::CallFrame;
+pub use crate::hardware::cpu::{Cpu, MemoryEditError, RunError, StepOutcome, CYCLES_PER_FRAME, OPCODE_COUNTS_SIZE};
+pub use crate::hardware::interrupts::Interrupt;
+pub use crate::hardware::joypad::Button;
+pub use crate::hardware::ram::{RamError, BOOTSTRAP_RAM_SIZE};
+pub use crate::hardware::register_bank::{BitFlags, DoubleRegisters, SingleRegisters};
+pub use crate::hardware::screen::tile_map::{Framebuffer, TileMapAddressMode, Viewport, TILE_MAP_PIXELS};
+pub use crate::hardware::stack_guard::StackViolation;
+pub use crate::hardware::test_port::{TestPortEvent, TEST_PORT_ADDRESS};
+pub use crate::hardware::trace::TraceEvent;
+pub use crate::hardware::watchpoints::WatchHit;
+pub use crate::host_clock::{FramePacer, HostClock, SystemClock};
+pub use crate::instructions::{Executable, ExecutionError, Instruction};
+
+#[cfg(test)]
+mod tests {
+	const EXPECTED_EXPORTS: [&str; 60] = [
+		"BOOTSTRAP_RAM_SIZE",
+		"CYCLES_PER_FRAME",
+		"OPCODE_COUNTS_SIZE",
+		"TEST_PORT_ADDRESS",
+		"TILE_MAP_PIXELS",
+		"AccumulatorAddress",
+		"AluOperation",
+		"BitFlags",
+		"BreakReason",
+		"Button",
+		"CallFrame",
+		"ByteOperand",
+		"Condition",
+		"CoverageReport",
+		"Cpu",
+		"Debugger",
+		"DecodeError",
+		"DecodedInstruction",
+		"DoctorLogger",
+		"DoubleOperand",
+		"DoubleRegisters",
+		"Executable",
+		"ExecutionError",
+		"Framebuffer",
+		"FrameConfidence",
+		"FramePacer",
+		"HookAction",
+		"HookWrite",
+		"HostClock",
+		"ImmediateKind",
+		"Instruction",
+		"InstructionSpec",
+		"Interrupt",
+		"MemoryEditError",
+		"OpcodeInfo",
+		"OpcodeHookHandler",
+		"OperandKind",
+		"RamError",
+		"RunError",
+		"ShiftOperation",
+		"SingleRegisters",
+		"StackFrame",
+		"StackViolation",
+		"StepOutcome",
+		"SystemClock",
+		"TestPortEvent",
+		"TileMapAddressMode",
+		"TraceEvent",
+		"Viewport",
+		"WatchHit",
+		"decode_from_slice",
+		"disassemble",
+		"doctor_line",
+		"encode",
+		"fetch_and_decode",
+		"opcode_info",
+		"opcode_table",
+		"peek_and_decode",
+		"prefixed_opcode_table",
+		"walk_stack",
+	];
+
+	// Names brought in by every `pub use` before the tests module
+	fn exported_names(source: &str) -> Vec<String> {
+		let exports: String = source
+			.split("#[cfg(test)]")
+			.next()
+			.unwrap()
+			.lines()
+			.filter(|line| !line.starts_with("//"))
+			.collect();
+
+		exports
+			.split(';')
+			.filter_map(|statement| statement.trim().strip_prefix("pub use "))
+			.flat_map(|path| match path.split_once('{') {
+				Some((_, names)) => names.trim_end_matches('}').split(',').collect::<Vec<_>>(),
+				None => vec![path.rsplit("::").next().unwrap()],
+			})
+			.map(|name| name.trim().to_string())
+			.filter(|name| !name.is_empty())
+			.collect()
+	}
+
+	#[test]
+	fn prelude_contents() {
+		let mut exported = exported_names(include_str!("prelude.rs"));
+		exported.sort();
+
+		let mut expected: Vec<_> = EXPECTED_EXPORTS.iter().map(|name| name.to_string()).collect();
+		expected.sort();
+
+		assert_eq!(exported, expected);
+	}
+}
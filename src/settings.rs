@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use corrosion::prelude::Button;
+
+// User settings for the SDL frontend, stored as `key=value` lines. Parsing is tolerant: keys it doesn't know are kept
+// and written back, values it can't read fall back to their defaults. Files from a newer version are refused, as their
+// keys may mean something else and saving would write them back under an older version.
+
+/// Where the settings are kept unless told otherwise
+pub(crate) const DEFAULT_SETTINGS_PATH: &str = "corrosion.cfg";
+
+/// Bumped when a key changes meaning, so `parse` can migrate older files
+const SETTINGS_VERSION: u32 = 1;
+
+/// The file was written by a newer version
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct NewerVersion(pub(crate) u32);
+
+impl Display for NewerVersion {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"settings version {} is newer than the supported version {SETTINGS_VERSION}",
+			self.0
+		)
+	}
+}
+
+impl Error for NewerVersion {}
+
+const BUTTONS: [(Button, &str); 8] = [
+	(Button::Right, "right"),
+	(Button::Left, "left"),
+	(Button::Up, "up"),
+	(Button::Down, "down"),
+	(Button::A, "a"),
+	(Button::B, "b"),
+	(Button::Select, "select"),
+	(Button::Start, "start"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Settings {
+	/// Shades from lightest to darkest, as 0xRRGGBB
+	pub(crate) palette: [u32; 4],
+	/// Window pixels per Game Boy pixel
+	pub(crate) scale: u32,
+	/// Emulation speed in percent of the real hardware
+	pub(crate) speed: u32,
+	/// Frames emulated but not drawn after each drawn one
+	pub(crate) frame_skip: u32,
+	/// Frontend key name for each button, in the order of `Button`
+	pub(crate) key_bindings: [String; 8],
+	pub(crate) boot_rom: Option<PathBuf>,
+	pub(crate) last_rom_directory: Option<PathBuf>,
+	/// Keys this version doesn't know, in file order
+	unknown: Vec<(String, String)>,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			palette: [0xE0F8D0, 0x88C070, 0x346856, 0x081820],
+			scale: 4,
+			speed: 100,
+			frame_skip: 0,
+			key_bindings: ["Right", "Left", "Up", "Down", "X", "Z", "Backspace", "Return"].map(String::from),
+			boot_rom: None,
+			last_rom_directory: None,
+			unknown: Vec::new(),
+		}
+	}
+}
+
+impl Settings {
+	/// Reads settings from `path`, or the defaults if there's no file there yet
+	pub(crate) fn load(path: &Path) -> std::io::Result<Self> {
+		match std::fs::read_to_string(path) {
+			Ok(contents) => {
+				Self::parse(&contents).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+			}
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+			Err(err) => Err(err),
+		}
+	}
+
+	pub(crate) fn save(&self, path: &Path) -> std::io::Result<()> {
+		std::fs::write(path, self.to_string())
+	}
+
+	pub(crate) fn parse(contents: &str) -> Result<Self, NewerVersion> {
+		let mut settings = Self::default();
+
+		for line in contents.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let (key, value) = match line.split_once('=') {
+				Some((key, value)) => (key.trim(), value.trim()),
+				None => continue,
+			};
+
+			if key == "version" {
+				// Only version 1 exists, so there's nothing to migrate yet and the version is rewritten on save
+				match value.parse() {
+					Ok(version) if version > SETTINGS_VERSION => return Err(NewerVersion(version)),
+					_ => continue,
+				}
+			}
+			if !settings.apply(key, value) {
+				settings.unknown.push((key.to_string(), value.to_string()));
+			}
+		}
+
+		Ok(settings)
+	}
+
+	/// Button bound to a frontend key name
+	pub(crate) fn button_for_key(&self, key: &str) -> Option<Button> {
+		BUTTONS
+			.iter()
+			.zip(&self.key_bindings)
+			.find(|(_, bound)| bound.as_str() == key)
+			.map(|((button, _), _)| *button)
+	}
+
+	// Whether the key is known, invalid values leave the default in place
+	fn apply(&mut self, key: &str, value: &str) -> bool {
+		match key {
+			"palette" => {
+				let shades: Option<Vec<u32>> = value
+					.split(',')
+					.map(|shade| u32::from_str_radix(shade.trim(), 16).ok())
+					.collect();
+				if let Some(Ok(palette)) = shades.map(<[u32; 4]>::try_from) {
+					self.palette = palette;
+				}
+			}
+			"scale" => parse_into(value, &mut self.scale),
+			"speed" => parse_into(value, &mut self.speed),
+			"frame_skip" => parse_into(value, &mut self.frame_skip),
+			"boot_rom" => self.boot_rom = parse_path(value),
+			"last_rom_directory" => self.last_rom_directory = parse_path(value),
+			_ => match key.strip_prefix("key.").and_then(button_index) {
+				Some(index) => self.key_bindings[index] = value.to_string(),
+				None => return false,
+			},
+		}
+
+		true
+	}
+}
+
+impl Display for Settings {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let palette: Vec<_> = self.palette.iter().map(|shade| format!("{shade:06X}")).collect();
+		let path = |path: &Option<PathBuf>| path.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+
+		writeln!(f, "version={SETTINGS_VERSION}")?;
+		writeln!(f, "palette={}", palette.join(","))?;
+		writeln!(f, "scale={}", self.scale)?;
+		writeln!(f, "speed={}", self.speed)?;
+		writeln!(f, "frame_skip={}", self.frame_skip)?;
+		writeln!(f, "boot_rom={}", path(&self.boot_rom))?;
+		writeln!(f, "last_rom_directory={}", path(&self.last_rom_directory))?;
+		for ((_, name), key) in BUTTONS.iter().zip(&self.key_bindings) {
+			writeln!(f, "key.{name}={key}")?;
+		}
+		for (key, value) in &self.unknown {
+			writeln!(f, "{key}={value}")?;
+		}
+
+		Ok(())
+	}
+}
+
+fn parse_into(value: &str, setting: &mut u32) {
+	if let Ok(value) = value.parse() {
+		*setting = value;
+	}
+}
+
+fn parse_path(value: &str) -> Option<PathBuf> {
+	(!value.is_empty()).then(|| PathBuf::from(value))
+}
+
+fn button_index(name: &str) -> Option<usize> {
+	BUTTONS.iter().position(|(_, button)| *button == name)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let settings = Settings {
+			palette: [0xFFFFFF, 0xAAAAAA, 0x555555, 0x000000],
+			scale: 2,
+			speed: 200,
+			frame_skip: 1,
+			boot_rom: Some(PathBuf::from("roms/dmg_boot.bin")),
+			..Settings::default()
+		};
+
+		assert_eq!(Settings::parse(&settings.to_string()), Ok(settings));
+	}
+
+	#[test]
+	fn unknown_keys_preserved() {
+		let settings = Settings::parse("version=1\nscale=3\nshader=crt\n").unwrap();
+
+		assert_eq!(settings.scale, 3);
+		assert!(settings.to_string().ends_with("shader=crt\n"));
+		assert_eq!(Settings::parse(&settings.to_string()), Ok(settings));
+	}
+
+	#[test]
+	fn newer_version_refused() {
+		assert_eq!(Settings::parse("version=2\nscale=3\n"), Err(NewerVersion(2)));
+		// Not a version this one could have written either, so there's nothing to protect
+		assert_eq!(Settings::parse("version=one\nscale=3\n").unwrap().scale, 3);
+	}
+
+	#[test]
+	fn missing_and_invalid_values_defaulted() {
+		let settings =
+			Settings::parse("# no version\nspeed=fast\npalette=FFFFFF,000000\nkey.start=Space\nbroken line\n").unwrap();
+		let defaults = Settings::default();
+
+		assert_eq!(settings.speed, defaults.speed);
+		assert_eq!(settings.palette, defaults.palette);
+		assert_eq!(settings.scale, defaults.scale);
+		assert_eq!(settings.boot_rom, None);
+		assert_eq!(settings.button_for_key("Space"), Some(Button::Start));
+		assert_eq!(settings.button_for_key("X"), Some(Button::A));
+		assert_eq!(settings.button_for_key("Return"), None);
+	}
+
+	#[test]
+	fn missing_file() {
+		let path = std::env::temp_dir().join("corrosion-settings-that-do-not-exist.cfg");
+
+		assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+	}
+}
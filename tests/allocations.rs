@@ -0,0 +1,74 @@
+// Counts heap allocations made while executing instructions. The counting allocator replaces the global one for the
+// whole test binary, which is why this lives in its own integration test instead of the library's unit tests.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use corrosion::prelude::{fetch_and_decode, Cpu, DoubleRegisters};
+
+const PROGRAM_START: u16 = 0xC000;
+
+// Counts the allocations made by the current thread only, as tests run in parallel
+struct CountingAllocator;
+
+thread_local! {
+	static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		// The counter may be gone while the thread shuts down
+		let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+	let before = ALLOCATIONS.with(Cell::get);
+	f();
+	ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn instructions_without_allocations() {
+	let instructions: [&[u8]; 6] = [
+		&[0x80],       // add A, B
+		&[0x3C],       // inc A
+		&[0xA0],       // and B
+		&[0xCB, 0x00], // rlc B
+		&[0x22],       // ld (HL+), A
+		&[0x41],       // ld B, C
+	];
+
+	for bytes in instructions {
+		let mut cpu = Cpu::new();
+		for (offset, byte) in bytes.iter().enumerate() {
+			cpu.write_byte(PROGRAM_START + offset as u16, *byte).unwrap();
+		}
+		cpu.set_pc(PROGRAM_START);
+		cpu.write_double_register(DoubleRegisters::HL, PROGRAM_START + 0x100);
+		let instruction = fetch_and_decode(&mut cpu).unwrap();
+
+		let allocations = allocations_during(|| instruction.execute(&mut cpu).map(|_| ()).unwrap());
+		assert_eq!(allocations, 0, "{instruction}");
+	}
+}
+
+#[test]
+fn boxed_instruction_allocates() {
+	// The counter itself must see allocations, or the test above would pass with a broken allocator
+	let mut cpu = Cpu::new();
+	// ld B, C
+	cpu.write_byte(PROGRAM_START, 0x41).unwrap();
+	cpu.set_pc(PROGRAM_START);
+	let instruction = fetch_and_decode(&mut cpu).unwrap();
+
+	assert_eq!(allocations_during(|| drop(instruction.into_boxed())), 1);
+}
@@ -0,0 +1,196 @@
+// Runs the SM83 single step test vectors: one JSON file per opcode, each holding cases with the CPU and RAM state
+// before and after executing a single instruction.
+//
+// Point SINGLE_STEP_TESTS_PATH to the directory with the JSON files and run
+// `cargo test --features single-step-tests --test single_step`. Only the number of bus cycles is compared, not the
+// accesses made on each of them.
+
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use corrosion::prelude::{fetch_and_decode, CoverageReport, Cpu, SingleRegisters, OPCODE_COUNTS_SIZE};
+
+const TESTS_PATH_VAR: &str = "SINGLE_STEP_TESTS_PATH";
+
+// The vectors model the opcode fetch overlapping the previous instruction, so PC is one past the opcode
+const PREFETCH_OFFSET: u16 = 1;
+
+const IE_ADDRESS: u16 = 0xFFFF;
+
+// Failures printed per file, the rest are only counted
+const REPORTED_FAILURES: usize = 5;
+
+// Every legal opcode has a file in the full corpus, so anything never executed is missing from the directory
+const COVERAGE_THRESHOLD: f64 = 100.0;
+
+#[derive(Deserialize)]
+struct TestCase {
+	name: String,
+	initial: CpuState,
+	#[serde(rename = "final")]
+	final_: CpuState,
+	cycles: Vec<serde_json::Value>,
+}
+
+// Each bus cycle listed in the vectors is a machine cycle of 4 clock cycles
+const CLOCKS_PER_BUS_CYCLE: u16 = 4;
+
+#[derive(Deserialize)]
+struct CpuState {
+	pc: u16,
+	sp: u16,
+	a: u8,
+	b: u8,
+	c: u8,
+	d: u8,
+	e: u8,
+	f: u8,
+	h: u8,
+	l: u8,
+	ime: u8,
+	ie: Option<u8>,
+	ram: Vec<(u16, u8)>,
+}
+
+impl CpuState {
+	fn registers(&self) -> [(SingleRegisters, u8); 8] {
+		[
+			(SingleRegisters::A, self.a),
+			(SingleRegisters::B, self.b),
+			(SingleRegisters::C, self.c),
+			(SingleRegisters::D, self.d),
+			(SingleRegisters::E, self.e),
+			(SingleRegisters::F, self.f),
+			(SingleRegisters::H, self.h),
+			(SingleRegisters::L, self.l),
+		]
+	}
+
+	fn load(&self) -> Cpu {
+		let mut cpu = Cpu::with_test_bus();
+
+		cpu.set_pc(self.pc.wrapping_sub(PREFETCH_OFFSET));
+		cpu.set_sp(self.sp);
+		for (register, value) in self.registers() {
+			cpu.write_register(register, value);
+		}
+		cpu.set_ime(self.ime != 0);
+		if let Some(ie) = self.ie {
+			cpu.write_byte(IE_ADDRESS, ie).expect("Write IE");
+		}
+		for &(address, value) in &self.ram {
+			cpu.write_byte(address, value).expect("Write to test bus");
+		}
+
+		cpu
+	}
+
+	fn diff(&self, cpu: &Cpu) -> Vec<String> {
+		let mut diffs = Vec::new();
+		let mut compare = |field: &str, expected: u16, actual: u16| {
+			if expected != actual {
+				diffs.push(format!("{field}: expected {expected:#06X}, got {actual:#06X}"));
+			}
+		};
+
+		compare("PC", self.pc, cpu.current_pc().wrapping_add(PREFETCH_OFFSET));
+		compare("SP", self.sp, cpu.current_sp());
+		for (register, value) in self.registers() {
+			compare(&register.to_string(), value.into(), cpu.read_register(register).into());
+		}
+		compare("IME", self.ime.into(), cpu.ime().into());
+		if let Some(ie) = self.ie {
+			compare("IE", ie.into(), read(cpu, IE_ADDRESS).into());
+		}
+		for &(address, value) in &self.ram {
+			compare(&format!("({address:#06X})"), value.into(), read(cpu, address).into());
+		}
+
+		diffs
+	}
+}
+
+fn read(cpu: &Cpu, address: u16) -> u8 {
+	cpu.read_byte(address).expect("Read from test bus")
+}
+
+fn run_case(case: &TestCase, counts: &mut [u64; OPCODE_COUNTS_SIZE]) -> Vec<String> {
+	let mut cpu = case.initial.load();
+	cpu.set_opcode_profiling(true);
+
+	let result = catch_unwind(AssertUnwindSafe(|| {
+		let instruction = fetch_and_decode(&mut cpu)?;
+		instruction.execute(&mut cpu)
+	}));
+	for (total, count) in counts.iter_mut().zip(cpu.opcode_counts()) {
+		*total += count;
+	}
+
+	match result {
+		Ok(Ok(clocks)) => {
+			let mut diffs = case.final_.diff(&cpu);
+			let expected = case.cycles.len() as u16 * CLOCKS_PER_BUS_CYCLE;
+			if u16::from(clocks) != expected {
+				diffs.push(format!("cycles: expected {expected}, got {clocks}"));
+			}
+			diffs
+		}
+		Ok(Err(err)) => vec![format!("execution error: {err}")],
+		Err(_) => vec!["panicked".to_string()],
+	}
+}
+
+fn run_file(path: &Path, counts: &mut [u64; OPCODE_COUNTS_SIZE]) -> usize {
+	let json = fs::read_to_string(path).expect("Read test vector file");
+	let cases: Vec<TestCase> = serde_json::from_str(&json).expect("Parse test vector file");
+
+	let mut failures = 0;
+	for (index, case) in cases.iter().enumerate() {
+		let diffs = run_case(case, counts);
+		if diffs.is_empty() {
+			continue;
+		}
+
+		if failures < REPORTED_FAILURES {
+			eprintln!("{} case {index} ({}):", path.display(), case.name);
+			for diff in diffs {
+				eprintln!("\t{diff}");
+			}
+		}
+		failures += 1;
+	}
+
+	if failures > 0 {
+		eprintln!("{}: {failures}/{} cases failed", path.display(), cases.len());
+	}
+
+	failures
+}
+
+#[test]
+fn single_step_vectors() {
+	let Some(directory) = std::env::var_os(TESTS_PATH_VAR) else {
+		eprintln!("{TESTS_PATH_VAR} is not set, skipping single step tests");
+		return;
+	};
+
+	let mut files: Vec<PathBuf> = fs::read_dir(directory)
+		.expect("Read test vector directory")
+		.map(|entry| entry.expect("Read test vector directory entry").path())
+		.filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+		.collect();
+	files.sort();
+
+	let mut counts = [0; OPCODE_COUNTS_SIZE];
+	let failures: usize = files.iter().map(|path| run_file(path, &mut counts)).sum();
+	let coverage = CoverageReport::new(&counts);
+
+	assert_eq!(failures, 0, "{failures} single step cases failed");
+	assert!(
+		coverage.meets_threshold(COVERAGE_THRESHOLD),
+		"Opcodes not covered by the test vectors:\n{coverage}"
+	);
+}